@@ -0,0 +1,789 @@
+use crate::calendar::{period, TradingCalendar};
+use chrono::{DateTime, Duration, DurationRound, NaiveTime, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// A single trade.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Copy)]
+pub struct Tick {
+    pub time: DateTime<Utc>,
+    pub price: f64,
+    pub volume: f64,
+}
+
+/// An OHLCV bar. For time bars `start`/`end` are the bucket bounds, for every
+/// other kind they are the times of the first and last trade in the bar.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct Bar {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+    pub ticks: usize,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BarKind {
+    /// Fixed wall-clock buckets of any length.
+    Time(Duration),
+    /// A bar every `n` trades.
+    Tick(usize),
+    /// A bar every time this much volume has traded.
+    Volume(f64),
+    /// A bar every time this much notional (`price * volume`) has traded.
+    Dollar(f64),
+    /// Renko bricks of the given size.
+    Renko(f64),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum BarEvent {
+    /// A bar has been completed and will only change again through `Amended`.
+    Closed(Bar),
+    /// A late tick landed in an already closed bar.
+    Amended(Bar),
+}
+
+#[derive(Debug, Clone)]
+struct Building {
+    bar: Bar,
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    activity: f64,
+}
+
+impl Building {
+    fn new(tick: &Tick, start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Building {
+            bar: Bar {
+                start,
+                end,
+                open: tick.price,
+                high: tick.price,
+                low: tick.price,
+                close: tick.price,
+                volume: tick.volume,
+                ticks: 1,
+            },
+            first: tick.time,
+            last: tick.time,
+            activity: 0.0,
+        }
+    }
+
+    fn add(&mut self, tick: &Tick) {
+        let bar = &mut self.bar;
+        if tick.time < self.first {
+            bar.open = tick.price;
+            self.first = tick.time;
+        }
+        if tick.time >= self.last {
+            bar.close = tick.price;
+            self.last = tick.time;
+        }
+        bar.high = bar.high.max(tick.price);
+        bar.low = bar.low.min(tick.price);
+        bar.volume += tick.volume;
+        bar.ticks += 1;
+    }
+}
+
+/// Turns a stream of ticks into bars. The same aggregator is used for live
+/// ticks and for history, so both always produce identical bars.
+///
+/// Ticks older than the latest seen tick minus the allowed lateness are
+/// dropped. A late tick within the allowance amends the time bar covering it;
+/// for activity-based kinds it is merged into the bar that is still open.
+#[derive(Debug, Clone)]
+pub struct BarAggregator {
+    kind: BarKind,
+    lateness: Duration,
+    session_gap: Option<Duration>,
+    calendar: Option<TradingCalendar>,
+    current: Option<Building>,
+    closed: Vec<Building>,
+    watermark: Option<DateTime<Utc>>,
+    renko: Option<(f64, f64)>,
+}
+
+impl BarAggregator {
+    pub fn new(kind: BarKind) -> Self {
+        BarAggregator {
+            kind,
+            lateness: Duration::zero(),
+            session_gap: None,
+            calendar: None,
+            current: None,
+            closed: Vec::new(),
+            watermark: None,
+            renko: None,
+        }
+    }
+
+    /// How far behind the newest tick a trade may arrive and still be counted.
+    pub fn with_lateness(mut self, lateness: Duration) -> Self {
+        self.lateness = lateness;
+        self
+    }
+
+    /// Closes the open activity bar whenever two consecutive ticks are further
+    /// apart than `gap`, so it never straddles an overnight or lunch break.
+    pub fn with_session_gap(mut self, gap: Duration) -> Self {
+        self.session_gap = Some(gap);
+        self
+    }
+
+    /// Aligns time bars to the exchange's session open and calendar, and
+    /// closes the open activity bar whenever a new trading day starts.
+    pub fn with_calendar(mut self, calendar: TradingCalendar) -> Self {
+        self.calendar = Some(calendar);
+        self
+    }
+
+    /// The bar currently being built, if any.
+    pub fn current(&self) -> Option<&Bar> {
+        self.current.as_ref().map(|b| &b.bar)
+    }
+
+    pub fn push(&mut self, tick: Tick) -> Vec<BarEvent> {
+        let mut events = Vec::new();
+
+        if let Some(watermark) = self.watermark {
+            if tick.time < watermark - self.lateness {
+                return events;
+            }
+            if tick.time >= watermark {
+                let gap = self
+                    .session_gap
+                    .is_some_and(|gap| tick.time - watermark > gap);
+                let new_session = self
+                    .calendar
+                    .as_ref()
+                    .is_some_and(|c| c.local_date(tick.time) != c.local_date(watermark));
+                // Time bars already end at their bucket boundary.
+                let time_bars = matches!(self.kind, BarKind::Time(_));
+                if !time_bars && (gap || new_session) {
+                    self.close_current(&mut events);
+                }
+            }
+        }
+        self.watermark = Some(self.watermark.map_or(tick.time, |w| w.max(tick.time)));
+
+        match self.kind {
+            BarKind::Time(interval) => self.push_time(tick, interval, &mut events),
+            BarKind::Tick(n) => self.push_activity(tick, 1.0, n as f64, &mut events),
+            BarKind::Volume(v) => self.push_activity(tick, tick.volume, v, &mut events),
+            BarKind::Dollar(d) => {
+                self.push_activity(tick, tick.price * tick.volume, d, &mut events)
+            }
+            BarKind::Renko(size) => self.push_renko(tick, size, &mut events),
+        }
+
+        self.prune();
+        events
+    }
+
+    /// Feeds an already aggregated bar, e.g. a provider candle, through the
+    /// same logic as live ticks by replaying it as open, high/low and close.
+    pub fn push_bar(&mut self, bar: &Bar) -> Vec<BarEvent> {
+        let span = (bar.end - bar.start).max(Duration::zero());
+        let step = span / 4;
+        let (first, second) = if bar.close >= bar.open {
+            (bar.low, bar.high)
+        } else {
+            (bar.high, bar.low)
+        };
+        let volume = bar.volume / 4.0;
+
+        [bar.open, first, second, bar.close]
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, price)| {
+                self.push(Tick {
+                    time: bar.start + step * i as i32,
+                    price,
+                    volume,
+                })
+            })
+            .collect()
+    }
+
+    fn push_time(&mut self, tick: Tick, interval: Duration, events: &mut Vec<BarEvent>) {
+        let (start, end) = self.bucket(tick.time, interval);
+
+        match &mut self.current {
+            Some(current) if current.bar.start == start => {
+                current.add(&tick);
+                return;
+            }
+            Some(current) if start < current.bar.start => {
+                if let Some(closed) = self.closed.iter_mut().find(|b| b.bar.start == start) {
+                    closed.add(&tick);
+                    events.push(BarEvent::Amended(closed.bar.clone()));
+                } else {
+                    // The bucket never had a trade of its own; emit it as a
+                    // closed bar so consumers can slot it into place.
+                    let late = Building::new(&tick, start, end);
+                    events.push(BarEvent::Closed(late.bar.clone()));
+                    self.closed.push(late);
+                    self.closed.sort_by_key(|b| b.bar.start);
+                }
+                return;
+            }
+            _ => (),
+        }
+
+        self.close_current(events);
+        self.current = Some(Building::new(&tick, start, end));
+    }
+
+    /// Bounds of the time bar containing `t`. Bars of a day or longer follow
+    /// calendar days, weeks and months, in the exchange's time zone when a
+    /// calendar is set and in UTC otherwise.
+    fn bucket(&self, t: DateTime<Utc>, interval: Duration) -> (DateTime<Utc>, DateTime<Utc>) {
+        if interval < Duration::days(1) {
+            let start = match &self.calendar {
+                Some(calendar) => calendar.align(t, interval),
+                None => t.duration_trunc(interval).unwrap_or(t),
+            };
+            return (start, start + interval);
+        }
+
+        match &self.calendar {
+            Some(calendar) => {
+                let (_, next) = period(calendar.local_date(t), interval);
+                (
+                    calendar.align(t, interval),
+                    calendar.at(next, NaiveTime::MIN),
+                )
+            }
+            None => {
+                let (first, next) = period(t.date_naive(), interval);
+                (
+                    first.and_time(NaiveTime::MIN).and_utc(),
+                    next.and_time(NaiveTime::MIN).and_utc(),
+                )
+            }
+        }
+    }
+
+    fn push_activity(
+        &mut self,
+        tick: Tick,
+        activity: f64,
+        threshold: f64,
+        events: &mut Vec<BarEvent>,
+    ) {
+        match &mut self.current {
+            Some(current) => {
+                current.add(&tick);
+                current.bar.end = current.last;
+                current.bar.start = current.first;
+                current.activity += activity;
+            }
+            None => {
+                let mut building = Building::new(&tick, tick.time, tick.time);
+                building.activity = activity;
+                self.current = Some(building);
+            }
+        }
+
+        if self
+            .current
+            .as_ref()
+            .is_some_and(|c| c.activity >= threshold)
+        {
+            self.close_current(events);
+        }
+    }
+
+    fn push_renko(&mut self, tick: Tick, size: f64, events: &mut Vec<BarEvent>) {
+        match &mut self.current {
+            Some(current) => {
+                current.add(&tick);
+                current.bar.end = current.last;
+            }
+            None => self.current = Some(Building::new(&tick, tick.time, tick.time)),
+        }
+
+        // (open, close) of the last brick; the first trade anchors a
+        // zero-height brick.
+        let (mut open, mut close) = *self.renko.get_or_insert((tick.price, tick.price));
+        if size <= 0.0 {
+            return;
+        }
+
+        // Continuing a trend needs one brick's move from the top (or bottom)
+        // of the last brick, a reversal needs two from its close.
+        loop {
+            let (next_open, next_close) = if tick.price >= close.max(open) + size {
+                (close.max(open), close.max(open) + size)
+            } else if tick.price <= close.min(open) - size {
+                (close.min(open), close.min(open) - size)
+            } else {
+                break;
+            };
+
+            let Some(mut brick) = self.current.take() else {
+                break;
+            };
+            brick.bar.open = next_open;
+            brick.bar.close = next_close;
+            brick.bar.high = next_open.max(next_close);
+            brick.bar.low = next_open.min(next_close);
+            events.push(BarEvent::Closed(brick.bar.clone()));
+            self.closed.push(brick);
+
+            self.current = Some(Building {
+                bar: Bar {
+                    start: tick.time,
+                    end: tick.time,
+                    open: next_close,
+                    high: next_close,
+                    low: next_close,
+                    close: next_close,
+                    volume: 0.0,
+                    ticks: 0,
+                },
+                first: tick.time,
+                last: tick.time,
+                activity: 0.0,
+            });
+            (open, close) = (next_open, next_close);
+            self.renko = Some((open, close));
+        }
+    }
+
+    fn close_current(&mut self, events: &mut Vec<BarEvent>) {
+        if let Some(current) = self.current.take() {
+            if let BarKind::Renko(_) = self.kind {
+                // An unfinished brick is not a brick.
+                self.current = Some(current);
+                return;
+            }
+            events.push(BarEvent::Closed(current.bar.clone()));
+            self.closed.push(current);
+        }
+    }
+
+    fn prune(&mut self) {
+        let Some(watermark) = self.watermark else {
+            return;
+        };
+        let horizon = watermark - self.lateness;
+        self.closed.retain(|b| b.bar.end > horizon);
+    }
+}
+
+/// Bars ready for charting: every closed bar plus the one still forming.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone, Default)]
+pub struct BarSeries {
+    pub bars: Vec<Bar>,
+    pub partial: Option<Bar>,
+}
+
+impl BarSeries {
+    pub fn apply(&mut self, events: Vec<BarEvent>) {
+        for event in events {
+            match event {
+                BarEvent::Closed(bar) => {
+                    let at = self.bars.partition_point(|b| b.start <= bar.start);
+                    self.bars.insert(at, bar);
+                }
+                BarEvent::Amended(bar) => {
+                    if let Some(existing) =
+                        self.bars.iter_mut().rev().find(|b| b.start == bar.start)
+                    {
+                        *existing = bar;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Applies a live tick and refreshes the forming bar.
+    pub fn push(&mut self, aggregator: &mut BarAggregator, tick: Tick) {
+        let events = aggregator.push(tick);
+        self.apply(events);
+        self.partial = aggregator.current().cloned();
+    }
+
+    /// The series in the `{ name: [{ period, v }] }` shape `ChartView` plots.
+    pub fn to_chart_series(&self) -> Map<String, Value> {
+        let bars = self.bars.iter().chain(self.partial.iter());
+        let column = |f: fn(&Bar) -> f64| {
+            Value::Array(
+                bars.clone()
+                    .map(|b| json!({ "period": b.start.to_rfc3339(), "v": f(b) }))
+                    .collect(),
+            )
+        };
+
+        let mut series = Map::new();
+        series.insert("open".into(), column(|b| b.open));
+        series.insert("high".into(), column(|b| b.high));
+        series.insert("low".into(), column(|b| b.low));
+        series.insert("close".into(), column(|b| b.close));
+        series.insert("volume".into(), column(|b| b.volume));
+        series
+    }
+}
+
+/// Re-buckets provider candles (or previously built bars) into another kind,
+/// using exactly the same aggregation as live data. The last bar is left
+/// open in `aggregator` so live ticks can carry on from it.
+pub fn resample(bars: &[Bar], aggregator: &mut BarAggregator) -> BarSeries {
+    let mut series = BarSeries::default();
+    for bar in bars {
+        let events = aggregator.push_bar(bar);
+        series.apply(events);
+    }
+    series.partial = aggregator.current().cloned();
+    series
+}
+
+/// Bar kinds offered on the price chart.
+pub const BAR_KINDS: [&str; 5] = ["time", "tick", "volume", "dollar", "renko"];
+
+/// Provider candles that go into one tick, volume or dollar bar.
+const CANDLES_PER_BAR: f64 = 5.0;
+
+pub fn bar_kind_label(kind: &str) -> &'static str {
+    match kind {
+        "tick" => "Tick bars",
+        "volume" => "Volume bars",
+        "dollar" => "Dollar bars",
+        "renko" => "Renko",
+        _ => "Time bars",
+    }
+}
+
+/// The bar kind named `kind`, sized from `history`: activity bars take about
+/// `CANDLES_PER_BAR` candles' worth of trading and Renko bricks are 1% of the
+/// last close. Falls back to time bars of `interval` without history.
+pub fn bar_kind(kind: &str, interval: Duration, history: &[Bar]) -> BarKind {
+    if history.is_empty() {
+        return BarKind::Time(interval);
+    }
+    let candles = history.len() as f64;
+    let per_candle = |f: fn(&Bar) -> f64| history.iter().map(f).sum::<f64>() / candles;
+    let last_close = history.last().map_or(0.0, |b| b.close);
+
+    let sized = match kind {
+        // `push_bar` replays every candle as four ticks.
+        "tick" => BarKind::Tick((4.0 * CANDLES_PER_BAR) as usize),
+        "volume" => BarKind::Volume(per_candle(|b| b.volume) * CANDLES_PER_BAR),
+        "dollar" => BarKind::Dollar(per_candle(|b| b.close * b.volume) * CANDLES_PER_BAR),
+        "renko" => BarKind::Renko(last_close * 0.01),
+        _ => BarKind::Time(interval),
+    };
+    match sized {
+        BarKind::Volume(size) | BarKind::Dollar(size) | BarKind::Renko(size)
+            if !size.is_finite() || size <= 0.0 =>
+        {
+            BarKind::Time(interval)
+        }
+        _ => sized,
+    }
+}
+
+/// Converts a Finnhub `/stock/candle` payload into bars. The provider only
+/// reports the candle open time, so each bar spans `interval` from there.
+#[cfg(feature = "server")]
+pub fn candles_to_bars(candles: &Value, interval: Duration) -> Vec<Bar> {
+    let column = |key: &str| -> Vec<f64> {
+        candles
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|a| a.iter().map(|v| v.as_f64().unwrap_or(0.0)).collect())
+            .unwrap_or_default()
+    };
+
+    let (t, o, h, l, c, v) = (
+        column("t"),
+        column("o"),
+        column("h"),
+        column("l"),
+        column("c"),
+        column("v"),
+    );
+
+    (0..t.len())
+        .filter_map(|i| {
+            let start = DateTime::from_timestamp(t[i] as i64, 0)?;
+            Some(Bar {
+                start,
+                end: start + interval,
+                open: *o.get(i)?,
+                high: *h.get(i)?,
+                low: *l.get(i)?,
+                close: *c.get(i)?,
+                volume: *v.get(i)?,
+                ticks: 0,
+            })
+        })
+        .collect()
+}
+
+/// Candle resolutions offered on the chart, finest first.
+pub const RESOLUTIONS: [&str; 7] = ["1", "5", "15", "30", "60", "D", "W"];
+
+/// Whether a resolution is shorter than a trading day.
+pub fn is_intraday(resolution: &str) -> bool {
+    resolution_interval(resolution) < Duration::days(1)
+}
+
+pub fn resolution_label(resolution: &str) -> String {
+    match resolution {
+        "D" => String::from("1 day"),
+        "W" => String::from("1 week"),
+        "M" => String::from("1 month"),
+        "60" => String::from("1 hour"),
+        minutes => format!("{minutes} min"),
+    }
+}
+
+/// How much history to request for a resolution, keeping intraday requests
+/// within what the provider returns in one call.
+pub fn resolution_lookback(resolution: &str) -> Duration {
+    match resolution {
+        "1" => Duration::days(2),
+        "5" | "15" => Duration::days(10),
+        "30" | "60" => Duration::days(60),
+        _ => Duration::days(5 * 365),
+    }
+}
+
+/// Finnhub candle resolutions and the bar length each one stands for. Weeks
+/// and months are bucketed by `calendar::period` rather than by length.
+pub fn resolution_interval(resolution: &str) -> Duration {
+    match resolution {
+        "1" => Duration::minutes(1),
+        "5" => Duration::minutes(5),
+        "15" => Duration::minutes(15),
+        "30" => Duration::minutes(30),
+        "60" => Duration::hours(1),
+        "W" => Duration::weeks(1),
+        "M" => Duration::days(30),
+        _ => Duration::days(1),
+    }
+}
+
+#[server]
+pub async fn get_candles(
+    symbol: String,
+    resolution: String,
+    from: i64,
+    to: i64,
+    api_key: String,
+) -> Result<Vec<Bar>, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/stock/candle?symbol={}&resolution={}&from={}&to={}&token={api_key}",
+        symbol, resolution, from, to
+    )).await?;
+    let candles: serde_json::Value = serde_json::from_str(&body)?;
+    Ok(candles_to_bars(&candles, resolution_interval(&resolution)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::exchange::Exchange;
+
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, day, hour, minute, 0).unwrap()
+    }
+
+    fn tick(time: DateTime<Utc>, price: f64, volume: f64) -> Tick {
+        Tick {
+            time,
+            price,
+            volume,
+        }
+    }
+
+    fn closed(events: &[BarEvent]) -> Vec<&Bar> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                BarEvent::Closed(bar) => Some(bar),
+                BarEvent::Amended(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn time_bars_bucket_by_interval() {
+        let mut aggregator = BarAggregator::new(BarKind::Time(Duration::minutes(5)));
+        assert!(aggregator.push(tick(at(3, 14, 31), 10.0, 1.0)).is_empty());
+        assert!(aggregator.push(tick(at(3, 14, 33), 12.0, 2.0)).is_empty());
+        assert!(aggregator.push(tick(at(3, 14, 34), 9.0, 1.0)).is_empty());
+
+        let events = aggregator.push(tick(at(3, 14, 36), 11.0, 1.0));
+        let bar = Bar {
+            start: at(3, 14, 30),
+            end: at(3, 14, 35),
+            open: 10.0,
+            high: 12.0,
+            low: 9.0,
+            close: 9.0,
+            volume: 4.0,
+            ticks: 3,
+        };
+        assert_eq!(events, vec![BarEvent::Closed(bar)]);
+        assert_eq!(aggregator.current().map(|b| b.start), Some(at(3, 14, 35)));
+    }
+
+    #[test]
+    fn late_tick_within_lateness_amends_its_bar() {
+        let mut aggregator = BarAggregator::new(BarKind::Time(Duration::minutes(5)))
+            .with_lateness(Duration::minutes(5));
+        aggregator.push(tick(at(3, 14, 31), 10.0, 1.0));
+        aggregator.push(tick(at(3, 14, 33), 11.0, 1.0));
+        aggregator.push(tick(at(3, 14, 36), 11.0, 1.0));
+
+        let events = aggregator.push(tick(at(3, 14, 34), 13.0, 1.0));
+        let [BarEvent::Amended(bar)] = events.as_slice() else {
+            panic!("expected one amendment, got {events:?}");
+        };
+        assert_eq!(bar.start, at(3, 14, 30));
+        assert_eq!((bar.high, bar.close, bar.ticks), (13.0, 13.0, 3));
+        assert_eq!(aggregator.current().map(|b| b.ticks), Some(1));
+    }
+
+    #[test]
+    fn late_tick_beyond_lateness_is_dropped() {
+        let mut aggregator = BarAggregator::new(BarKind::Time(Duration::minutes(5)))
+            .with_lateness(Duration::minutes(1));
+        aggregator.push(tick(at(3, 14, 31), 10.0, 1.0));
+        aggregator.push(tick(at(3, 14, 36), 11.0, 1.0));
+        let forming = aggregator.current().cloned();
+
+        assert!(aggregator.push(tick(at(3, 14, 34), 13.0, 1.0)).is_empty());
+        assert_eq!(aggregator.current().cloned(), forming);
+    }
+
+    #[test]
+    fn session_gap_closes_activity_bar() {
+        let mut aggregator =
+            BarAggregator::new(BarKind::Volume(100.0)).with_session_gap(Duration::minutes(30));
+        aggregator.push(tick(at(3, 15, 0), 10.0, 10.0));
+        assert!(aggregator.push(tick(at(3, 15, 20), 11.0, 10.0)).is_empty());
+
+        let events = aggregator.push(tick(at(3, 16, 30), 12.0, 10.0));
+        let bars = closed(&events);
+        assert_eq!(bars.len(), 1);
+        assert_eq!((bars[0].start, bars[0].end), (at(3, 15, 0), at(3, 15, 20)));
+        assert_eq!(bars[0].volume, 20.0);
+    }
+
+    #[test]
+    fn new_trading_day_closes_bar() {
+        let calendar = Exchange::Us.calendar().clone();
+        let mut aggregator = BarAggregator::new(BarKind::Tick(10)).with_calendar(calendar);
+        // 15:50 and 15:55 New York on Monday, then the Tuesday open.
+        aggregator.push(tick(at(3, 20, 50), 10.0, 1.0));
+        aggregator.push(tick(at(3, 20, 55), 11.0, 1.0));
+
+        let events = aggregator.push(tick(at(4, 14, 31), 12.0, 1.0));
+        let bars = closed(&events);
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].ticks, 2);
+        assert_eq!(aggregator.current().map(|b| b.open), Some(12.0));
+    }
+
+    #[test]
+    fn monday_to_friday_makes_one_weekly_bar() {
+        // Daily candles, stamped at midnight UTC, for Monday 3 to Friday
+        // 7 March 2025 and the following Monday.
+        let daily = |day: u32| Bar {
+            start: at(day, 0, 0),
+            end: at(day + 1, 0, 0),
+            open: 10.0,
+            high: 11.0,
+            low: 9.0,
+            close: 10.0,
+            volume: 100.0,
+            ticks: 0,
+        };
+        let mut aggregator = BarAggregator::new(BarKind::Time(Duration::weeks(1)));
+        let mut events = Vec::new();
+        for day in [3, 4, 5, 6, 7, 10] {
+            events.extend(aggregator.push_bar(&daily(day)));
+        }
+        let bars = closed(&events);
+        assert_eq!(bars.len(), 1);
+        assert_eq!((bars[0].start, bars[0].end), (at(3, 0, 0), at(10, 0, 0)));
+        assert_eq!(bars[0].volume, 500.0);
+
+        // The same week from intraday ticks, bucketed in New York time.
+        let calendar = Exchange::Us.calendar().clone();
+        let mut aggregator =
+            BarAggregator::new(BarKind::Time(Duration::weeks(1))).with_calendar(calendar);
+        let mut events = Vec::new();
+        for day in [3, 4, 5, 6, 7, 10] {
+            events.extend(aggregator.push(tick(at(day, 15, 0), 10.0, 1.0)));
+            events.extend(aggregator.push(tick(at(day, 20, 30), 10.0, 1.0)));
+        }
+        let bars = closed(&events);
+        assert_eq!(bars.len(), 1);
+        assert_eq!(bars[0].start, at(3, 5, 0));
+        assert_eq!(bars[0].ticks, 10);
+    }
+
+    #[test]
+    fn bar_kinds_fall_back_to_time_bars_without_history() {
+        let interval = Duration::minutes(5);
+        for kind in BAR_KINDS {
+            assert_eq!(
+                bar_kind(kind, interval, &[]),
+                BarKind::Time(interval),
+                "{kind}"
+            );
+        }
+
+        let candle = |close, volume| Bar {
+            start: at(3, 14, 30),
+            end: at(3, 14, 35),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+            ticks: 4,
+        };
+        let history = [candle(10.0, 100.0), candle(30.0, 300.0)];
+        assert_eq!(bar_kind("tick", interval, &history), BarKind::Tick(20));
+        assert_eq!(
+            bar_kind("volume", interval, &history),
+            BarKind::Volume(1000.0)
+        );
+        assert_eq!(
+            bar_kind("dollar", interval, &history),
+            BarKind::Dollar(25000.0)
+        );
+        assert_eq!(bar_kind("renko", interval, &history), BarKind::Renko(0.3));
+        // Without volume there is nothing to size activity bars by.
+        let quiet = [candle(10.0, 0.0)];
+        assert_eq!(
+            bar_kind("volume", interval, &quiet),
+            BarKind::Time(interval)
+        );
+    }
+
+    #[test]
+    fn renko_needs_two_bricks_to_reverse() {
+        let mut aggregator = BarAggregator::new(BarKind::Renko(1.0));
+        let mut bricks = Vec::new();
+        for (minute, price) in [(0, 100.0), (1, 101.2), (2, 102.0), (3, 100.5), (4, 99.9)] {
+            let events = aggregator.push(tick(at(3, 15, minute), price, 1.0));
+            bricks.extend(closed(&events).iter().map(|b| (b.open, b.close)));
+        }
+        assert_eq!(bricks, vec![(100.0, 101.0), (101.0, 102.0), (101.0, 100.0)],);
+    }
+}
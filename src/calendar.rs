@@ -0,0 +1,379 @@
+use crate::diagnostics::log;
+use crate::exchange::Exchange;
+use chrono::{DateTime, Datelike, Duration, Months, NaiveDate, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use dioxus::logger::tracing::Level;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// Environment variable pointing at a directory of calendar files that
+/// override the bundled ones, e.g. to add next year's holidays.
+pub const CALENDAR_DIR_VAR: &str = "FINORACLE_CALENDAR_DIR";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Hours {
+    pub open: NaiveTime,
+    pub close: NaiveTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Session {
+    PreMarket,
+    Regular,
+    Lunch,
+    PostMarket,
+    Closed,
+}
+
+impl Session {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Session::PreMarket => "Pre-market",
+            Session::Regular => "Market open",
+            Session::Lunch => "Lunch break",
+            Session::PostMarket => "After hours",
+            Session::Closed => "Market closed",
+        }
+    }
+}
+
+/// Trading sessions and holidays for one exchange, in the exchange's own time
+/// zone. Loaded from the JSON files in `assets/calendars`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TradingCalendar {
+    pub timezone: Tz,
+    pub regular: Hours,
+    /// Start of pre-market trading.
+    #[serde(default)]
+    pub pre: Option<NaiveTime>,
+    /// End of after-hours trading.
+    #[serde(default)]
+    pub post: Option<NaiveTime>,
+    /// Midday break inside the regular session.
+    #[serde(default)]
+    pub lunch: Option<Hours>,
+    /// Early close on `half_days`.
+    #[serde(default)]
+    pub half_day_close: Option<NaiveTime>,
+    #[serde(default)]
+    pub holidays: BTreeSet<NaiveDate>,
+    #[serde(default)]
+    pub half_days: BTreeSet<NaiveDate>,
+}
+
+impl TradingCalendar {
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    /// Loads the calendar for `exchange`, preferring a file in
+    /// `FINORACLE_CALENDAR_DIR` over the bundled one.
+    pub fn load(exchange: &Exchange) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Ok(dir) = std::env::var(CALENDAR_DIR_VAR) {
+            let path = std::path::Path::new(&dir).join(exchange.calendar_file());
+            if let Some(calendar) = std::fs::read_to_string(path)
+                .ok()
+                .and_then(|json| Self::from_json(&json).ok())
+            {
+                return calendar;
+            }
+        }
+
+        let bundled = match exchange {
+            Exchange::Us => include_str!("../assets/calendars/us.json"),
+            Exchange::Asx => include_str!("../assets/calendars/asx.json"),
+            Exchange::Lse => include_str!("../assets/calendars/lse.json"),
+            Exchange::Tse => include_str!("../assets/calendars/tse.json"),
+        };
+        Self::from_json(bundled).expect("bundled calendar files are valid")
+    }
+
+    /// The exchange-local date of an instant.
+    pub fn local_date(&self, t: DateTime<Utc>) -> NaiveDate {
+        t.with_timezone(&self.timezone).date_naive()
+    }
+
+    /// An exchange-local wall-clock time as an instant.
+    pub fn at(&self, date: NaiveDate, time: NaiveTime) -> DateTime<Utc> {
+        let local = date.and_time(time);
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+            .unwrap_or_else(|| Utc.from_utc_datetime(&local))
+    }
+
+    pub fn is_trading_day(&self, date: NaiveDate) -> bool {
+        self.warn_past_holidays(date);
+        !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) && !self.holidays.contains(&date)
+    }
+
+    /// Whether `date` falls in a year the holiday list covers. Outside it
+    /// only weekends are known to be closed.
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        match (self.holidays.first(), self.holidays.last()) {
+            (Some(first), Some(last)) => (first.year()..=last.year()).contains(&date.year()),
+            _ => false,
+        }
+    }
+
+    /// Warns, once per exchange and year, that a date after the holiday list
+    /// is being treated as a trading day on every weekday.
+    fn warn_past_holidays(&self, date: NaiveDate) {
+        static WARNED: Mutex<BTreeSet<(&str, i32)>> = Mutex::new(BTreeSet::new());
+
+        let last = match self.holidays.last() {
+            Some(last) if date.year() > last.year() => last.year(),
+            _ => return,
+        };
+        let first = WARNED
+            .lock()
+            .is_ok_and(|mut warned| warned.insert((self.timezone.name(), date.year())));
+        if first {
+            log(
+                Level::WARN,
+                format!(
+                    "{} holidays are only listed until {last}, so every weekday in {} \
+                     counts as a trading day; newer calendars can go in {CALENDAR_DIR_VAR}",
+                    self.timezone.name(),
+                    date.year(),
+                ),
+            );
+        }
+    }
+
+    pub fn is_half_day(&self, date: NaiveDate) -> bool {
+        self.half_days.contains(&date)
+    }
+
+    pub fn close_time(&self, date: NaiveDate) -> NaiveTime {
+        match self.half_day_close {
+            Some(close) if self.is_half_day(date) => close,
+            _ => self.regular.close,
+        }
+    }
+
+    /// The regular trading intervals on `date`, split around any lunch break.
+    pub fn regular_hours(&self, date: NaiveDate) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+        if !self.is_trading_day(date) {
+            return Vec::new();
+        }
+        let open = self.regular.open;
+        let close = self.close_time(date);
+
+        match self.lunch {
+            Some(lunch) if lunch.open < close => vec![
+                (self.at(date, open), self.at(date, lunch.open)),
+                (self.at(date, lunch.close.min(close)), self.at(date, close)),
+            ],
+            _ => vec![(self.at(date, open), self.at(date, close))],
+        }
+    }
+
+    pub fn session_at(&self, t: DateTime<Utc>) -> Session {
+        let date = self.local_date(t);
+        if !self.is_trading_day(date) {
+            return Session::Closed;
+        }
+
+        let hours = self.regular_hours(date);
+        let (Some(first), Some(last)) = (hours.first(), hours.last()) else {
+            return Session::Closed;
+        };
+
+        if hours.iter().any(|(open, close)| *open <= t && t < *close) {
+            Session::Regular
+        } else if first.0 <= t && t < last.1 {
+            Session::Lunch
+        } else if t < first.0 && self.pre.is_some_and(|pre| self.at(date, pre) <= t) {
+            Session::PreMarket
+        } else if t >= last.1
+            && !self.is_half_day(date)
+            && self.post.is_some_and(|post| t < self.at(date, post))
+        {
+            Session::PostMarket
+        } else {
+            Session::Closed
+        }
+    }
+
+    pub fn is_open(&self, t: DateTime<Utc>) -> bool {
+        self.session_at(t) == Session::Regular
+    }
+
+    pub fn next_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut next = date + Duration::days(1);
+        while !self.is_trading_day(next) {
+            next += Duration::days(1);
+        }
+        next
+    }
+
+    pub fn previous_trading_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut previous = date - Duration::days(1);
+        while !self.is_trading_day(previous) {
+            previous -= Duration::days(1);
+        }
+        previous
+    }
+
+    /// The date of the most recent session that has started by `t`.
+    pub fn last_session_date(&self, t: DateTime<Utc>) -> NaiveDate {
+        let date = self.local_date(t);
+        if self.is_trading_day(date) && self.at(date, self.regular.open) <= t {
+            date
+        } else {
+            self.previous_trading_day(date)
+        }
+    }
+
+    /// The next regular open strictly after `t`.
+    pub fn next_open(&self, t: DateTime<Utc>) -> DateTime<Utc> {
+        let mut date = self.local_date(t);
+        if !self.is_trading_day(date) {
+            date = self.next_trading_day(date);
+        }
+        loop {
+            if let Some((open, _)) = self
+                .regular_hours(date)
+                .into_iter()
+                .find(|(open, _)| *open > t)
+            {
+                return open;
+            }
+            date = self.next_trading_day(date);
+        }
+    }
+
+    /// Regular trading time elapsed between two instants. Plotting against
+    /// this instead of wall-clock time removes nights, weekends and holidays
+    /// from intraday axes.
+    pub fn trading_duration(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Duration {
+        if to <= from {
+            return Duration::zero();
+        }
+
+        let mut total = Duration::zero();
+        let mut date = self.local_date(from) - Duration::days(1);
+        let last = self.local_date(to) + Duration::days(1);
+        while date <= last {
+            for (open, close) in self.regular_hours(date) {
+                let start = open.max(from);
+                let end = close.min(to);
+                if end > start {
+                    total += end - start;
+                }
+            }
+            date += Duration::days(1);
+        }
+        total
+    }
+
+    /// `trading_duration` from the first of `times` (sorted ascending) to each
+    /// of them, computed in a single pass.
+    pub fn trading_offsets(&self, times: &[DateTime<Utc>]) -> Vec<Duration> {
+        let mut elapsed = Duration::zero();
+        let mut previous = times.first().copied();
+        times
+            .iter()
+            .map(|t| {
+                if let Some(p) = previous {
+                    elapsed += self.trading_duration(p, *t);
+                }
+                previous = Some(*t);
+                elapsed
+            })
+            .collect()
+    }
+
+    /// Start of the bar of length `interval` containing `t`, counting buckets
+    /// from the session open rather than from midnight UTC. Bars of a day or
+    /// longer cover whole local days of their `period`, so they start at
+    /// local midnight and take in extended-hours trades; intraday trades
+    /// before the open are bucketed from the pre-market start.
+    pub fn align(&self, t: DateTime<Utc>, interval: Duration) -> DateTime<Utc> {
+        let date = self.local_date(t);
+        let open = self.at(date, self.regular.open);
+        if interval >= Duration::days(1) {
+            return self.at(period(date, interval).0, NaiveTime::MIN).min(t);
+        }
+        if interval <= Duration::zero() {
+            return t;
+        }
+
+        let base = if t >= open {
+            open
+        } else {
+            match self.pre.map(|pre| self.at(date, pre)) {
+                Some(pre) if pre <= t => pre,
+                _ => self.at(date, NaiveTime::MIN),
+            }
+        };
+        let elapsed = (t - base).num_milliseconds();
+        let step = interval.num_milliseconds();
+        base + Duration::milliseconds(elapsed - elapsed % step)
+    }
+}
+
+/// The first day of the period of length `interval` containing `date`, and
+/// the first day of the next one. Weekly bars follow Monday-to-Sunday weeks
+/// and anything of four weeks or more follows calendar months, so neither
+/// drifts against the trading week the way fixed lengths from the epoch do.
+pub fn period(date: NaiveDate, interval: Duration) -> (NaiveDate, NaiveDate) {
+    if interval >= Duration::weeks(4) {
+        let first = date.with_day(1).unwrap_or(date);
+        let next = first.checked_add_months(Months::new(1)).unwrap_or(first);
+        (first, next)
+    } else if interval >= Duration::weeks(1) {
+        let monday = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+        (monday, monday + Duration::weeks(1))
+    } else {
+        (date, date + Duration::days(interval.num_days().max(1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_calendars_cover_next_year() {
+        let next_year = NaiveDate::from_ymd_opt(2027, 12, 31).unwrap();
+        for exchange in Exchange::ALL {
+            assert!(exchange.calendar().covers(next_year), "{exchange:?}");
+        }
+    }
+
+    #[test]
+    fn daily_bars_never_start_after_the_trade() {
+        let calendar = Exchange::Us.calendar();
+        // 08:15 New York, before the 09:30 open.
+        let pre_market = Utc.with_ymd_and_hms(2026, 10, 19, 12, 15, 0).unwrap();
+        let start = calendar.align(pre_market, Duration::days(1));
+        assert!(start <= pre_market);
+        assert_eq!(calendar.local_date(start), calendar.local_date(pre_market));
+
+        let regular = Utc.with_ymd_and_hms(2026, 10, 19, 15, 0, 0).unwrap();
+        assert_eq!(calendar.align(regular, Duration::days(1)), start);
+    }
+
+    #[test]
+    fn weeks_and_months_follow_the_calendar() {
+        let date = |m, d| NaiveDate::from_ymd_opt(2026, m, d).unwrap();
+        // Thursday 22 October 2026.
+        assert_eq!(
+            period(date(10, 22), Duration::weeks(1)),
+            (date(10, 19), date(10, 26))
+        );
+        assert_eq!(
+            period(date(10, 22), Duration::days(30)),
+            (date(10, 1), date(11, 1))
+        );
+        assert_eq!(
+            period(date(10, 22), Duration::days(1)),
+            (date(10, 22), date(10, 23))
+        );
+    }
+}
@@ -1,71 +1,104 @@
-use chrono::{Datelike, NaiveDate};
+use crate::bars::{
+    bar_kind, bar_kind_label, get_candles, is_intraday, resample, resolution_interval,
+    resolution_label, resolution_lookback, BarAggregator, BarKind, BarSeries, Tick, BAR_KINDS,
+    RESOLUTIONS,
+};
+use crate::corporate::{load_corporate_actions, Adjustment, PRICE_ADJUSTMENT};
+use crate::diagnostics::{self, traced};
+use crate::earnings::EARNINGS;
+use crate::fx::{display_currency, FX_RATES};
+use crate::news::{NEWS, SENTIMENT_OVERLAY};
+use crate::platform::{bounds, Canvas};
+use crate::quality::{clean, Policy, QualityReport, Rules, QUALITY_POLICY};
+use crate::quotes::QUOTES;
+use crate::sentiment::{daily_sentiment, default_scorer};
+use crate::series::{
+    axis_ticks, indicator, indicator_label, range_label, range_start, DataPoint, INDICATORS,
+    RANGES, TIME_DISPLAY,
+};
+use crate::statements::STATEMENT_SERIES;
+use crate::store::{update, Instrument, APP_STATE};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Utc};
 use dioxus::prelude::*;
-use serde::de::{self, Visitor};
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::cmp::Ordering;
-use std::fmt;
-use std::marker::PhantomData;
-use web_sys::wasm_bindgen::JsCast;
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
-
-#[derive(Deserialize, Debug, PartialEq, PartialOrd, Clone)]
-struct DataPoint {
-    #[serde(deserialize_with = "str_to_date")]
-    period: NaiveDate,
-    v: f64,
-}
 
-impl Eq for DataPoint {}
+/// Symbols overlaid on the price chart, rebased to the selected symbol.
+pub static COMPARISON: GlobalMemo<Vec<String>> =
+    Memo::global(|| APP_STATE.read().comparison.clone());
+
+/// What the chart shows; the part of its state that goes into links.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ChartQuery {
+    pub tab: String,
+    pub series: String,
+    pub resolution: String,
+    /// One of `RANGES`.
+    pub range: String,
+    /// Keys from `INDICATORS`.
+    pub indicators: Vec<String>,
+}
 
-impl Ord for DataPoint {
-    fn cmp(&self, other: &DataPoint) -> Ordering {
-        if self.period < other.period {
-            Ordering::Less
-        } else if self.period > other.period {
-            Ordering::Greater
-        } else {
-            Ordering::Equal
+impl ChartQuery {
+    pub fn series(tab: &str, series: &str) -> ChartQuery {
+        ChartQuery {
+            tab: tab.to_string(),
+            series: series.to_string(),
+            ..Default::default()
         }
     }
 }
 
-fn str_to_date<'de, D>(deserializer: D) -> Result<NaiveDate, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    struct Struct(PhantomData<fn() -> NaiveDate>);
+/// State to bring up in the chart, set by the views and routes that link
+/// into it and consumed once shown. Empty fields are left as they are.
+pub static CHART_SELECTION: GlobalSignal<Option<ChartQuery>> = Global::new(|| None);
+/// The chart's current state, saved by `ChartView` as it changes.
+pub static CHART_STATE: GlobalMemo<ChartQuery> = Memo::global(|| APP_STATE.read().chart.clone());
+
+const COMPARISON_COLORS: [&str; 6] = [
+    "rgb(0, 170, 255)",
+    "rgb(0, 220, 120)",
+    "rgb(255, 160, 0)",
+    "rgb(200, 100, 255)",
+    "rgb(255, 90, 160)",
+    "rgb(180, 180, 180)",
+];
+
+pub fn add_to_comparison(symbols: &[String]) {
+    update(|state| {
+        for symbol in symbols {
+            if !state.comparison.contains(symbol) {
+                state.comparison.push(symbol.to_owned());
+            }
+        }
+    });
+}
 
-    impl<'de> Visitor<'de> for Struct {
-        type Value = NaiveDate;
+pub fn remove_from_comparison(symbol: &str) {
+    update(|state| state.comparison.retain(|s| s != symbol));
+}
 
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("NaiveDate")
-        }
+/// Longest pause between intraday ticks that still counts as one session;
+/// longer ones, such as a lunch break, close the forming activity bar.
+const SESSION_GAP_MINUTES: i64 = 45;
 
-        fn visit_str<E>(self, value: &str) -> Result<NaiveDate, E>
-        where
-            E: de::Error,
-        {
-            Ok(NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap_or(NaiveDate::default()))
-        }
-    }
+const INDICATOR_COLORS: [&str; 3] = ["rgb(255, 215, 0)", "rgb(255, 105, 180)", "rgb(0, 206, 209)"];
 
-    deserializer.deserialize_str(Struct(PhantomData))
+fn indicator_color(key: &str) -> &'static str {
+    let i = INDICATORS.iter().position(|k| *k == key).unwrap_or(0);
+    INDICATOR_COLORS[i % INDICATOR_COLORS.len()]
 }
 
-impl From<DataPoint> for String {
-    fn from(value: DataPoint) -> Self {
-        let year = value.period.year();
-        let month = value.period.month();
-        let day = value.period.day();
-
-        String::from(format!("{}-{}-{}", year, month, day))
-    }
+fn comparison_color(symbol: &str) -> &'static str {
+    let i = COMPARISON().iter().position(|s| s == symbol).unwrap_or(0);
+    COMPARISON_COLORS[i % COMPARISON_COLORS.len()]
 }
 
 #[component]
-pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
+pub fn ChartView(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("ChartView");
     let api_key: &'static str = env!("FINNHUB_API_KEY");
 
     let mut selected_tab = use_signal(|| String::from("annual"));
@@ -74,6 +107,15 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
     // let mut selected_quarterly_series = use_signal(|| HashSet::<String>::new());
     let mut selected_annual_series = use_signal(|| String::from(""));
     let mut selected_quarterly_series = use_signal(|| String::from(""));
+    let mut selected_price_series = use_signal(|| String::from(""));
+    let mut selected_statement_series = use_signal(|| String::from(""));
+    let mut resolution = use_signal(|| String::from("D"));
+    let mut bar_type = use_signal(|| String::from("time"));
+    let mut range = use_signal(|| String::from("MAX"));
+    let mut indicators: Signal<Vec<String>> = use_signal(Vec::new);
+    // Price bars built from the candle history and extended by live quotes.
+    let mut live: Signal<Option<(BarAggregator, BarSeries)>> = use_signal(|| None);
+    let mut last_quote = use_signal(|| 0i64);
     let dummy_string = String::from("");
     let dummy_val = Value::Null;
     let mut selected_financial: Signal<Option<(String, Value)>> = use_signal(|| None);
@@ -83,7 +125,56 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
     let ak = api_key.to_owned();
     let financials = use_resource(move || {
         let ak = ak.clone();
-        async move { get_basic_financials(symbol().0, ak).await }
+        async move {
+            let symbol = symbol().symbol;
+            let request = get_basic_financials(symbol.clone(), ak);
+            traced("basic_financials", &symbol, request).await
+        }
+    });
+
+    let ak = api_key.to_owned();
+    let candles = use_resource(move || {
+        let ak = ak.clone();
+        async move {
+            let res = resolution();
+            let to = Utc::now();
+            let from = to - resolution_lookback(&res);
+            let symbol = symbol().symbol;
+            let request = get_candles(symbol.clone(), res, from.timestamp(), to.timestamp(), ak);
+            traced("candles", &symbol, request).await
+        }
+    });
+
+    let ak = api_key.to_owned();
+    let actions = use_resource(move || {
+        let ak = ak.clone();
+        async move { load_corporate_actions(symbol().symbol, ak).await }
+    });
+
+    let ak = api_key.to_owned();
+    let compared = use_resource(move || {
+        let ak = ak.clone();
+        async move {
+            let res = resolution();
+            let to = Utc::now();
+            let from = to - resolution_lookback(&res);
+            let mut compared = Vec::new();
+            for sym in COMPARISON() {
+                let request = get_candles(
+                    sym.to_owned(),
+                    res.to_owned(),
+                    from.timestamp(),
+                    to.timestamp(),
+                    ak.to_owned(),
+                );
+                if let Ok(bars) = traced("candles", &sym, request).await {
+                    let closes: Vec<(DateTime<Utc>, f64)> =
+                        bars.iter().map(|b| (b.start, b.close)).collect();
+                    compared.push((sym, closes));
+                }
+            }
+            compared
+        }
     });
 
     let mut series = use_signal(|| Map::<String, Value>::new());
@@ -92,28 +183,152 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
     let mut xmin = use_signal(|| 0i32);
     let mut ymin = use_signal(|| 0f64);
     let mut ymax = use_signal(|| 0f64);
+    let mut quality = use_signal(QualityReport::default);
 
     use_effect(move || {
-        series.set(
-            financials
-                .read_unchecked()
-                .to_owned()
-                .unwrap_or(Ok(Map::<String, Value>::new()))
-                .ok()
-                .unwrap()
-                .get("series")
-                .unwrap_or(&Value::Object(Map::<String, Value>::new()))
-                .as_object()
-                .unwrap_or(&Map::<String, Value>::new())
-                .to_owned(),
-        );
+        let candles = candles.read_unchecked();
+        let Some(Ok(bars)) = &*candles else {
+            live.set(None);
+            return;
+        };
+        if bars.is_empty() {
+            live.set(None);
+            return;
+        }
+
+        let res = resolution();
+        let calendar = symbol().exchange.calendar();
+        let interval = resolution_interval(&res);
+        let bars = match &*actions.read_unchecked() {
+            Some(actions) => actions.adjust(bars, calendar, PRICE_ADJUSTMENT()),
+            None => bars.to_vec(),
+        };
+        let kind = bar_kind(&bar_type(), interval, &bars);
+        // A quote can still land in the newest candle.
+        let mut aggregator = BarAggregator::new(kind).with_lateness(interval);
+        // Provider daily candles are stamped at midnight UTC, so only
+        // intraday bars are aligned to the exchange session.
+        if is_intraday(&res) {
+            aggregator = aggregator.with_calendar(calendar.clone());
+            if !matches!(kind, BarKind::Time(_)) {
+                aggregator = aggregator.with_session_gap(Duration::minutes(SESSION_GAP_MINUTES));
+            }
+        }
+        let bars = resample(&bars, &mut aggregator);
+        last_quote.set(0);
+        live.set(Some((aggregator, bars)));
     });
 
+    // Every new quote is a trade for the forming bar. Quotes carry no
+    // volume, so they move prices but never fill a volume or dollar bar.
     use_effect(move || {
-        let document = web_sys::window().unwrap().document().unwrap();
-        let container = document.get_element_by_id("chart-container").unwrap();
-        let rect = container.get_bounding_client_rect();
-        size.set(Some((rect.width(), rect.height())));
+        let Some(state) = QUOTES().get(&symbol().symbol).cloned() else {
+            return;
+        };
+        if state.quote.timestamp <= *last_quote.peek() {
+            return;
+        }
+        let Some(time) = Utc.timestamp_opt(state.quote.timestamp, 0).single() else {
+            return;
+        };
+        if let Some((aggregator, bars)) = live.write().as_mut() {
+            let tick = Tick {
+                time,
+                price: state.quote.current,
+                volume: 0.0,
+            };
+            bars.push(aggregator, tick);
+            last_quote.set(state.quote.timestamp);
+        }
+    });
+
+    use_effect(move || {
+        let mut s = financials
+            .read_unchecked()
+            .to_owned()
+            .unwrap_or(Ok(Map::<String, Value>::new()))
+            .ok()
+            .unwrap()
+            .get("series")
+            .unwrap_or(&Value::Object(Map::<String, Value>::new()))
+            .as_object()
+            .unwrap_or(&Map::<String, Value>::new())
+            .to_owned();
+
+        if let Some((_, bars)) = &*live.read() {
+            s.insert(String::from("price"), Value::Object(bars.to_chart_series()));
+        }
+
+        let statements = STATEMENT_SERIES();
+        if !statements.is_empty() {
+            s.insert(String::from("statements"), Value::Object(statements));
+        }
+
+        series.set(s);
+    });
+
+    use_effect(move || {
+        if let Some(query) = CHART_SELECTION() {
+            let tab = if query.tab.is_empty() {
+                selected_tab.peek().to_owned()
+            } else {
+                query.tab
+            };
+            if !query.series.is_empty() {
+                match tab.as_str() {
+                    "annual" => selected_annual_series.set(query.series),
+                    "quarterly" => selected_quarterly_series.set(query.series),
+                    "price" => selected_price_series.set(query.series),
+                    "statements" => selected_statement_series.set(query.series),
+                    _ => (),
+                }
+            }
+            if RESOLUTIONS.contains(&query.resolution.as_str()) {
+                resolution.set(query.resolution);
+            }
+            if RANGES.contains(&query.range.as_str()) {
+                range.set(query.range);
+            }
+            let shown: Vec<String> = query
+                .indicators
+                .into_iter()
+                .filter(|i| INDICATORS.contains(&i.as_str()))
+                .collect();
+            if !shown.is_empty() {
+                indicators.set(shown);
+            }
+            selected_tab.set(tab);
+            *CHART_SELECTION.write() = None;
+        }
+    });
+
+    use_effect(move || {
+        let tab = selected_tab();
+        let series = match tab.as_str() {
+            "annual" => selected_annual_series(),
+            "quarterly" => selected_quarterly_series(),
+            "price" => selected_price_series(),
+            "statements" => selected_statement_series(),
+            _ => String::new(),
+        };
+        let state = ChartQuery {
+            tab,
+            series,
+            resolution: resolution(),
+            range: range(),
+            indicators: indicators(),
+        };
+        if *CHART_STATE.peek() != state {
+            update(|saved| saved.chart = state);
+        }
+    });
+
+    use_effect(move || {
+        spawn(async move {
+            if let Some(rect) = bounds("chart-container").await {
+                size.set(Some((rect.width, rect.height)));
+            }
+        });
     });
 
     use_effect(move || {
@@ -135,24 +350,64 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                 .find(|(k, _)| match selected_tab().as_str() {
                     "annual" => **k == selected_annual_series(),
                     "quarterly" => **k == selected_quarterly_series(),
+                    "price" => **k == selected_price_series(),
+                    "statements" => **k == selected_statement_series(),
                     _ => false,
                 })
                 .unwrap_or(default_tuple);
 
-        let mut ts: Vec<DataPoint> =
-            serde_json::from_value(ts.1.to_owned()).unwrap_or(Vec::<DataPoint>::new());
+        let calendar = symbol().exchange.calendar();
+        let interval = resolution_interval(&resolution());
+        let mut rules = match (selected_tab().as_str(), ts.0.as_str()) {
+            ("price", "volume") => Rules::volumes(calendar, interval),
+            ("price", _) => Rules::prices(calendar, interval),
+            _ => Rules::fundamentals(),
+        };
+        // Only time bars come at a fixed interval to count missing ones by.
+        if bar_type() != "time" {
+            rules.interval = None;
+        }
+        let cleaned = clean(&ts.1, &rules, QUALITY_POLICY());
+        let mut ts: Vec<DataPoint> = cleaned.points;
+        let mut marked = cleaned.marked;
+        quality.set(cleaned.report);
+
+        // Prices follow the display currency; fundamentals and volume are
+        // plotted as reported.
+        let native = symbol().currency;
+        let price_currency = (selected_tab() == "price" && selected_price_series() != "volume")
+            .then(|| display_currency(native));
+        if let Some(shown) = price_currency {
+            let rate = FX_RATES().rate(native, shown).unwrap_or(1.0);
+            for dp in ts.iter_mut() {
+                dp.v *= rate;
+            }
+        }
 
-        ts.sort_by(|a, b| {
-            if a.period < b.period {
-                Ordering::Less
-            } else if a.period > b.period {
-                Ordering::Greater
-            } else {
-                Ordering::Equal
+        // Indicators are computed before narrowing to the range so their
+        // windows are full from its first point.
+        let mut overlays: Vec<(String, Vec<DataPoint>)> = match price_currency {
+            Some(_) => indicators()
+                .into_iter()
+                .map(|key| {
+                    let line = indicator(&key, &ts);
+                    (key, line)
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        if let Some(start) = ts.last().and_then(|dp| range_start(&range(), dp.period)) {
+            (ts, marked) = ts
+                .into_iter()
+                .zip(marked)
+                .filter(|(dp, _)| dp.period >= start)
+                .unzip();
+            for (_, line) in overlays.iter_mut() {
+                line.retain(|dp| dp.period >= start);
             }
-        });
+        }
 
-        let mut dates = Vec::<NaiveDate>::new();
+        let mut dates = Vec::<DateTime<Utc>>::new();
         let mut values = Vec::<f64>::new();
 
         for dp in &ts {
@@ -160,41 +415,27 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
             values.push(dp.v);
         }
 
-        let document = web_sys::window().unwrap().document().unwrap();
-        let el = document.get_element_by_id("chart").unwrap();
-        let canvas: HtmlCanvasElement = el.dyn_into::<HtmlCanvasElement>().map_err(|_| ()).unwrap();
         if let Some(size) = size() {
-            canvas.set_width(size.0 as u32);
-            canvas.set_height((size.1 * 0.7f64) as u32);
-
-            let ctx = canvas
-                .get_context("2d")
-                .unwrap()
-                .unwrap()
-                .dyn_into::<CanvasRenderingContext2d>()
-                .unwrap();
-
-            ctx.set_stroke_style_str("rgb(238, 0, 0)");
-            ctx.set_fill_style_str("rgb(238,0,0)");
-            ctx.set_line_width(2.0);
-
-            ctx.move_to(
-                0.1f64 * canvas.width() as f64,
-                0.01f64 * canvas.height() as f64,
-            );
-            ctx.line_to(
-                0.1f64 * canvas.width() as f64,
-                0.9f64 * canvas.height() as f64,
-            );
-            ctx.line_to(
-                0.95f64 * canvas.width() as f64,
-                0.9f64 * canvas.height() as f64,
-            );
+            let Some(canvas) = Canvas::open("chart", size.0, size.1 * 0.7f64) else {
+                return;
+            };
+
+            canvas.set_stroke_style_str("rgb(238, 0, 0)");
+            canvas.set_fill_style_str("rgb(238,0,0)");
+            canvas.set_line_width(2.0);
 
-            ctx.stroke();
+            canvas.move_to(0.1f64 * canvas.width(), 0.01f64 * canvas.height());
+            canvas.line_to(0.1f64 * canvas.width(), 0.9f64 * canvas.height());
+            canvas.line_to(0.95f64 * canvas.width(), 0.9f64 * canvas.height());
 
-            let default_max_date = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
-            let default_min_date = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+            canvas.stroke();
+
+            let last_session = calendar.last_session_date(Utc::now());
+            let default_max_date = calendar.at(last_session, calendar.regular.open);
+            let default_min_date = calendar.at(
+                calendar.previous_trading_day(last_session - Duration::days(5 * 365)),
+                calendar.regular.open,
+            );
 
             let x_max = dates.iter().max().unwrap_or(&default_max_date);
             let x_min = dates.iter().min().unwrap_or(&default_min_date);
@@ -224,26 +465,33 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                 })
                 .unwrap_or(&0.0);
 
-            // ctx.move_to(0.25 * canvas.width() as f64, 0.05 * canvas.height() as f64);
-            ctx.set_font("20px sans-serif");
-            let step = (x_max.year() as usize - x_min.year() as usize) / 5;
-
-            if step > 0 {
-                for year in (x_min.year()..=x_max.year())
-                    .step_by((x_max.year() as usize - x_min.year() as usize) / 5)
-                {
-                    let x = ((year - x_min.year()) as f64 / (x_max.year() - x_min.year()) as f64)
-                * (0.85 * canvas.width() as f64)
-                + 0.075 * canvas.width() as f64
-                // - 0.025 * canvas.width() as f64
-                ;
-
-                    let _ = ctx.fill_text(
-                        format!("{year}").as_str(),
-                        x as f64,
-                        0.95 * canvas.height() as f64,
-                    );
+            // Prices only move while the exchange trades, so plot them
+            // against trading time to close up nights, weekends and holidays.
+            let compressed = selected_tab() == "price";
+            let span = (*x_max - *x_min).num_seconds().max(1) as f64;
+            let trading_span = calendar
+                .trading_duration(*x_min, *x_max)
+                .num_seconds()
+                .max(1) as f64;
+            let position = |t: DateTime<Utc>| {
+                if compressed {
+                    calendar.trading_duration(*x_min, t).num_seconds() as f64 / trading_span
+                } else {
+                    (t - *x_min).num_seconds() as f64 / span
                 }
+            };
+
+            canvas.set_font("20px sans-serif");
+            let mut last_label: Option<f64> = None;
+            for (t, label) in axis_ticks(*x_min, *x_max, 6, calendar.timezone, TIME_DISPLAY()) {
+                let x = position(t) * (0.85 * canvas.width()) + 0.075 * canvas.width();
+                // Ticks that fall in a closed period collapse onto the next
+                // open, so drop any that would overlap.
+                if last_label.is_some_and(|l| x - l < 0.1 * canvas.width()) {
+                    continue;
+                }
+                last_label = Some(x);
+                canvas.fill_text(label.as_str(), x, 0.95 * canvas.height());
             }
 
             let y_start = *y_min;
@@ -257,43 +505,166 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
             ymax.set(*y_max);
 
             for value in 0..10 {
-                let y = 0.95 * canvas.height() as f64
-                    - (value as f64 / 10.0) * (0.95 * canvas.height() as f64)
-                    - 0.04 * canvas.height() as f64;
-                let _ = ctx.fill_text(
-                    format!("{:.2}", y_start + value as f64 * (y_end / 10.0)).as_str(),
-                    0.005 * canvas.width() as f64,
+                let y = 0.95 * canvas.height()
+                    - (value as f64 / 10.0) * (0.95 * canvas.height())
+                    - 0.04 * canvas.height();
+                let label = y_start + value as f64 * (y_end / 10.0);
+                canvas.fill_text(
+                    match price_currency {
+                        Some(currency) => currency.format(label),
+                        None => format!("{:.2}", label),
+                    }
+                    .as_str(),
+                    0.005 * canvas.width(),
                     y,
                 );
             }
-            ctx.stroke();
+            canvas.stroke();
 
             if values.len() > 0 && dates.len() > 0 {
-                let max_days = ts
-                    .iter()
-                    .max()
-                    .unwrap_or(&DataPoint {
-                        period: NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
-                        v: 0.0,
-                    })
-                    .period
-                    .signed_duration_since(*x_min)
-                    .num_days();
-
-                ctx.move_to(
-                    0.1f64 * canvas.width() as f64,
-                    0.9f64 * canvas.height() as f64
-                        - ((values[0] - *y_min) / y_max) * 0.9 * canvas.height() as f64,
+                let positions: Vec<f64> = if compressed {
+                    calendar
+                        .trading_offsets(&dates)
+                        .iter()
+                        .map(|d| d.num_seconds() as f64 / trading_span)
+                        .collect()
+                } else {
+                    dates.iter().map(|t| position(*t)).collect()
+                };
+
+                canvas.move_to(
+                    0.1f64 * canvas.width(),
+                    0.9f64 * canvas.height()
+                        - ((values[0] - *y_min) / y_max) * 0.9 * canvas.height(),
                 );
 
-                for dp in &ts {
-                    let days = dp.period.signed_duration_since(*x_min).num_days();
-                    let x = 0.1 * canvas.width() as f64
-                        + days as f64 / max_days as f64 * 0.85 * canvas.width() as f64;
-                    let y = 0.9 * canvas.height() as f64
-                        - (dp.v - *y_min) / *y_max * 0.9 * canvas.height() as f64;
-                    ctx.line_to(x, y);
-                    ctx.stroke();
+                let mut flagged = Vec::new();
+                for ((dp, position), marked) in ts.iter().zip(&positions).zip(&marked) {
+                    let x = 0.1 * canvas.width() + position * 0.85 * canvas.width();
+                    let y =
+                        0.9 * canvas.height() - (dp.v - *y_min) / *y_max * 0.9 * canvas.height();
+                    canvas.line_to(x, y);
+                    canvas.stroke();
+                    if *marked {
+                        flagged.push((x, y));
+                    }
+                }
+
+                canvas.set_fill_style_str("rgb(255, 200, 0)");
+                for (x, y) in flagged {
+                    canvas.begin_path();
+                    canvas.arc(x, y, 5.0, 0.0, std::f64::consts::TAU);
+                    canvas.fill();
+                }
+
+                // Each indicator point falls on one of the newest points.
+                for (key, line) in &overlays {
+                    let lead = positions.len().saturating_sub(line.len());
+                    canvas.set_stroke_style_str(indicator_color(key));
+                    canvas.begin_path();
+                    for (dp, position) in line.iter().zip(&positions[lead..]) {
+                        let x = 0.1 * canvas.width() + position * 0.85 * canvas.width();
+                        let y = 0.9 * canvas.height()
+                            - (dp.v - *y_min) / *y_max * 0.9 * canvas.height();
+                        canvas.line_to(x, y);
+                    }
+                    canvas.stroke();
+                }
+            }
+
+            // Compared symbols, rebased to the selected symbol's first value so
+            // they share its axis whatever their price level or currency.
+            let overlay = compressed && selected_price_series() != "volume";
+            if let (true, Some(first), Some(compared)) =
+                (overlay, ts.first(), &*compared.read_unchecked())
+            {
+                for (sym, closes) in compared.iter().filter(|(sym, _)| *sym != symbol().symbol) {
+                    let closes: Vec<_> = closes
+                        .iter()
+                        .filter(|(t, v)| *t >= *x_min && *t <= *x_max && *v > 0.0)
+                        .collect();
+                    let Some((start, base)) = closes.first() else {
+                        continue;
+                    };
+                    let scale = first.v / base;
+                    let lead = calendar.trading_duration(*x_min, *start).num_seconds() as f64;
+                    let times: Vec<_> = closes.iter().map(|(t, _)| *t).collect();
+
+                    canvas.set_stroke_style_str(comparison_color(sym));
+                    canvas.begin_path();
+                    for ((_, v), offset) in closes.iter().zip(calendar.trading_offsets(&times)) {
+                        let position = (lead + offset.num_seconds() as f64) / trading_span;
+                        let x = 0.1 * canvas.width() + position * 0.85 * canvas.width();
+                        let y = 0.9 * canvas.height()
+                            - (v * scale - *y_min) / *y_max * 0.9 * canvas.height();
+                        canvas.line_to(x, y);
+                    }
+                    canvas.stroke();
+                }
+            }
+
+            // Daily news sentiment as bars above the time axis.
+            if compressed && SENTIMENT_OVERLAY() {
+                if let Some(articles) = NEWS().get(&symbol().symbol) {
+                    let baseline = 0.8 * canvas.height();
+                    for day in daily_sentiment(default_scorer(), articles, calendar.timezone) {
+                        let t = calendar.at(day.date, calendar.regular.open);
+                        if t < *x_min || t > *x_max {
+                            continue;
+                        }
+                        let x = 0.1 * canvas.width() + position(t) * 0.85 * canvas.width();
+                        canvas.set_fill_style_str(if day.mean >= 0.0 {
+                            "rgba(0, 204, 68, 0.7)"
+                        } else {
+                            "rgba(238, 0, 0, 0.7)"
+                        });
+                        canvas.fill_rect(x - 2.0, baseline, 4.0, -day.mean * 0.1 * canvas.height());
+                    }
+                }
+            }
+
+            // Splits and ex-dividend dates along the time axis.
+            if compressed {
+                if let Some(actions) = &*actions.read_unchecked() {
+                    canvas.set_font("14px sans-serif");
+                    for (date, label) in actions.markers() {
+                        let t = calendar.at(date, calendar.regular.open);
+                        if t < *x_min || t > *x_max {
+                            continue;
+                        }
+                        let x = 0.1 * canvas.width() + position(t) * 0.85 * canvas.width();
+                        let color = if label.starts_with('S') {
+                            "rgb(255, 140, 0)"
+                        } else {
+                            "rgb(0, 200, 120)"
+                        };
+                        canvas.set_stroke_style_str(color);
+                        canvas.set_fill_style_str(color);
+                        canvas.begin_path();
+                        canvas.move_to(x, 0.9 * canvas.height());
+                        canvas.line_to(x, 0.86 * canvas.height());
+                        canvas.stroke();
+                        canvas.fill_text(label.as_str(), x + 2.0, 0.86 * canvas.height());
+                    }
+                }
+
+                // Earnings releases, placed at the open or close they land on.
+                if let Some(events) = EARNINGS().get(&symbol().symbol) {
+                    canvas.set_font("14px sans-serif");
+                    canvas.set_stroke_style_str("rgb(0, 170, 255)");
+                    canvas.set_fill_style_str("rgb(0, 170, 255)");
+                    for event in events {
+                        let t = event.time(calendar);
+                        if t < *x_min || t > *x_max {
+                            continue;
+                        }
+                        let x = 0.1 * canvas.width() + position(t) * 0.85 * canvas.width();
+                        canvas.begin_path();
+                        canvas.move_to(x, 0.9 * canvas.height());
+                        canvas.line_to(x, 0.83 * canvas.height());
+                        canvas.stroke();
+                        canvas.fill_text("E", x + 2.0, 0.83 * canvas.height());
+                    }
                 }
             }
         }
@@ -303,7 +674,7 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
         div {class:"flex flex-col justify-start items-center m-[0px] w-[100%] h-[50vh]",
             div { class:"w-[100%] h-[100%] relative flex flex-col",
                 div { class:"sticky z-50 top-[0px] left-[0px] flex flex-col gap-x-[0.5rem] h-[10%] w-[100%] rounded-t-[0.85rem] bg-[#000] m-[0px]",
-                    button {class:"text-[1.5rem] font-bold w-[100%] cursor-pointer m-[0px]", onclick: move |_| series_btn.set(!series_btn()), {format!("{}", if selected_tab() == "annual" && selected_annual_series().as_str() != "" {selected_annual_series()} else if selected_tab() == "quarterly" && selected_quarterly_series().as_str() != "" {selected_quarterly_series()} else {String::from("Time Series Data")}) }}
+                    button {class:"text-[1.5rem] font-bold w-[100%] cursor-pointer m-[0px]", onclick: move |_| series_btn.set(!series_btn()), {if selected_tab() == "annual" && selected_annual_series().as_str() != "" {selected_annual_series()} else if selected_tab() == "quarterly" && selected_quarterly_series().as_str() != "" {selected_quarterly_series()} else if selected_tab() == "price" && selected_price_series().as_str() != "" {selected_price_series()} else if selected_tab() == "statements" && selected_statement_series().as_str() != "" {selected_statement_series()} else {String::from("Time Series Data")} }}
         div {class:"flex flex-row", for (k, _) in series().into_iter() {
             {
                 let kpy = k.clone();
@@ -318,6 +689,78 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                 }
             }
         }}
+        div {class:"flex flex-row justify-end gap-x-[0.5rem] px-[0.5rem]",
+            if selected_tab() == "price" {
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Resolution", onchange:move |e| resolution.set(e.value()),
+                    for res in RESOLUTIONS {
+                        option {value:res, selected: resolution() == res, {resolution_label(res)}}
+                    }
+                }
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Bar type", onchange:move |e| bar_type.set(e.value()),
+                    for kind in BAR_KINDS {
+                        option {value:kind, selected: bar_type() == kind, {bar_kind_label(kind)}}
+                    }
+                }
+                for key in INDICATORS {
+                    button {class:"bg-[#000000] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", color:indicator_color(key),
+                        opacity: if indicators().iter().any(|i| i == key) {"1"} else {"0.5"},
+                        onclick:move |_| {
+                            let mut shown = indicators.write();
+                            match shown.iter().position(|i| i == key) {
+                                Some(i) => {
+                                    shown.remove(i);
+                                }
+                                None => shown.push(key.to_string()),
+                            }
+                        },
+                        {indicator_label(key)}
+                    }
+                }
+                for sym in COMPARISON() {
+                    {
+                        let removed = sym.to_owned();
+                        rsx! {
+                            button {class:"bg-[#000000] border-none cursor-pointer", color:comparison_color(&sym), title:"Remove from comparison",
+                                onclick:move |_| remove_from_comparison(&removed),
+                                "{sym} ✕"
+                            }
+                        }
+                    }
+                }
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Price adjustment", onchange:move |e| {
+                        if let Some(adjustment) = Adjustment::ALL.into_iter().find(|a| a.label() == e.value()) {
+                            update(|state| state.settings.adjustment = adjustment);
+                        }
+                    },
+                    for adjustment in Adjustment::ALL {
+                        option {value:adjustment.label(), selected: PRICE_ADJUSTMENT() == adjustment, {adjustment.label()}}
+                    }
+                }
+            }
+            select {class:"bg-[#000000] text-[#ffffff]", title:"Range", onchange:move |e| range.set(e.value()),
+                for r in RANGES {
+                    option {value:r, selected: range() == r, {range_label(r)}}
+                }
+            }
+            span {class:"text-[#ffc800]", title:quality().flags.iter().map(|f| format!("{}: {}", f.period.map(|p| p.to_rfc3339()).unwrap_or(format!("#{}", f.index)), f.issue.describe())).collect::<Vec<_>>().join("\n"),
+                {quality().summary()}
+            }
+            select {class:"bg-[#000000] text-[#ffffff]", title:"Data quality policy", onchange:move |e| {
+                    if let Some(policy) = Policy::ALL.into_iter().find(|p| p.label() == e.value()) {
+                        update(|state| state.settings.quality_policy = policy);
+                    }
+                },
+                for policy in Policy::ALL {
+                    option {value:policy.label(), selected: QUALITY_POLICY() == policy, {policy.label()}}
+                }
+            }
+            button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", onclick:move |_| {
+                    let display = TIME_DISPLAY().toggled();
+                    update(|state| state.settings.time_display = display);
+                },
+                {TIME_DISPLAY().label()}
+            }
+        }
     }
         for (k, v) in series().into_iter() {
             div {class:"absolute z-0 top-[5rem] left-[0rem] px-[0.5rem] flex flex-col w-[100%] h-[80%] overflow-y-scroll", visibility: if k.to_owned() == selected_tab() {"visible"} else {"hidden"},
@@ -339,6 +782,8 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                                             input {r#type:"radio", checked: match selected_tab().as_str() {
                                                 "annual" => s == selected_annual_series(),
                                                 "quarterly" => s == selected_quarterly_series(),
+                                                "price" => s == selected_price_series(),
+                                                "statements" => s == selected_statement_series(),
                                                 _ => false
                                             }, onchange:move |_| {
                                                 match y.as_str() {
@@ -354,6 +799,8 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                                                         // else { series.insert(s.to_owned()); }
                                                         selected_quarterly_series.set(s.to_owned());
                                                     },
+                                                    "price" => selected_price_series.set(s.to_owned()),
+                                                    "statements" => selected_statement_series.set(s.to_owned()),
                                                     _ => ()
                                                 }
                                             }}
@@ -377,6 +824,8 @@ pub fn ChartView(symbol: Signal<(String, String)>) -> Element {
                 canvas {visibility: match selected_tab().as_str() {
                 "annual" => if selected_annual_series() == String::from("") { "hidden"} else {"visible"},
                 "quarterly" => if selected_quarterly_series() == String::from("") { "hidden" } else {"visible"},
+                "price" => if selected_price_series().is_empty() { "hidden" } else {"visible"},
+                "statements" => if selected_statement_series().is_empty() { "hidden" } else {"visible"},
                 _ => "hidden"
             },width:"100%", height:"100%", class:"w-[100%] h-[100%] p-[1rem]", id:"chart"}}
             // p{"xmin: {xmin} xmax: {xmax} ymin: {ymin} ymax:{ymax}"}
@@ -391,12 +840,10 @@ async fn get_basic_financials(
     symbol: String,
     api_key: String,
 ) -> Result<Map<String, Value>, ServerFnError> {
-    let body = reqwest::get(&format!(
+    let body = crate::server::provider_get(&format!(
         "https://finnhub.io/api/v1/stock/metric?symbol={}&metric=all&token={api_key}",
         symbol
     ))
-    .await?
-    .text()
     .await?;
     let financials: serde_json::Value = serde_json::from_str(&body)?;
     Ok(financials.as_object().unwrap().clone())
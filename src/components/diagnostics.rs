@@ -0,0 +1,130 @@
+use crate::diagnostics::{clear, snapshot, Snapshot, DIAGNOSTICS_OPEN, PROVIDER_LIMIT_PER_MINUTE};
+use crate::quotes::sleep;
+use chrono::{DateTime, Local, Utc};
+use dioxus::logger::tracing::Level;
+use dioxus::prelude::*;
+
+/// How often the open panel re-reads the log buffer.
+const POLL_MILLIS: u64 = 1000;
+
+fn clock(time: DateTime<Utc>) -> String {
+    time.with_timezone(&Local).format("%H:%M:%S").to_string()
+}
+
+fn level_color(level: Level) -> &'static str {
+    if level == Level::ERROR {
+        "#ee0000"
+    } else if level == Level::WARN {
+        "#ffc800"
+    } else {
+        "inherit"
+    }
+}
+
+/// An overlay listing recent provider requests, warnings and errors, the
+/// provider rate-limit budget and component render timings.
+#[component]
+pub fn DiagnosticsPanel() -> Element {
+    let mut tab = use_signal(|| String::from("requests"));
+    let mut shown = use_signal(snapshot);
+
+    use_future(move || async move {
+        loop {
+            sleep(POLL_MILLIS).await;
+            let latest = snapshot();
+            if *shown.peek() != latest {
+                shown.set(latest);
+            }
+        }
+    });
+
+    let Snapshot {
+        requests,
+        log,
+        renders,
+        calls_last_minute,
+        throttled_at,
+    } = shown();
+    let near_limit = calls_last_minute * 5 >= PROVIDER_LIMIT_PER_MINUTE * 4;
+
+    rsx! {
+        div {class:"fixed right-[0.5rem] top-[2.5rem] bottom-[0.5rem] w-[36rem] flex flex-col p-[0.75rem] bg-[#000000] border-solid border-2 rounded-[0.75rem] overflow-hidden z-[10]",
+            div {class:"flex flex-row justify-between items-center w-[100%]",
+                h2 {class:"text-[1.5rem] font-bold my-[0.5rem]", "Diagnostics"}
+                div {class:"flex flex-row gap-x-[0.5rem]",
+                    for (key, label) in [("requests", "Requests"), ("log", "Errors"), ("renders", "Renders")] {
+                        button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", border_bottom: if tab() == key {"solid 2px #ffffff"} else {"none"},
+                            onclick:move |_| tab.set(key.to_string()),
+                            "{label}"
+                        }
+                    }
+                    button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer",
+                        onclick:move |_| {
+                            clear();
+                            shown.set(snapshot());
+                        },
+                        "Clear"
+                    }
+                    button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Close",
+                        onclick:move |_| *DIAGNOSTICS_OPEN.write() = false,
+                        "✕"
+                    }
+                }
+            }
+            p {class:"my-[0.25rem] text-[0.875rem]", color: if near_limit {"#ffc800"} else {"inherit"},
+                "{calls_last_minute} / {PROVIDER_LIMIT_PER_MINUTE} provider calls in the last minute"
+                if let Some(at) = throttled_at {
+                    span {class:"text-[#ee0000]", " · last throttled at {clock(at)}"}
+                }
+            }
+            div {class:"flex-1 min-h-[0px] overflow-y-scroll",
+                if tab() == "requests" {
+                    if requests.is_empty() {
+                        p {class:"text-[#888888]", "No provider requests yet."}
+                    }
+                    div {class:"grid grid-cols-[5rem_9rem_6rem_4rem_1fr] gap-x-[0.5rem] gap-y-[0.25rem] text-[0.875rem]",
+                        for heading in ["Time", "Call", "Subject", "ms", "Result"] {
+                            span {class:"font-bold", "{heading}"}
+                        }
+                        for request in requests {
+                            span {{clock(request.started)}}
+                            span {"{request.call}"}
+                            span {class:"font-bold", "{request.subject}"}
+                            span {{format!("{:.0}", request.millis)}}
+                            match request.error {
+                                Some(e) => rsx! { span {class:"text-[#ee0000] break-all", "{e}"} },
+                                None => rsx! { span {class:"text-[#00cc44]", "ok"} },
+                            }
+                        }
+                    }
+                } else if tab() == "log" {
+                    if log.is_empty() {
+                        p {class:"text-[#888888]", "Nothing logged."}
+                    }
+                    div {class:"grid grid-cols-[5rem_4rem_1fr] gap-x-[0.5rem] gap-y-[0.25rem] text-[0.875rem]",
+                        for entry in log {
+                            span {{clock(entry.time)}}
+                            span {color: level_color(entry.level),
+                                "{entry.level}"
+                            }
+                            span {class:"break-all", "{entry.message}"}
+                        }
+                    }
+                } else {
+                    div {class:"grid grid-cols-[1fr_5rem_5rem_5rem_5rem] gap-x-[0.5rem] gap-y-[0.25rem] text-[0.875rem]",
+                        for heading in ["Component", "Renders", "Last ms", "Mean ms", "Max ms"] {
+                            span {class:"font-bold", "{heading}"}
+                        }
+                        for (component, stats) in renders {
+                            span {"{component}"}
+                            span {"{stats.count}"}
+                            span {{format!("{:.2}", stats.last_ms)}}
+                            span {{format!("{:.2}", stats.mean_ms())}}
+                            span {{format!("{:.2}", stats.max_ms)}}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
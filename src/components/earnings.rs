@@ -0,0 +1,164 @@
+use crate::components::WATCHLIST;
+use crate::currency::compact;
+use crate::diagnostics;
+use crate::earnings::{load_earnings, upcoming_earnings, EarningsEvent, EARNINGS};
+use crate::platform::{bounds, Canvas};
+use crate::store::Instrument;
+use chrono::{Duration, Utc};
+use dioxus::prelude::*;
+
+fn percent(value: Option<f64>) -> String {
+    value.map(|p| format!("{p:+.1}%")).unwrap_or_default()
+}
+
+fn surprise_color(value: Option<f64>) -> &'static str {
+    match value {
+        Some(p) if p > 0.0 => "#00cc44",
+        Some(p) if p < 0.0 => "#ee0000",
+        _ => "#ffffff",
+    }
+}
+
+/// EPS surprise per reported quarter as bars either side of zero.
+fn draw_surprises(canvas: &Canvas, reported: &[EarningsEvent]) {
+    let (w, h) = (canvas.width(), canvas.height());
+    canvas.clear_rect(0.0, 0.0, w, h);
+
+    let surprises: Vec<f64> = reported
+        .iter()
+        .map(|e| e.eps_surprise().unwrap_or(0.0))
+        .collect();
+    let max = surprises.iter().fold(1.0f64, |m, s| m.max(s.abs()));
+    let zero = 0.45 * h;
+    let slot = 0.9 * w / surprises.len().max(1) as f64;
+
+    canvas.set_stroke_style_str("rgb(255, 255, 255)");
+    canvas.begin_path();
+    canvas.move_to(0.05 * w, zero);
+    canvas.line_to(0.95 * w, zero);
+    canvas.stroke();
+
+    canvas.set_font("12px sans-serif");
+    for (i, (event, surprise)) in reported.iter().zip(&surprises).enumerate() {
+        let x = 0.05 * w + i as f64 * slot;
+        let height = surprise / max * 0.4 * h;
+        canvas.set_fill_style_str(if *surprise >= 0.0 {
+            "rgb(0, 204, 68)"
+        } else {
+            "rgb(238, 0, 0)"
+        });
+        canvas.fill_rect(x + 0.15 * slot, zero, 0.7 * slot, -height);
+        canvas.set_fill_style_str("rgb(255, 255, 255)");
+        canvas.fill_text(&event.period(), x + 0.1 * slot, 0.97 * h);
+    }
+}
+
+#[component]
+pub fn EarningsPanel(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("EarningsPanel");
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+    let mut tab = use_signal(|| String::from("history"));
+
+    let ak = api_key.to_owned();
+    let _history = use_resource(move || {
+        let ak = ak.clone();
+        async move { load_earnings(symbol().symbol, ak).await }
+    });
+
+    let ak = api_key.to_owned();
+    let upcoming = use_resource(move || {
+        let ak = ak.clone();
+        let mut symbols: Vec<String> = WATCHLIST().into_iter().map(|i| i.symbol).collect();
+        if !symbol().symbol.is_empty() && !symbols.contains(&symbol().symbol) {
+            symbols.push(symbol().symbol);
+        }
+        let today = Utc::now().date_naive();
+        async move { upcoming_earnings(symbols, today, today + Duration::days(30), ak).await }
+    });
+
+    let events = EARNINGS()
+        .get(&symbol().symbol)
+        .cloned()
+        .unwrap_or_default();
+    let reported: Vec<EarningsEvent> = events.iter().filter(|e| e.is_reported()).cloned().collect();
+    let today = symbol().exchange.calendar().local_date(Utc::now());
+    let next = events
+        .iter()
+        .find(|e| e.date >= today && !e.is_reported())
+        .cloned();
+
+    use_effect(move || {
+        if tab() != "history" {
+            return;
+        }
+        let events = EARNINGS()
+            .get(&symbol().symbol)
+            .cloned()
+            .unwrap_or_default();
+        let reported: Vec<EarningsEvent> = events.into_iter().filter(|e| e.is_reported()).collect();
+        spawn(async move {
+            let Some(rect) = bounds("earnings-chart").await else {
+                return;
+            };
+            if let Some(canvas) = Canvas::open("earnings-chart", rect.width, rect.height) {
+                draw_surprises(&canvas, &reported);
+            }
+        });
+    });
+
+    rsx! {
+        div {class:"flex flex-col w-[100%] h-[100%] p-[0.75rem] overflow-y-scroll",
+            div {class:"flex flex-row justify-between items-center w-[100%]",
+                h2 {class:"text-[1.5rem] font-bold my-[0.5rem]", "Earnings"}
+                div {class:"flex flex-row gap-x-[0.5rem]",
+                    for (key, label) in [("history", "Surprises"), ("calendar", "Calendar")] {
+                        button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", border_bottom: if tab() == key {"solid 2px #ffffff"} else {"none"},
+                            onclick:move |_| tab.set(key.to_string()),
+                            "{label}"
+                        }
+                    }
+                }
+            }
+            if tab() == "history" {
+                if let Some(next) = next {
+                    p {class:"my-[0.25rem]",
+                        {format!("Next: {} {} · EPS est. {}", next.date.format("%b %d %Y"), next.timing(), next.eps_estimate.map(|e| format!("{e:.2}")).unwrap_or(String::from("n/a")))}
+                    }
+                }
+                canvas {id:"earnings-chart", class:"w-[100%] h-[8rem] shrink-0"}
+                div {class:"grid grid-cols-7 gap-x-[0.5rem] gap-y-[0.25rem] text-[0.875rem]",
+                    for heading in ["Period", "EPS est.", "EPS", "Surprise", "Rev. est.", "Revenue", "Surprise"] {
+                        span {class:"font-bold", "{heading}"}
+                    }
+                    for event in reported.iter().rev() {
+                        span {"{event.period()}"}
+                        span {{event.eps_estimate.map(|e| format!("{e:.2}")).unwrap_or_default()}}
+                        span {{event.eps_actual.map(|e| format!("{e:.2}")).unwrap_or_default()}}
+                        span {color:surprise_color(event.eps_surprise()), {percent(event.eps_surprise())}}
+                        span {{event.revenue_estimate.map(compact).unwrap_or_default()}}
+                        span {{event.revenue_actual.map(compact).unwrap_or_default()}}
+                        span {color:surprise_color(event.revenue_surprise()), {percent(event.revenue_surprise())}}
+                    }
+                }
+            } else {
+                match &*upcoming.read_unchecked() {
+                    Some(Ok(events)) if !events.is_empty() => rsx! {
+                        div {class:"grid grid-cols-[1fr_1fr_2fr_1fr] gap-x-[0.5rem] gap-y-[0.25rem] text-[0.875rem]",
+                            for heading in ["Date", "Symbol", "Time", "EPS est."] {
+                                span {class:"font-bold", "{heading}"}
+                            }
+                            for event in events.iter() {
+                                span {{event.date.format("%a %b %d").to_string()}}
+                                span {class:"font-bold", "{event.symbol}"}
+                                span {"{event.timing()}"}
+                                span {{event.eps_estimate.map(|e| format!("{e:.2}")).unwrap_or_default()}}
+                            }
+                        }
+                    },
+                    Some(Err(e)) => rsx! { p {class:"text-[#888888]", "{e}"} },
+                    _ => rsx! { p {class:"text-[#888888]", "No earnings scheduled in the next 30 days for the selected or watched symbols."} },
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::store::{
+    delete_workspace, export_workspace, import_workspace, load_workspace, save_workspace,
+    update_layout, Layout, Panel, Split, APP_STATE,
+};
+use dioxus::prelude::*;
+
+/// A pane with a title bar. Dragging the title onto another pane swaps the
+/// two; the buttons minimize the pane to its title bar or maximize it over
+/// the workspace. `flex` sizes it within its row or column.
+#[component]
+pub fn PanelFrame(
+    panel: Panel,
+    flex: String,
+    dragging: Signal<Option<Panel>>,
+    children: Element,
+) -> Element {
+    let minimized = APP_STATE.read().layout.is_minimized(panel);
+    let maximized = APP_STATE.read().layout.maximized == Some(panel);
+    let target = dragging().is_some_and(|p| p != panel);
+
+    rsx! {
+        div {class:"flex flex-col min-w-[0px] min-h-[0px] border-solid border-2 rounded-[0.75rem] overflow-hidden", flex:"{flex}",
+            border_color: if target {"#0000ee"} else {"#ffffff"},
+            ondragover:move |e| e.prevent_default(),
+            ondrop:move |e| {
+                e.prevent_default();
+                if let Some(from) = dragging() {
+                    update_layout(|l| l.swap(from, panel));
+                }
+                dragging.set(None);
+            },
+            div {class:"flex flex-row justify-between items-center px-[0.5rem] bg-[#111111] cursor-grab select-none", draggable:"true",
+                ondragstart:move |_| dragging.set(Some(panel)),
+                ondragend:move |_| dragging.set(None),
+                span {class:"text-[0.875rem] font-bold", {panel.label()}}
+                div {class:"flex flex-row gap-x-[0.25rem]",
+                    button {class:"bg-[transparent] text-[#ffffff] border-none cursor-pointer", title: if minimized {"Restore"} else {"Minimize"},
+                        onclick:move |_| update_layout(|l| l.toggle_minimized(panel)),
+                        if minimized {"▢"} else {"▁"}
+                    }
+                    button {class:"bg-[transparent] text-[#ffffff] border-none cursor-pointer", title: if maximized {"Restore"} else {"Maximize"},
+                        onclick:move |_| update_layout(|l| l.toggle_maximized(panel)),
+                        if maximized {"❐"} else {"□"}
+                    }
+                }
+            }
+            if !minimized {
+                div {class:"flex flex-col flex-1 min-h-[0px] overflow-hidden", {children}}
+            }
+        }
+    }
+}
+
+/// A bar between panes. Pressing it starts a resize, which the workspace
+/// follows with the pointer until release.
+#[component]
+pub fn Splitter(split: Split, resizing: Signal<Option<Split>>) -> Element {
+    let (class, cursor) = match split {
+        Split::LeftWidth => ("w-[0.75rem] h-[100%]", "col-resize"),
+        Split::LeftHeight | Split::MainHeight => ("h-[0.75rem] w-[100%]", "row-resize"),
+    };
+    let active = resizing() == Some(split);
+
+    rsx! {
+        div {class:"flex-none {class}", cursor,
+            background_color: if active {"#333333"} else {"transparent"},
+            onmousedown:move |e| {
+                e.prevent_default();
+                resizing.set(Some(split));
+            },
+        }
+    }
+}
+
+/// Percent-encodes `text` for a `data:` URL.
+fn data_url(text: &str) -> String {
+    let mut url = String::from("data:application/json;charset=utf-8,");
+    for b in text.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                url.push(b as char)
+            }
+            _ => url.push_str(&format!("%{b:02X}")),
+        }
+    }
+    url
+}
+
+/// Saves, loads, deletes, exports and imports named workspaces.
+#[component]
+pub fn WorkspaceMenu() -> Element {
+    let mut name = use_signal(String::new);
+    let mut error = use_signal(|| None::<String>);
+
+    let workspaces = APP_STATE.read().workspaces.clone();
+    let saved = workspaces.iter().any(|w| w.name == name());
+    let export = export_workspace(&name());
+
+    rsx! {
+        div {class:"flex flex-row items-center gap-x-[0.5rem] ml-auto text-[0.875rem]",
+            select {class:"bg-[#000000] text-[#ffffff]", title:"Saved workspaces", onchange:move |e| {
+                    load_workspace(&e.value());
+                    name.set(e.value());
+                },
+                option {value:"", selected: !saved, "Workspaces…"}
+                for workspace in workspaces {
+                    option {value:"{workspace.name}", selected: name() == workspace.name, "{workspace.name}"}
+                }
+            }
+            input {class:"bg-[#000000] text-[#ffffff] w-[8rem]", placeholder:"Workspace name", value:"{name}", onchange:move |e| name.set(e.value())}
+            button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", disabled: name().trim().is_empty(),
+                onclick:move |_| {
+                    let trimmed = name().trim().to_string();
+                    save_workspace(&trimmed);
+                    name.set(trimmed);
+                },
+                "Save"
+            }
+            if saved {
+                button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Delete saved workspace",
+                    onclick:move |_| {
+                        delete_workspace(&name());
+                        name.set(String::new());
+                    },
+                    "✕"
+                }
+            }
+            if let Some(json) = export {
+                a {class:"text-[#ffffff]", href: data_url(&json), download:"{name}.finoracle", "Export"}
+            }
+            label {class:"cursor-pointer underline", title:"Open a .finoracle workspace file",
+                "Import"
+                input {r#type:"file", accept:".finoracle,application/json", display:"none",
+                    onchange:move |e| async move {
+                        let Some(files) = e.files() else {
+                            return;
+                        };
+                        for file in files.files() {
+                            let Some(json) = files.read_file_to_string(&file).await else {
+                                continue;
+                            };
+                            match import_workspace(&json) {
+                                Ok(imported) => {
+                                    name.set(imported);
+                                    error.set(None);
+                                }
+                                Err(e) => error.set(Some(format!("{file}: {e}"))),
+                            }
+                        }
+                    }
+                }
+            }
+            button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Restore the default layout",
+                onclick:move |_| update_layout(|l| *l = Layout::default()),
+                "Reset"
+            }
+            if let Some(e) = error() {
+                span {class:"text-[#ee0000]", "{e}"}
+            }
+        }
+    }
+}
@@ -1,12 +1,111 @@
+use crate::platform::{scroll_metrics, set_scroll_top};
 use dioxus::prelude::*;
 
+/// Rows mounted above and below the viewport so fast scrolling does not
+/// show gaps.
+const OVERSCAN: usize = 8;
+
+/// A scrolling list that only mounts the rows in view. Rows have a fixed
+/// `row_height` in pixels; `row` renders the row at an index. Arrow keys,
+/// Page Up/Down and Home/End move `highlighted`, and Enter passes it to
+/// `onselect`. The scroll offset is kept when `len` changes, clamped to the
+/// new end of the list.
 #[component]
-pub fn ListView(class: &'static str, children: Element) -> Element {
+pub fn ListView(
+    id: String,
+    class: &'static str,
+    len: usize,
+    row_height: f64,
+    highlighted: Signal<Option<usize>>,
+    onselect: EventHandler<usize>,
+    row: Callback<usize, Element>,
+) -> Element {
+    let mut scroll_top = use_signal(|| 0f64);
+    let mut viewport = use_signal(|| 0f64);
+
+    let height = len as f64 * row_height;
+    let top = scroll_top().min((height - viewport()).max(0.0));
+    let visible = (viewport() / row_height).ceil() as usize + 1;
+    let first = ((top / row_height) as usize).saturating_sub(OVERSCAN);
+    let last = (first + visible + 2 * OVERSCAN).min(len);
+
+    // A shorter list can leave the highlight past its end.
+    let current = highlighted().filter(|h| *h < len);
+
+    let measure = {
+        let id = id.to_owned();
+        move || {
+            let id = id.to_owned();
+            spawn(async move {
+                if let Some((top, height)) = scroll_metrics(&id).await {
+                    scroll_top.set(top);
+                    viewport.set(height);
+                }
+            });
+        }
+    };
+    let on_mounted = measure.clone();
+    let on_scroll = measure.clone();
+    // Panels resize with the window and the workspace layout.
+    let on_resize = measure;
+
+    // Scrolling fires `onscroll`, which measures the new offset.
+    let scroll_into_view = {
+        let id = id.to_owned();
+        move |i: usize| {
+            let (row_top, row_bottom) = (i as f64 * row_height, (i + 1) as f64 * row_height);
+            let view_top = scroll_top();
+            if row_top < view_top {
+                set_scroll_top(&id, row_top);
+            } else if row_bottom > view_top + viewport() {
+                set_scroll_top(&id, row_bottom - viewport());
+            }
+        }
+    };
+
+    let onkeydown = move |e: Event<KeyboardData>| {
+        if len == 0 {
+            return;
+        }
+        let page = visible.saturating_sub(1).max(1);
+        let current = highlighted().filter(|h| *h < len);
+        let next = match e.key() {
+            Key::ArrowDown => current.map_or(0, |h| (h + 1).min(len - 1)),
+            Key::ArrowUp => current.map_or(0, |h| h.saturating_sub(1)),
+            Key::PageDown => current.map_or(0, |h| (h + page).min(len - 1)),
+            Key::PageUp => current.map_or(0, |h| h.saturating_sub(page)),
+            Key::Home => 0,
+            Key::End => len - 1,
+            Key::Enter => {
+                if let Some(h) = current {
+                    onselect.call(h);
+                }
+                return;
+            }
+            _ => return,
+        };
+        e.prevent_default();
+        highlighted.set(Some(next));
+        scroll_into_view(next);
+    };
+
     rsx! {
-        div {class:" w-[100%] h-[100%] overflow-y-scroll overflow-x-hidden {class}",
-        ul {
-    class: "list-none list-outside relative gap-y-[0px] m-[0px] top-[0.5rem] px-[0.5rem] pt-[0px] w-[100%] h-[90%] z-[1]",
-    {children}}
-    }
+        div {id:"{id}", class:"w-[100%] h-[100%] overflow-y-scroll overflow-x-hidden outline-none {class}", tabindex:"0",
+            onmounted:move |_| on_mounted(),
+            onscroll:move |_| on_scroll(),
+            onresize:move |_| on_resize(),
+            onkeydown,
+            div {class:"relative w-[100%]", height:"{height}px",
+                div {class:"absolute left-[0px] w-[100%]", top:"{first as f64 * row_height}px",
+                    for i in first..last {
+                        div {key:"{i}", class:"w-[100%] overflow-hidden", height:"{row_height}px",
+                            background_color: if current == Some(i) {"#222222"} else {"transparent"},
+                            onclick:move |_| highlighted.set(Some(i)),
+                            {row.call(i)}
+                        }
+                    }
+                }
+            }
+        }
     }
 }
@@ -1,6 +1,9 @@
 mod list_view;
 pub use list_view::*;
 
+mod layout;
+pub use layout::*;
+
 mod stock_view;
 pub use stock_view::*;
 
@@ -9,3 +12,24 @@ pub use chart_view::*;
 
 mod stock_list;
 pub use stock_list::*;
+
+mod watchlist;
+pub use watchlist::*;
+
+mod profile;
+pub use profile::*;
+
+mod news;
+pub use news::*;
+
+mod earnings;
+pub use earnings::*;
+
+mod statements;
+pub use statements::*;
+
+mod screener;
+pub use screener::*;
+
+mod diagnostics;
+pub use diagnostics::*;
@@ -0,0 +1,97 @@
+use crate::diagnostics;
+use crate::news::{load_news, news_between, SENTIMENT_OVERLAY};
+use crate::sentiment::{daily_sentiment, default_scorer, score_article, SentimentScorer};
+use crate::store::{update, Instrument};
+use chrono::{Duration, Local, NaiveDate, Utc};
+use dioxus::prelude::*;
+
+fn sentiment_color(score: f64) -> &'static str {
+    if score > 0.15 {
+        "#00cc44"
+    } else if score < -0.15 {
+        "#ee0000"
+    } else {
+        "#888888"
+    }
+}
+
+#[component]
+pub fn NewsPanel(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("NewsPanel");
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+
+    // Days are the exchange's, as in `daily_sentiment`.
+    let timezone = symbol().exchange.calendar().timezone;
+    let today = Utc::now().with_timezone(&timezone).date_naive();
+    let mut from = use_signal(move || today - Duration::days(7));
+    let mut to = use_signal(move || today);
+
+    let ak = api_key.to_owned();
+    let _news = use_resource(move || {
+        let ak = ak.clone();
+        // The provider takes UTC dates, which can start a day either side of
+        // the exchange's.
+        let (from, to) = (from() - Duration::days(1), to() + Duration::days(1));
+        async move { load_news(symbol().symbol, from, to, ak).await }
+    });
+
+    let scorer = default_scorer();
+    let articles = news_between(&symbol().symbol, from(), to(), timezone);
+    let days = daily_sentiment(scorer, &articles, timezone);
+    let overall = if articles.is_empty() {
+        0.0
+    } else {
+        days.iter().map(|d| d.mean * d.count as f64).sum::<f64>() / articles.len() as f64
+    };
+
+    rsx! {
+        div {class:"flex flex-col w-[100%] h-[100%] p-[0.75rem] overflow-y-scroll",
+            div {class:"flex flex-row justify-between items-center w-[100%]",
+                h2 {class:"text-[1.5rem] font-bold my-[0.5rem]", "News"}
+                div {class:"flex flex-row items-center gap-x-[0.5rem]",
+                    input {r#type:"date", class:"bg-[#000000] text-[#ffffff]", value:"{from}", max:"{to}",
+                        onchange:move |e| if let Ok(d) = e.value().parse::<NaiveDate>() { from.set(d) }
+                    }
+                    input {r#type:"date", class:"bg-[#000000] text-[#ffffff]", value:"{to}", min:"{from}",
+                        onchange:move |e| if let Ok(d) = e.value().parse::<NaiveDate>() { to.set(d) }
+                    }
+                    label {title:"Overlay daily sentiment on the price chart",
+                        input {r#type:"checkbox", checked:SENTIMENT_OVERLAY(), onchange:move |e| update(|state| state.settings.sentiment_overlay = e.checked())}
+                        "Chart"
+                    }
+                }
+            }
+            if !articles.is_empty() {
+                p {class:"my-[0.25rem]", title:"{scorer.name()} sentiment over {articles.len()} articles",
+                    "Sentiment "
+                    span {color:sentiment_color(overall), {format!("{overall:+.2}")}}
+                    for day in days.iter().rev() {
+                        span {class:"ml-[0.5rem] text-[0.875rem]", color:sentiment_color(day.mean), title:"{day.count} articles",
+                            {format!("{} {:+.2}", day.date.format("%b %d"), day.mean)}
+                        }
+                    }
+                }
+            } else {
+                p {class:"text-[#888888]", "No news for this period."}
+            }
+            for article in articles {
+                {
+                    let score = score_article(scorer, &article);
+                    let published = article
+                        .published()
+                        .map(|t| t.with_timezone(&Local).format("%b %d %H:%M").to_string())
+                        .unwrap_or_default();
+                    rsx! {
+                        div {class:"flex flex-row gap-x-[0.75rem] items-start py-[0.25rem] border-b-[1px] border-[#333333]",
+                            span {class:"font-bold w-[3rem] shrink-0", color:sentiment_color(score), {format!("{score:+.2}")}}
+                            div {class:"flex flex-col",
+                                a {class:"text-[#ffffff] font-bold", href:"{article.url}", target:"_blank", rel:"noopener", "{article.headline}"}
+                                p {class:"my-[0px] text-[#888888] text-[0.875rem]", "{article.source} · {published}"}
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
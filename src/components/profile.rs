@@ -0,0 +1,152 @@
+use crate::components::{add_to_comparison, COMPARISON};
+use crate::currency::{compact, Currency};
+use crate::diagnostics::traced;
+use crate::exchange::Exchange;
+use crate::fx::display_money;
+use crate::money::{Money, Quantity, MILLION};
+use crate::store::Instrument;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Company metadata from the profile endpoint. Counts and market cap are
+/// reported in millions.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CompanyProfile {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub ticker: String,
+    #[serde(default)]
+    pub exchange: String,
+    #[serde(default)]
+    pub country: String,
+    #[serde(default)]
+    pub currency: String,
+    #[serde(rename = "finnhubIndustry", default)]
+    pub industry: String,
+    #[serde(default)]
+    pub ipo: String,
+    #[serde(default)]
+    pub logo: String,
+    #[serde(rename = "weburl", default)]
+    pub website: String,
+    #[serde(rename = "marketCapitalization", default)]
+    pub market_cap: f64,
+    #[serde(rename = "shareOutstanding", default)]
+    pub shares_outstanding: f64,
+}
+
+#[component]
+pub fn Profile(symbol: Signal<Instrument>) -> Element {
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+
+    let ak = api_key.to_owned();
+    let profile = use_resource(move || {
+        let ak = ak.clone();
+        async move {
+            let symbol = symbol().symbol;
+            let request = get_company_profile(symbol.clone(), ak);
+            traced("company_profile", &symbol, request).await
+        }
+    });
+
+    let ak = api_key.to_owned();
+    let peers = use_resource(move || {
+        let ak = ak.clone();
+        async move {
+            let symbol = symbol().symbol;
+            traced("peers", &symbol, get_peers(symbol.clone(), ak)).await
+        }
+    });
+
+    let profile = match &*profile.read_unchecked() {
+        Some(Ok(profile)) if !profile.name.is_empty() => profile.clone(),
+        _ => return rsx! {},
+    };
+    let peers: Vec<String> = match &*peers.read_unchecked() {
+        Some(Ok(peers)) => peers
+            .iter()
+            .filter(|p| **p != symbol().symbol)
+            .cloned()
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let exchange = symbol().exchange;
+    let currency = Currency::from_code(&profile.currency).unwrap_or(exchange.currency());
+    let market_cap = display_money(Money::from_f64(profile.market_cap, currency) * MILLION);
+    let shares = compact((Quantity::from_f64(profile.shares_outstanding) * MILLION).to_f64());
+    let compared = peers.iter().all(|p| COMPARISON().contains(p));
+    let to_compare = peers.clone();
+
+    rsx! {
+        div {class:"flex flex-col gap-y-[0.5rem] w-[100%] py-[0.5rem]",
+            div {class:"flex flex-row items-center gap-x-[1rem]",
+                if !profile.logo.is_empty() {
+                    img {class:"h-[3rem] w-[3rem] rounded-[0.5rem] bg-[#ffffff]", src:"{profile.logo}", alt:"{profile.name} logo"}
+                }
+                div {class:"flex flex-col",
+                    h3 {class:"my-[0px]", "{profile.name}"}
+                    p {class:"my-[0px] text-[#888888]", "{profile.industry} · {profile.country}"}
+                }
+            }
+            div {class:"grid grid-cols-[8fr_1fr] gap-x-[3rem]",
+                h3 {"Market Cap"}
+                p {"{market_cap}"}
+                h3 {"Shares Outstanding"}
+                p {"{shares}"}
+                h3 {"IPO Date"}
+                p {"{profile.ipo}"}
+                h3 {"Website"}
+                a {class:"text-[#4488ff] my-auto", href:"{profile.website}", target:"_blank", rel:"noopener", "{profile.website}"}
+            }
+            if !peers.is_empty() {
+                div {class:"flex flex-row justify-between items-center",
+                    h3 {"Peers"}
+                    button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", disabled: compared,
+                        onclick:move |_| add_to_comparison(&to_compare),
+                        if compared {"Comparing peers"} else {"Add peers to comparison"}
+                    }
+                }
+                div {class:"flex flex-row flex-wrap gap-[0.5rem]",
+                    for peer in peers {
+                        {
+                            let selected = peer.clone();
+                            rsx! {
+                                button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer",
+                                    onclick:move |_| symbol.set(Instrument::new(&selected, "")),
+                                    {Exchange::from_symbol(&peer).display_symbol(&peer).to_string()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[server]
+async fn get_company_profile(
+    symbol: String,
+    api_key: String,
+) -> Result<CompanyProfile, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/stock/profile2?symbol={}&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let profile: CompanyProfile = serde_json::from_str(&body)?;
+    Ok(profile)
+}
+
+#[server]
+async fn get_peers(symbol: String, api_key: String) -> Result<Vec<String>, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/stock/peers?symbol={}&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let peers: Vec<String> = serde_json::from_str(&body)?;
+    Ok(peers)
+}
@@ -0,0 +1,206 @@
+use crate::components::WATCHLIST;
+use crate::diagnostics;
+use crate::exchange::Exchange;
+use crate::metrics::metric_info;
+use crate::screener::{
+    cancel_refresh, delete_screen, parse_symbols, refresh_metrics, run_screen, save_screen,
+    sort_matches, Rule, Screen, Universe, METRIC_STORE, REFRESH_PROGRESS, SCREENS, STORED_SYMBOLS,
+};
+use crate::store::{update, Instrument};
+use crate::EXCHANGE;
+use dioxus::prelude::*;
+
+/// Rows rendered at once; larger result sets are cut off with a note.
+const RESULT_LIMIT: usize = 200;
+
+#[component]
+pub fn Screener(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("Screener");
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+    let mut rule = use_signal(|| String::from("peTTM < 15 AND roeTTM > 0.15 AND marketCap > 2e9"));
+    let mut universe = use_signal(|| Universe::Exchange(EXCHANGE()));
+    let mut imported = use_signal(String::new);
+    let mut sort = use_signal(|| (0usize, true));
+    let mut name = use_signal(String::new);
+
+    let ak = api_key.to_owned();
+    let members = use_resource(move || {
+        let ak = ak.clone();
+        let universe = universe();
+        // Track watchlist edits only when screening the watchlist.
+        if universe == Universe::Watchlist {
+            let _ = WATCHLIST();
+        }
+        async move { universe.symbols(ak).await }
+    });
+    let members = members.read_unchecked().clone().unwrap_or_default();
+
+    let parsed = Rule::parse(&rule());
+    let fields = parsed.as_ref().map(|r| r.fields()).unwrap_or_default();
+    let store = METRIC_STORE();
+    let covered = members
+        .iter()
+        .filter(|(s, _)| store.contains_key(s))
+        .count();
+    let mut matches = match &parsed {
+        Ok(rule) => run_screen(rule, &members, &store),
+        Err(_) => Vec::new(),
+    };
+    sort_matches(&mut matches, sort().0, sort().1);
+    let total = matches.len();
+    matches.truncate(RESULT_LIMIT);
+    let none_matched = matches.is_empty();
+    let matched: Vec<Instrument> = matches
+        .iter()
+        .map(|m| Instrument::new(&m.symbol, &m.description))
+        .collect();
+
+    let symbols: Vec<String> = members.iter().map(|(s, _)| s.to_owned()).collect();
+    let ak = api_key.to_owned();
+
+    rsx! {
+        div {class:"flex flex-col w-[100%] h-[100%] p-[0.75rem] gap-y-[0.5rem] overflow-y-scroll",
+            div {class:"flex flex-row justify-between items-center w-[100%]",
+                h2 {class:"text-[1.5rem] font-bold my-[0.5rem]", "Screener"}
+                div {class:"flex flex-row items-center gap-x-[0.5rem]",
+                    select {class:"bg-[#000000] text-[#ffffff]", title:"Saved screens", onchange:move |e| {
+                            if let Some(screen) = SCREENS().into_iter().find(|s| s.name == e.value()) {
+                                rule.set(screen.rule);
+                                if let Universe::Imported(symbols) = &screen.universe {
+                                    imported.set(symbols.join(" "));
+                                }
+                                universe.set(screen.universe);
+                                name.set(screen.name);
+                            }
+                        },
+                        option {value:"", selected: name().is_empty(), "Saved screens…"}
+                        for screen in SCREENS() {
+                            option {value:"{screen.name}", selected: name() == screen.name, "{screen.name}"}
+                        }
+                    }
+                    input {class:"bg-[#000000] text-[#ffffff] w-[8rem]", placeholder:"Screen name", value:"{name}", onchange:move |e| name.set(e.value())}
+                    button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", disabled: name().trim().is_empty() || parsed.is_err(),
+                        onclick:move |_| save_screen(Screen { name: name().trim().to_string(), rule: rule(), universe: universe() }),
+                        "Save"
+                    }
+                    if SCREENS().iter().any(|s| s.name == name()) {
+                        button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Delete saved screen",
+                            onclick:move |_| {
+                                delete_screen(&name());
+                                name.set(String::new());
+                            },
+                            "✕"
+                        }
+                    }
+                }
+            }
+            div {class:"flex flex-row items-center gap-x-[0.5rem]",
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Universe", onchange:move |e| {
+                        let value = e.value();
+                        universe.set(match value.as_str() {
+                            "watchlist" => Universe::Watchlist,
+                            "imported" => Universe::Imported(parse_symbols(&imported())),
+                            country => Universe::Exchange(Exchange::from_country(country).unwrap_or(EXCHANGE())),
+                        });
+                    },
+                    for exchange in Exchange::ALL {
+                        option {value:exchange.country(), selected: universe() == Universe::Exchange(exchange), {exchange.name()}}
+                    }
+                    option {value:"watchlist", selected: universe() == Universe::Watchlist, "Watchlist"}
+                    option {value:"imported", selected: matches!(universe(), Universe::Imported(_)), "Imported list"}
+                }
+                span {class:"text-[0.875rem] text-[#888888]", {format!("{covered} of {} with metrics", members.len())}}
+                if members.len() > STORED_SYMBOLS {
+                    span {class:"text-[0.875rem] text-[#ffc800]", title:"Metrics are kept for {STORED_SYMBOLS} symbols at most. Larger universes are screened over those fetched most recently, and a refresh fetches at most {STORED_SYMBOLS}.",
+                        {format!("Limited to {STORED_SYMBOLS} symbols")}
+                    }
+                }
+                match REFRESH_PROGRESS() {
+                    Some((done, total)) => rsx! {
+                        span {class:"text-[0.875rem]", {format!("Refreshing {done}/{total}")}}
+                        button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", onclick:move |_| cancel_refresh(), "Stop"}
+                    },
+                    None => rsx! {
+                        button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", title:"Fetch missing and week-old metrics for up to {STORED_SYMBOLS} symbols, paced to the API rate limit",
+                            onclick:move |_| {
+                                let symbols = symbols.to_owned();
+                                let ak = ak.to_owned();
+                                spawn(async move { refresh_metrics(symbols, ak).await });
+                            },
+                            "Refresh metrics"
+                        }
+                    },
+                }
+            }
+            if matches!(universe(), Universe::Imported(_)) {
+                textarea {class:"bg-[#000000] text-[#ffffff] w-[100%] h-[3rem]", placeholder:"Paste symbols or a CSV with symbols in the first column", value:"{imported}",
+                    onchange:move |e| {
+                        imported.set(e.value());
+                        universe.set(Universe::Imported(parse_symbols(&e.value())));
+                    }
+                }
+            }
+            input {class:"bg-[#000000] text-[#ffffff] w-[100%] font-mono", value:"{rule}", onchange:move |e| rule.set(e.value()),
+                title:"Compare metric keys with < <= > >= = != and combine with AND, OR, NOT and parentheses. Amounts are in whole currency units (2e9 or 2B), percentages as fractions (0.15)."
+            }
+            match &parsed {
+                Err(e) => rsx! { p {class:"my-[0px] text-[#ee0000] text-[0.875rem]", "{e}"} },
+                Ok(_) => rsx! {
+                    div {class:"flex flex-row justify-between items-center",
+                        span {class:"text-[0.875rem]",
+                            {format!("{total} matches")}
+                            if total > RESULT_LIMIT { {format!(", showing the first {RESULT_LIMIT}")} }
+                        }
+                        button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", disabled: none_matched,
+                            onclick:move |_| {
+                                update(|state| {
+                                    for instrument in matched.iter() {
+                                        if !state.watchlist.iter().any(|w| w.symbol == instrument.symbol) {
+                                            state.watchlist.push(instrument.to_owned());
+                                        }
+                                    }
+                                });
+                            },
+                            "Add results to watchlist"
+                        }
+                    }
+                },
+            }
+            div {class:"grid gap-x-[0.75rem] gap-y-[0.25rem] text-[0.875rem] items-center", grid_template_columns:"minmax(4rem, 1fr) minmax(8rem, 3fr) repeat({fields.len()}, minmax(4rem, 1fr))",
+                span {class:"font-bold", "Symbol"}
+                span {class:"font-bold", "Name"}
+                for (i, field) in fields.iter().enumerate() {
+                    {
+                        let info = metric_info(field);
+                        rsx! {
+                            button {class:"bg-[#000000] text-[#ffffff] border-none font-bold text-right cursor-pointer", title:"{info.description}",
+                                onclick:move |_| sort.set((i, if sort().0 == i { !sort().1 } else { true })),
+                                {info.label}
+                                if sort().0 == i { {if sort().1 {" ▼"} else {" ▲"}} }
+                            }
+                        }
+                    }
+                }
+                for m in matches {
+                    {
+                        let exchange = Exchange::from_symbol(&m.symbol);
+                        let ticker = exchange.display_symbol(&m.symbol).to_string();
+                        let selected = Instrument::new(&m.symbol, &m.description);
+                        rsx! {
+                            button {class:"bg-[#000000] border-none font-bold text-left cursor-pointer", color: if m.symbol == symbol().symbol {"#0000ee"} else {"#ffffff"},
+                                onclick:move |_| symbol.set(selected.to_owned()),
+                                "{ticker}"
+                            }
+                            span {class:"truncate", "{m.description}"}
+                            for (field, value) in fields.iter().zip(m.values.iter()) {
+                                span {class:"text-right",
+                                    {value.as_ref().map(|v| metric_info(field).unit.format(v, exchange.currency())).unwrap_or_default()}
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::components::{ChartQuery, CHART_SELECTION};
+use crate::currency::compact;
+use crate::diagnostics::{self, traced};
+use crate::statements::{
+    get_financials_reported, Frequency, Statement, StatementTable, STATEMENT_SERIES,
+};
+use crate::store::Instrument;
+use dioxus::prelude::*;
+
+/// Reported periods shown side by side.
+const COLUMNS: usize = 4;
+
+#[component]
+pub fn Statements(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("Statements");
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+    let mut statement = use_signal(|| Statement::Income);
+    let mut frequency = use_signal(|| Frequency::Annual);
+
+    // Charted line items belong to the symbol they came from.
+    use_effect(move || {
+        let _ = symbol();
+        STATEMENT_SERIES.write().clear();
+    });
+
+    let ak = api_key.to_owned();
+    let filings = use_resource(move || {
+        let ak = ak.clone();
+        async move {
+            let symbol = symbol().symbol;
+            let request = get_financials_reported(symbol.clone(), frequency(), ak);
+            traced("financials_reported", &symbol, request).await
+        }
+    });
+
+    let table = match &*filings.read_unchecked() {
+        Some(Ok(filings)) => StatementTable::build(filings, statement()),
+        _ => StatementTable::default(),
+    };
+    let periods: Vec<_> = table.periods.iter().take(COLUMNS).cloned().collect();
+
+    rsx! {
+        div {class:"flex flex-col w-[100%] gap-y-[0.5rem] py-[0.5rem]",
+            div {class:"flex flex-row justify-between items-center",
+                h3 {class:"my-[0px]", "Financial Statements"}
+                div {class:"flex flex-row gap-x-[0.5rem]",
+                    select {class:"bg-[#000000] text-[#ffffff]", onchange:move |e| {
+                            if let Some(s) = Statement::ALL.into_iter().find(|s| s.label() == e.value()) {
+                                statement.set(s);
+                            }
+                        },
+                        for s in Statement::ALL {
+                            option {value:s.label(), selected: statement() == s, {s.label()}}
+                        }
+                    }
+                    select {class:"bg-[#000000] text-[#ffffff]", onchange:move |e| {
+                            frequency.set(if e.value() == "quarterly" { Frequency::Quarterly } else { Frequency::Annual });
+                        },
+                        option {value:"annual", selected: frequency() == Frequency::Annual, "Annual"}
+                        option {value:"quarterly", selected: frequency() == Frequency::Quarterly, "Quarterly"}
+                    }
+                }
+            }
+            match &*filings.read_unchecked() {
+                None => rsx! { p {class:"text-[#888888]", "Loading filings…"} },
+                Some(Err(e)) => rsx! { p {class:"text-[#888888]", "{e}"} },
+                Some(Ok(_)) if table.rows.is_empty() => rsx! { p {class:"text-[#888888]", "No reported statements."} },
+                Some(Ok(_)) => rsx! {
+                    div {class:"overflow-x-auto w-[100%]",
+                        div {class:"grid gap-x-[0.75rem] gap-y-[0.25rem] text-[0.875rem]", grid_template_columns:"minmax(10rem, 2fr) repeat({periods.len()}, minmax(5rem, 1fr)) auto",
+                            span {class:"font-bold", "Line item"}
+                            for (label, _) in periods.iter() {
+                                span {class:"font-bold text-right", "{label}"}
+                            }
+                            span {}
+                            for row in table.rows.iter() {
+                                {
+                                    let growth = row.growth();
+                                    let name = format!("{} ({})", row.label, frequency().param());
+                                    let series = row.to_chart_series(&table.periods);
+                                    rsx! {
+                                        span {title:"{row.concept}", "{row.label}"}
+                                        for (i, value) in row.values.iter().take(COLUMNS).enumerate() {
+                                            span {class:"flex flex-col items-end",
+                                                {value.map(compact).unwrap_or_default()}
+                                                if let Some(g) = growth[i] {
+                                                    span {class:"text-[0.75rem]", color: if g >= 0.0 {"#00cc44"} else {"#ee0000"}, {format!("{g:+.1}%")}}
+                                                }
+                                            }
+                                        }
+                                        button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Chart this line item",
+                                            onclick:move |_| {
+                                                STATEMENT_SERIES.write().insert(name.to_owned(), series.to_owned());
+                                                *CHART_SELECTION.write() = Some(ChartQuery::series("statements", &name));
+                                            },
+                                            "📈"
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+        }
+    }
+}
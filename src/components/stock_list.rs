@@ -1,23 +1,29 @@
-use crate::COUNTRY;
+use crate::components::ListView;
+use crate::diagnostics::{self, traced};
+use crate::exchange::Exchange;
+use crate::search::{highlight, Filters, SearchIndex, SecurityType};
+use crate::store::{update, Instrument};
+use crate::EXCHANGE;
 use dioxus::prelude::*;
 use serde_json::{Map, Value};
-use std::collections::BTreeMap;
 // use std::env;
 // use std::env::Vars;
 
-pub static STOCK_INFO: GlobalSignal<Map<String, Value>> =
-    Global::new(|| Map::<String, Value>::new());
+/// Height of a directory row in pixels.
+const ROW_HEIGHT: f64 = 28.0;
 
 #[component]
-pub fn StockList(symbol: Signal<(String, String)>) -> Element {
+pub fn StockList(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("StockList");
     let api_key: &'static str = env!("FINNHUB_API_KEY");
 
     let mut search_term = use_signal(|| String::from(""));
-
-    let country = use_signal(|| String::from("US"));
+    let mut kind: Signal<Option<SecurityType>> = use_signal(|| None);
+    let mut currency: Signal<Option<String>> = use_signal(|| None);
+    let mut highlighted: Signal<Option<usize>> = use_signal(|| None);
 
     let onvaluechange = move |e: Event<FormData>| {
-        *search_term.write() = e.value().to_uppercase();
+        *search_term.write() = e.value();
     };
 
     // let mut vars = env::vars();
@@ -34,95 +40,121 @@ pub fn StockList(symbol: Signal<(String, String)>) -> Element {
     // let ak = &api_key.1.to_owned();
     // let ak = ak.to_owned();
     let symbols = use_resource(move || {
-        // let country = COUNTRY();
         let ak = api_key.clone();
-        async move { fetch_symbols(country(), ak.to_string()).await }
+        async move {
+            let exchange = EXCHANGE();
+            let request = fetch_symbols(exchange, ak.to_string());
+            traced("symbols", exchange.code(), request).await
+        }
     });
 
-    let value = Value::String("".to_string());
-    let mut stock_list: BTreeMap<String, String> = {
-        let s = &*symbols.read_unchecked();
-        if let Some(s) = s {
-            // api_key = s.1.clone();
-            if let Ok(s) = s {
-                s.iter()
-                    .map(|s| {
-                        let symbol = &s
-                            .get("symbol")
-                            .unwrap_or(&value)
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
-                        let description = &s
-                            .get("description")
-                            .unwrap_or(&value)
-                            .as_str()
-                            .unwrap_or("")
-                            .to_string();
-                        (symbol.to_owned(), description.to_owned())
-                    })
-                    // .collect::<Vec<(String, String)>>()
-                    .into_iter()
-                    .collect::<BTreeMap<String, String>>()
-            } else {
-                BTreeMap::<String, String>::new()
-            }
-        } else {
-            BTreeMap::<String, String>::new()
-        }
-    };
+    // Built once per directory load rather than on every keystroke.
+    let index = use_memo(move || match &*symbols.read() {
+        Some(Ok(directory)) => SearchIndex::build(directory),
+        _ => SearchIndex::default(),
+    });
 
-    let find_match = move |sym: String| match &*symbols.read_unchecked() {
-        Some(Ok(s)) => s
-            .into_iter()
-            .find(|map| map["symbol"] == sym)
-            .unwrap_or(&Map::<String, Value>::new())
-            .to_owned(),
-        Some(Err(_)) => Map::<String, Value>::new(),
-        _ => Map::<String, Value>::new(),
-    };
+    // Currencies differ between directories.
+    use_effect(move || {
+        let _ = EXCHANGE();
+        currency.set(None);
+    });
 
-    // let stock_list = stock_list.unwrap_or(Vec::<(String, String)>::new());
-    // stock_list.sort();
+    // A symbol opened from a link arrives without its description.
+    use_effect(move || {
+        let selected = symbol();
+        if selected.is_empty() || !selected.description.is_empty() {
+            return;
+        }
+        if let Some(listing) = index
+            .read()
+            .listings
+            .iter()
+            .find(|l| l.symbol == selected.symbol)
+        {
+            symbol.set(Instrument::from_listing(listing));
+        }
+    });
+
+    // Rows look listings up by index rather than copying them.
+    let hits = use_memo(move || {
+        let filters = Filters {
+            kind: kind(),
+            currency: currency(),
+        };
+        index.read().search(&search_term(), &filters)
+    });
 
-    let stock_list = stock_list.into_iter().filter(|(symbol, desc)| {
-        symbol.as_str().contains(search_term().as_str())
-            || desc.as_str().contains(search_term().as_str())
+    // The highlight points into the previous results.
+    use_effect(move || {
+        let _ = (search_term(), kind(), currency(), EXCHANGE());
+        highlighted.set(None);
     });
 
+    let count = hits.read().len();
+
     rsx! {
         div { class:"flex flex-col justify-start items-center w-[100%] h-[99.5%]",
             div {border_bottom:"solid #fff", border_right: "none", border_top: "none", class:"flex flex-row justify-between items-center w-[100%] h-[4rem] overflow-y-clipped",
-                input {onchange:onvaluechange, class:"my-[0.6rem] px-[1rem] h-[95%] py-[0.25rem] w-[100%] text-[1.5rem] border-none  fixed z-[10] top-0 left-0 bg-[#000000] text-[#ffffff] relative", placeholder:"Search for a symbol..."}
+                input {oninput:onvaluechange, class:"my-[0.6rem] px-[1rem] h-[95%] py-[0.25rem] w-[100%] text-[1.5rem] border-none  fixed z-[10] top-0 left-0 bg-[#000000] text-[#ffffff] relative", placeholder:"Search by ticker, name, ISIN or FIGI..."}
                 div { class:"grid grid-cols-4 px-[1rem] h-[100%] gap-x-[0.25rem] items-center w-[60%]",
-                    div {class:"flex flex-col justify-center items-center",
-                        label {for:"US", "US"}
-                        input{id:"US", r#type:"radio", value:"US", checked: COUNTRY() == "US".to_string(), onchange:move |_| *COUNTRY.write() = "US".into()}}
-                    div {class:"flex flex-col justify-center items-center",
-                        label {for:"AU", "AU"}
-                        input{id:"AU", disabled:true, r#type:"radio", value:"AU", checked: COUNTRY() == "AU".to_string(), onchange:move |_| *COUNTRY.write() = "AU".into()}}
-                    div {class:"flex flex-col justify-center items-center",
-                        label {for:"UK", "UK"}
-                        input{id:"UK", disabled:true, r#type:"radio", value:"UK" , checked: COUNTRY() == "GB".to_string(), onchange:move |_| *COUNTRY.write() =  "GB".into()}}
-                    div {class:"flex flex-col justify-center items-center", label {for:"JP", "JP"}
-                        input{id:"JP", disabled:true, r#type:"radio", value:"JP", checked: COUNTRY() == "JP".to_string(), onchange:move |_| *COUNTRY.write() = "JP".into() }
+                    for exchange in Exchange::ALL {
+                        div {class:"flex flex-col justify-center items-center", title: exchange.name(),
+                            label {for:exchange.country(), {exchange.country()}}
+                            input{id:exchange.country(), name:"exchange", r#type:"radio", value:exchange.country(), checked: EXCHANGE() == exchange, onchange:move |_| update(|state| state.exchange = Some(exchange))}
+                        }
+                    }
+                }
+            }
+            div {class:"flex flex-row justify-start items-center gap-x-[0.5rem] w-[100%] px-[0.5rem] py-[0.25rem]",
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Security type", onchange:move |e| {
+                        kind.set(SecurityType::ALL.into_iter().find(|k| k.label() == e.value()));
+                    },
+                    option {value:"", selected: kind().is_none(), "All types"}
+                    for k in SecurityType::ALL {
+                        option {value:k.label(), selected: kind() == Some(k), {k.label()}}
+                    }
+                }
+                select {class:"bg-[#000000] text-[#ffffff]", title:"Currency", onchange:move |e| {
+                        currency.set(Some(e.value()).filter(|c| !c.is_empty()));
+                    },
+                    option {value:"", selected: currency().is_none(), "All currencies"}
+                    for code in index.read().currencies() {
+                        option {value:"{code}", selected: currency().as_deref() == Some(code.as_str()), "{code}"}
                     }
                 }
+                span {class:"text-[0.875rem] text-[#888888]", "{count} results"}
             }
             div {class:"border-[#ffffff] w-[100%] h-[90%] flex flex-col p-[0.125rem] overflow-x-hidden",
-                ul {class:"w-[100%] max-h-[90%] list-none list-outside ml-[0px] pl-[0.5rem]",
-            {stock_list.map(|(sym, desc)| {
-                rsx! {
-                        li { class: "text-[#ffffff] bg-[#000000] my-[0px] w-full",
-                            button {color: if sym.to_owned() == symbol().0 {"#0000ee"} else {"#ffffff"} , onclick:move |_| { symbol.set((sym.to_owned(), desc.to_owned())); *STOCK_INFO.write() = find_match(sym.to_owned())},class:"border-none grid-cols-4 hover:cursor-pointer hover:text-[#0000ee] bg-[#000000] text-left text-[#ffffff] w-[100%] font-bold text-[1.125rem] my-[0px] py-0 mx-[5px]", display:"grid",
-                                div {class:"text-left flex flex-row justify-start items-center", {sym.to_owned()}}
-                        div {class:"flex col-span-3 flex-row justify-start items-center text-left", {desc.to_owned()}}
+                ListView {id:"symbol-directory", class:"pl-[0.5rem]", len: count, row_height: ROW_HEIGHT, highlighted,
+                    onselect:move |i: usize| {
+                        if let Some(hit) = hits.read().get(i) {
+                            symbol.set(Instrument::from_listing(&index.read().listings[hit.index]));
+                        }
+                    },
+                    row:move |i: usize| {
+                        let hits = hits.read();
+                        let Some(hit) = hits.get(i) else {
+                            return rsx! {};
+                        };
+                        let index = index.read();
+                        let listing = &index.listings[hit.index];
+                        let selected = Instrument::from_listing(listing);
+                        rsx! {
+                            button {color: if listing.symbol == symbol().symbol {"#0000ee"} else {"#ffffff"}, onclick:move |_| symbol.set(selected.to_owned()), class:"border-none grid-cols-4 hover:cursor-pointer hover:text-[#0000ee] bg-[transparent] text-left text-[#ffffff] w-[100%] h-[100%] font-bold text-[1.125rem] my-[0px] py-0 mx-[5px]", display:"grid",
+                                div {class:"text-left flex flex-row justify-start items-center",
+                                    for (segment, marked) in highlight(&listing.display_symbol, &hit.symbol_spans) {
+                                        span {text_decoration: if marked {"underline"} else {"none"}, color: if marked {"#ffc800"} else {"inherit"}, "{segment}"}
+                                    }
+                                }
+                                div {class:"flex col-span-3 flex-row justify-start items-center text-left whitespace-pre overflow-hidden", title:"{listing.kind.label()} · {listing.currency}",
+                                    for (segment, marked) in highlight(&listing.description, &hit.name_spans) {
+                                        span {text_decoration: if marked {"underline"} else {"none"}, color: if marked {"#ffc800"} else {"inherit"}, "{segment}"}
+                                    }
+                                }
                             }
                         }
-                }
-
-            })
-            }
+                    }
                 }
             }
         }
@@ -130,12 +162,15 @@ pub fn StockList(symbol: Signal<(String, String)>) -> Element {
 }
 
 #[server]
-async fn fetch_symbols(
-    country: String,
+pub async fn fetch_symbols(
+    exchange: Exchange,
     api_key: String,
 ) -> Result<Vec<Map<String, Value>>, ServerFnError> {
-    let url = format!("https://finnhub.io/api/v1/stock/symbol?exchange={country}&token={api_key}");
-    let body = reqwest::get(&url).await?.text().await?;
+    let url = format!(
+        "https://finnhub.io/api/v1/stock/symbol?exchange={}&token={api_key}",
+        exchange.code()
+    );
+    let body = crate::server::provider_get(&url).await?;
     let symbols: serde_json::Value = serde_json::from_str(&body)?;
     let symbols = Vec::<Map<String, Value>>::from(
         symbols
@@ -1,6 +1,16 @@
-use crate::STOCK_INFO;
+use crate::components::{
+    toggle_watchlist, ChartQuery, MarketStatus, Profile, Statements, CHART_SELECTION, WATCHLIST,
+};
+use crate::diagnostics::{self, traced};
+use crate::fx::{display_money, CurrencySelect};
+use crate::metrics::{catalog_position, history, metric_info, Category, History, MetricInfo};
+use crate::money::{Money, Price, Rounding};
+use crate::platform::Canvas;
+use crate::quotes::{QuoteState, QUOTES};
+use crate::store::Instrument;
+use chrono::Local;
 use dioxus::prelude::*;
-use serde_json::{Map, Number, Value};
+use serde_json::{Map, Value};
 // use std::env;
 
 // fn get_api_key() -> String {
@@ -10,78 +20,114 @@ use serde_json::{Map, Number, Value};
 //         .1
 // }
 
+/// A metric with its reported value and, when the provider has one, its
+/// history.
+type MetricRow = (MetricInfo, Value, Option<History>);
+
+/// Draws `values` as a line filling the canvas, marking the latest point.
+fn draw_sparkline(canvas: &Canvas, values: &[f64], color: &str) {
+    let (w, h) = (canvas.width(), canvas.height());
+    canvas.clear_rect(0.0, 0.0, w, h);
+    if values.len() < 2 {
+        return;
+    }
+
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let range = if max > min { max - min } else { 1.0 };
+    let step = (w - 4.0) / (values.len() - 1) as f64;
+    let point = |i: usize, v: f64| {
+        (
+            2.0 + i as f64 * step,
+            h - 2.0 - (v - min) / range * (h - 4.0),
+        )
+    };
+
+    canvas.set_stroke_style_str(color);
+    canvas.set_line_width(1.5);
+    canvas.begin_path();
+    for (i, v) in values.iter().enumerate() {
+        let (x, y) = point(i, *v);
+        if i == 0 {
+            canvas.move_to(x, y);
+        } else {
+            canvas.line_to(x, y);
+        }
+    }
+    canvas.stroke();
+
+    let (x, y) = point(values.len() - 1, values[values.len() - 1]);
+    canvas.set_fill_style_str(color);
+    canvas.begin_path();
+    canvas.arc(x, y, 2.0, 0.0, std::f64::consts::TAU);
+    canvas.fill();
+}
+
 #[component]
-pub fn StockView(symbol: Signal<(String, String)>) -> Element {
+pub fn StockView(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("StockView");
     let api_key: &'static str = env!("FINNHUB_API_KEY");
 
-    let ak = api_key.to_owned();
-    let stock = use_resource(move || {
-        let ak = ak.clone();
-        async move { get_stock_quote(symbol().0, ak).await }
-    });
-
     let ak = api_key.to_owned();
     let financials = use_resource(move || {
         let ak = ak.clone();
-        async move { get_basic_financials(symbol().0, ak.to_string()).await }
+        async move {
+            let symbol = symbol().symbol;
+            let request = get_basic_financials(symbol.clone(), ak.to_string());
+            traced("basic_financials", &symbol, request).await
+        }
     });
 
-    let quote = stock
-        .read_unchecked()
-        .to_owned()
-        .unwrap_or(Ok(Map::<String, Value>::new()))
-        .unwrap_or(Map::<String, Value>::new());
+    let state = QUOTES().get(&symbol().symbol).cloned();
+    let quote = state
+        .as_ref()
+        .map(|s| s.quote.to_owned())
+        .unwrap_or_default();
 
-    let current_price = quote
-        .to_owned()
-        .get("c")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap()
-        .to_string();
+    let Instrument {
+        exchange, currency, ..
+    } = symbol();
+    let hidden = symbol().is_empty();
 
-    let opening_price = quote
-        .to_owned()
-        .get("o")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap()
-        .to_string();
-    let previous_close = quote
-        .to_owned()
-        .get("pc")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap()
-        .to_string();
-    let high = quote
-        .to_owned()
-        .get("h")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap()
-        .to_string();
-    let low = quote
-        .to_owned()
-        .get("l")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap()
-        .to_string();
-    let change = quote
-        .to_owned()
-        .get("d")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap_or(-9999.0)
-        .to_string();
+    // Quotes arrive as floats, so snap prices back onto the exchange's tick
+    // grid, widening the day's range rather than narrowing it.
+    let tick_size = symbol().tick_size();
+    let price = |amount: f64, rounding: Rounding| {
+        let price = tick_size.round(Price::from_f64(amount), rounding);
+        display_money(Money::new(price.0, currency))
+    };
+
+    let current_price = price(quote.current, Rounding::Nearest);
+    let opening_price = price(quote.open, Rounding::Nearest);
+    let previous_close = price(quote.previous_close, Rounding::Nearest);
+    let high = price(quote.high, Rounding::Up);
+    let low = price(quote.low, Rounding::Down);
+    let change = state
+        .as_ref()
+        .and_then(QuoteState::change)
+        .map(display_money)
+        .unwrap_or_default();
     let change_percent = quote
-        .to_owned()
-        .get("dp")
-        .unwrap_or(&Value::Number(Number::from_f64(0.0).unwrap()))
-        .as_f64()
-        .unwrap_or(-9999.0)
-        .to_string();
+        .change_percent
+        .map(|p| format!("{p:.2}"))
+        .unwrap_or_default();
+
+    let flash = state
+        .as_ref()
+        .and_then(|s| s.flash)
+        .map(|m| m.color())
+        .unwrap_or("#ffffff");
+    let updated = state
+        .as_ref()
+        .map(|s| {
+            s.updated
+                .with_timezone(&Local)
+                .format("%H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let stale = state.as_ref().is_some_and(|s| s.is_stale());
+    let watched = WATCHLIST().iter().any(|w| w.symbol == symbol().symbol);
 
     let metrics = financials
         .read_unchecked()
@@ -93,6 +139,37 @@ pub fn StockView(symbol: Signal<(String, String)>) -> Element {
         .unwrap_or(&Value::Object(Map::<String, Value>::new()))
         .to_owned();
 
+    let series = financials
+        .read_unchecked()
+        .to_owned()
+        .and_then(|f| f.ok())
+        .and_then(|f| f.get("series").cloned())
+        .unwrap_or(Value::Null);
+
+    // Catalogued metrics keep the catalog's order within each section;
+    // unknown keys fall to the end, alphabetically.
+    let mut entries: Vec<MetricRow> = metrics
+        .as_object()
+        .map(|m| {
+            m.iter()
+                .map(|(k, v)| (metric_info(k), v.to_owned(), history(k, &series)))
+                .collect()
+        })
+        .unwrap_or_default();
+    entries.sort_by_key(|(info, ..)| (catalog_position(&info.key), info.label.to_owned()));
+    let sections: Vec<(Category, Vec<MetricRow>)> = Category::ALL
+        .into_iter()
+        .map(|category| {
+            let rows = entries
+                .iter()
+                .filter(|(info, ..)| info.category == category)
+                .cloned()
+                .collect::<Vec<_>>();
+            (category, rows)
+        })
+        .filter(|(_, rows)| !rows.is_empty())
+        .collect();
+
     let desc = financials
         .read_unchecked()
         .to_owned()
@@ -106,86 +183,109 @@ pub fn StockView(symbol: Signal<(String, String)>) -> Element {
         .unwrap_or(format!("{quote:?}").as_str())
         .to_owned();
 
+    use_effect(move || {
+        let Some(Ok(financials)) = &*financials.read_unchecked() else {
+            return;
+        };
+        let (Some(metrics), Some(series)) = (
+            financials.get("metric").and_then(|m| m.as_object()),
+            financials.get("series"),
+        ) else {
+            return;
+        };
+        for key in metrics.keys() {
+            let Some(past) = history(key, series) else {
+                continue;
+            };
+            if let Some(canvas) = Canvas::open(&format!("sparkline-{key}"), 80.0, 20.0) {
+                draw_sparkline(&canvas, &past.values, past.trend().color());
+            }
+        }
+    });
+
     rsx! {
              div {class:"w-[100%] h-[100%] flex flex-col p-[0.75rem] m-auto w-[100%] overflow-y-scroll relative",
                  div {position:"sticky",
-                     h2 {class:"text-[#ffffff] text-center text-[1.5rem] font-bold",{format!("{}", symbol().1)}}
-                     h3 {class:"text-[#ffffff] text-[1rem] text-center", {format!("{}", symbol().0)}}
+                     h2 {class:"text-[#ffffff] text-center text-[1.5rem] font-bold",{symbol().description}}
+                     h3 {class:"text-[#ffffff] text-[1rem] text-center", {format!("{} · {} · {}", symbol().ticker(), exchange.name(), currency.code())}}
+                     div {class:"flex flex-row justify-center items-center gap-x-[1rem]", visibility: if hidden {"hidden"} else {"visible"},
+                         MarketStatus {exchange}
+                         CurrencySelect {}
+                         button {class:"bg-[#000000] text-[#ffffff] border-solid border-[1px] rounded-[0.5rem] cursor-pointer", onclick:move |_| toggle_watchlist(symbol()),
+                             if watched {"★ Watching"} else {"☆ Watch"}
+                         }
+                     }
                  }
 
-             div {class:"grid grid-cols-[8fr_1fr] gap-x-[3rem] h-[100%] w-[100%]", visibility: if STOCK_INFO().get("symbol") == None {"hidden"} else {"visible"},
+             div {visibility: if hidden {"hidden"} else {"visible"},
+                 Profile {symbol}
+                 Statements {symbol}
+             }
+
+             div {class:"grid grid-cols-[8fr_1fr] gap-x-[3rem] h-[100%] w-[100%]", visibility: if hidden {"hidden"} else {"visible"},
                  h3 {"Current Price"}
-                 p {"${current_price}"}
+                 p {transition:"color 1s", color:flash, "{current_price}"}
+                 h3 {"Last Updated"}
+                 p {color: if stale {"#ffaa00"} else {"#ffffff"}, "{updated}" if stale {" (stale)"}}
                  h3 {"Previous Close"}
-                 p {"${previous_close}"}
+                 p {"{previous_close}"}
                  h3 {"Opening Price"}
-                 p {"${opening_price}"}
+                 p {"{opening_price}"}
                  h3 {"Change"}
-                 p {"${change}"}
+                 p {"{change}"}
                  h3 {"Change Percent"}
                  p {"{change_percent}%"}
                  h3 {"High"}
-                 p {"${high}"}
+                 p {"{high}"}
                  h3 {"Low"}
-                 p {"${low}"}
-                 for (k, v) in metrics.as_object().unwrap().into_iter() {
-                     div {class:"flex flex-col justify-center items-start", h3 {{k.chars().fold(String::from(""), |a, e|
-                         {
-                             let len = &a.len();
-                             if *len > 0  {
-                                 let prev_c = a.chars().nth(len - 1).unwrap();
-                                 if ((prev_c.is_alphabetic() && prev_c.is_lowercase()) || e.is_alphabetic() &&
-                                     prev_c.is_digit(10))
-                                 && e == e.to_ascii_uppercase()
-                                 {a + " " + &e.to_string()}
-                                 else { a + &e.to_string()}
-                             }
-                             else {
-                                 a + e.to_ascii_uppercase().to_string().as_str()
+                 p {"{low}"}
+             }
+
+             div {class:"flex flex-col w-[100%] gap-y-[0.5rem]", visibility: if hidden {"hidden"} else {"visible"},
+                 for (category, entries) in sections {
+                     details {open: category != Category::Other,
+                         summary {class:"text-[1.125rem] font-bold cursor-pointer", {format!("{} ({})", category.label(), entries.len())}}
+                         div {class:"grid grid-cols-[6fr_2fr_1fr] gap-x-[3rem] items-center",
+                             for (info, v, past) in entries {
+                                 {
+                                     let selection = past.as_ref().map(|h| ChartQuery::series(h.tab, &h.series));
+                                     rsx! {
+                                         div {class:"contents", cursor: if selection.is_some() {"pointer"} else {"default"},
+                                             onclick:move |_| {
+                                                 if let Some(selection) = selection.to_owned() {
+                                                     *CHART_SELECTION.write() = Some(selection);
+                                                 }
+                                             },
+                                             h3 {class:"cursor-help", title:"{info.description}", "{info.label}"}
+                                             div {class:"flex flex-row items-center gap-x-[0.25rem]",
+                                                 if let Some(h) = past {
+                                                     canvas {id:"sparkline-{info.key}", width:"80", height:"20", title:"{h.tab} history · click to chart"}
+                                                     span {color:h.trend().color(), {h.trend().arrow()}}
+                                                 }
+                                             }
+                                             p {{info.unit.format(&v, currency)}}
+                                         }
+                                     }
+                                 }
                              }
                          }
-                     )}}}
-                     div {class:"flex flex-col justify-center items-start",
-                         p {{  match v.to_owned() {
-                             Value::Number(n) => n.to_string(),
-                             Value::String(s) => s,
-                             _ => String::new()
-                         }}}
-                    }}
-
+                     }
+                 }
              // p{"{financials:?}"}
              }
         }
     }
 }
 
-#[server]
-async fn get_stock_quote(
-    symbol: String,
-    api_key: String,
-) -> Result<Map<String, Value>, ServerFnError> {
-    let body = reqwest::get(&format!(
-        "https://finnhub.io/api/v1/quote?symbol={}&token={api_key}",
-        symbol
-    ))
-    .await?
-    .text()
-    .await?;
-    let quote: serde_json::Value = serde_json::from_str(&body)?;
-    Ok(quote.as_object().unwrap().clone())
-}
-
 #[server]
 async fn get_basic_financials(
     symbol: String,
     api_key: String,
 ) -> Result<Map<String, Value>, ServerFnError> {
-    let body = reqwest::get(&format!(
+    let body = crate::server::provider_get(&format!(
         "https://finnhub.io/api/v1/stock/metric?symbol={}&metric=all&token={api_key}",
         symbol
     ))
-    .await?
-    .text()
     .await?;
     let financials: serde_json::Value = serde_json::from_str(&body)?;
     Ok(financials.as_object().unwrap().clone())
@@ -0,0 +1,104 @@
+use crate::calendar::Session;
+use crate::diagnostics;
+use crate::exchange::Exchange;
+use crate::fx::display_money;
+use crate::quotes::{MARKET_SESSIONS, QUOTES, QUOTE_REFRESH_SECS};
+use crate::store::{update, Instrument, Settings, APP_STATE};
+use crate::EXCHANGE;
+use chrono::Local;
+use dioxus::prelude::*;
+
+pub static WATCHLIST: GlobalMemo<Vec<Instrument>> =
+    Memo::global(|| APP_STATE.read().watchlist.clone());
+
+pub fn toggle_watchlist(instrument: Instrument) {
+    update(|state| {
+        let watchlist = &mut state.watchlist;
+        if let Some(i) = watchlist.iter().position(|w| w.symbol == instrument.symbol) {
+            watchlist.remove(i);
+        } else if !instrument.is_empty() {
+            watchlist.push(instrument);
+        }
+    });
+}
+
+#[component]
+pub fn MarketStatus(exchange: Exchange) -> Element {
+    let session = MARKET_SESSIONS()
+        .get(&exchange)
+        .copied()
+        .unwrap_or(Session::Closed);
+    let color = match session {
+        Session::Regular => "#00cc44",
+        Session::PreMarket | Session::PostMarket | Session::Lunch => "#ffaa00",
+        Session::Closed => "#ee0000",
+    };
+    let label = session.label();
+
+    rsx! {
+        span {class:"text-[0.875rem] font-bold px-[0.5rem] rounded-[0.5rem]", color, border:"solid 1px {color}", title: exchange.name(), {label}}
+    }
+}
+
+#[component]
+pub fn Watchlist(symbol: Signal<Instrument>) -> Element {
+    let _render = diagnostics::render_timer("Watchlist");
+    rsx! {
+        div {class:"flex flex-col w-[100%] h-[100%] p-[0.75rem] overflow-y-scroll",
+            div {class:"flex flex-row justify-between items-center w-[100%]",
+                h2 {class:"text-[1.5rem] font-bold my-[0.5rem]", "Watchlist"}
+                div {class:"flex flex-row items-center gap-x-[1rem]",
+                    MarketStatus {exchange: EXCHANGE()}
+                    label {for:"refresh", "Refresh"}
+                    select {id:"refresh", class:"bg-[#000000] text-[#ffffff]", onchange:move |e| {
+                            if let Ok(secs) = e.value().parse::<u64>() { update(|state| state.settings.quote_refresh_secs = secs); }
+                        },
+                        for secs in Settings::REFRESH_SECS {
+                            option {value:"{secs}", selected: QUOTE_REFRESH_SECS() == secs, "{secs}s"}
+                        }
+                    }
+                }
+            }
+            if WATCHLIST().is_empty() {
+                p {class:"text-[#888888]", "Add symbols from the detail panel to follow their quotes."}
+            }
+            div {class:"grid grid-cols-[1fr_3fr_1fr_1fr_1fr_auto] gap-x-[1rem] gap-y-[0.25rem] items-center w-[100%]",
+                for instrument in WATCHLIST() {
+                    {
+                        let state = QUOTES().get(&instrument.symbol).cloned();
+                        let ticker = instrument.ticker().to_string();
+                        let selected = instrument.to_owned();
+                        let removed = instrument.to_owned();
+                        rsx! {
+                            button {class:"bg-[#000000] border-none font-bold text-left cursor-pointer text-[1.125rem]", color: if instrument.symbol == symbol().symbol {"#0000ee"} else {"#ffffff"},
+                                onclick:move |_| symbol.set(selected.to_owned()),
+                                "{ticker}"
+                            }
+                            p {class:"my-[0px]", "{instrument.description}"}
+                            match state {
+                                Some(state) => rsx! {
+                                    p {class:"my-[0px] font-bold", transition:"color 1s", color: state.flash.map(|m| m.color()).unwrap_or("#ffffff"),
+                                        {display_money(state.money(state.quote.current))}
+                                    }
+                                    p {class:"my-[0px]", color: if state.change().is_some_and(|c| c.amount.is_sign_negative()) {"#ee0000"} else {"#00cc44"},
+                                        {format!("{:+.2}%", state.quote.change_percent.unwrap_or(0.0))}
+                                    }
+                                    p {class:"my-[0px] text-[0.875rem]", color: if state.is_stale() {"#ffaa00"} else {"#888888"},
+                                        {state.updated.with_timezone(&Local).format("%H:%M:%S").to_string()}
+                                        if state.is_stale() { " (stale)" }
+                                    }
+                                },
+                                None => rsx! {
+                                    p {class:"my-[0px]", "…"}
+                                    p {class:"my-[0px]"}
+                                    p {class:"my-[0px]"}
+                                }
+                            }
+                            button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", onclick:move |_| toggle_watchlist(removed.to_owned()), "✕"}
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,396 @@
+use crate::bars::Bar;
+use crate::calendar::TradingCalendar;
+use crate::diagnostics::traced;
+use crate::platform;
+use crate::store::APP_STATE;
+use chrono::{Duration, NaiveDate, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// How price history is adjusted for corporate actions before charting.
+pub static PRICE_ADJUSTMENT: GlobalMemo<Adjustment> =
+    Memo::global(|| APP_STATE.read().settings.adjustment);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjustment {
+    /// Prices as traded.
+    Raw,
+    /// Earlier prices scaled so splits do not show as jumps.
+    Split,
+    /// Split-adjusted, with dividends reinvested on their ex-dates.
+    TotalReturn,
+}
+
+impl Adjustment {
+    pub const ALL: [Adjustment; 3] = [Adjustment::Raw, Adjustment::Split, Adjustment::TotalReturn];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Adjustment::Raw => "Raw",
+            Adjustment::Split => "Split-adjusted",
+            Adjustment::TotalReturn => "Total return",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Split {
+    pub date: NaiveDate,
+    #[serde(rename = "fromFactor")]
+    pub from_factor: f64,
+    #[serde(rename = "toFactor")]
+    pub to_factor: f64,
+}
+
+impl Split {
+    /// Shares held after the split for each share held before.
+    pub fn ratio(&self) -> f64 {
+        self.to_factor / self.from_factor
+    }
+
+    pub fn label(&self) -> String {
+        format!("{}:{}", self.to_factor, self.from_factor)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Dividend {
+    /// Ex-dividend date.
+    pub date: NaiveDate,
+    /// Cash per share, as paid at the time.
+    pub amount: f64,
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+/// Splits and dividends for one symbol, cached locally per symbol.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct CorporateActions {
+    pub symbol: String,
+    /// Day the provider was last asked; `None` if never fetched.
+    pub fetched: Option<NaiveDate>,
+    pub splits: Vec<Split>,
+    pub dividends: Vec<Dividend>,
+}
+
+impl CorporateActions {
+    fn storage_key(symbol: &str) -> String {
+        format!("finoracle.actions.{symbol}")
+    }
+
+    pub fn cached(symbol: &str) -> Option<Self> {
+        platform::load_cached(&Self::storage_key(symbol))
+    }
+
+    pub fn store(&self) {
+        platform::save_cached(&Self::storage_key(&self.symbol), self);
+    }
+
+    /// Each event's date and a short chart label, oldest first.
+    pub fn markers(&self) -> Vec<(NaiveDate, String)> {
+        let mut markers: Vec<_> = self
+            .splits
+            .iter()
+            .map(|s| (s.date, format!("S {}", s.label())))
+            .chain(
+                self.dividends
+                    .iter()
+                    .map(|d| (d.date, format!("D {:.2}", d.amount))),
+            )
+            .collect();
+        markers.sort();
+        markers
+    }
+
+    /// `bars` adjusted backwards from the latest, so the most recent prices
+    /// stay as traded. Bars must be sorted by start time.
+    pub fn adjust(
+        &self,
+        bars: &[Bar],
+        calendar: &TradingCalendar,
+        adjustment: Adjustment,
+    ) -> Vec<Bar> {
+        if adjustment == Adjustment::Raw {
+            return bars.to_vec();
+        }
+
+        let mut events: Vec<(NaiveDate, Event)> = self
+            .splits
+            .iter()
+            .map(|s| (s.date, Event::Split(s)))
+            .collect();
+        if adjustment == Adjustment::TotalReturn {
+            events.extend(self.dividends.iter().map(|d| (d.date, Event::Dividend(d))));
+        }
+        events.sort_by_key(|(date, _)| *date);
+
+        // Daily candles are stamped at midnight UTC on the session date;
+        // intraday bars belong to the exchange-local date.
+        let session_date = |bar: &Bar| -> NaiveDate {
+            if bar.end - bar.start >= Duration::days(1) {
+                bar.start.date_naive()
+            } else {
+                calendar.local_date(bar.start)
+            }
+        };
+
+        let mut price_factor = 1.0;
+        let mut volume_factor = 1.0;
+        let mut adjusted = bars.to_vec();
+        for bar in adjusted.iter_mut().rev() {
+            let date = session_date(bar);
+            // Every event dated after this bar applies to it.
+            let pending = events.split_off(events.partition_point(|(d, _)| *d <= date));
+            for (event_date, event) in &pending {
+                match event {
+                    Event::Split(split) if split.ratio() > 0.0 => {
+                        price_factor /= split.ratio();
+                        volume_factor *= split.ratio();
+                    }
+                    Event::Dividend(dividend) => {
+                        // Sized against this bar's close, the last before the
+                        // ex-date, counted in shares as of the ex-date.
+                        let close = bar.close / splits_until(&pending, *event_date);
+                        if dividend.amount > 0.0 && dividend.amount < close {
+                            price_factor *= 1.0 - dividend.amount / close;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            bar.open *= price_factor;
+            bar.high *= price_factor;
+            bar.low *= price_factor;
+            bar.close *= price_factor;
+            bar.volume *= volume_factor;
+        }
+        adjusted
+    }
+}
+
+/// A split or dividend, in the order `adjust` applies them.
+enum Event<'a> {
+    Split(&'a Split),
+    Dividend(&'a Dividend),
+}
+
+/// Shares each share became through the splits in `events` dated up to
+/// `date`.
+fn splits_until(events: &[(NaiveDate, Event)], date: NaiveDate) -> f64 {
+    events
+        .iter()
+        .filter_map(|(d, e)| match e {
+            Event::Split(split) if *d <= date && split.ratio() > 0.0 => Some(split.ratio()),
+            _ => None,
+        })
+        .product()
+}
+
+/// Corporate actions for `symbol`, fetched at most once a day and falling
+/// back to the local copy when the provider is unreachable.
+pub async fn load_corporate_actions(symbol: String, api_key: String) -> CorporateActions {
+    let today = Utc::now().date_naive();
+    let cached = CorporateActions::cached(&symbol);
+    if let Some(actions) = cached.as_ref().filter(|a| a.fetched == Some(today)) {
+        return actions.clone();
+    }
+
+    let request = get_corporate_actions(symbol.clone(), api_key);
+    match traced("corporate_actions", &symbol, request).await {
+        Ok(actions) => {
+            actions.store();
+            actions
+        }
+        Err(_) => cached.unwrap_or(CorporateActions {
+            symbol,
+            ..Default::default()
+        }),
+    }
+}
+
+#[server]
+async fn get_corporate_actions(
+    symbol: String,
+    api_key: String,
+) -> Result<CorporateActions, ServerFnError> {
+    let today = Utc::now().date_naive();
+    let range = format!("symbol={symbol}&from=2000-01-01&to={today}&token={api_key}");
+
+    let body =
+        crate::server::provider_get(&format!("https://finnhub.io/api/v1/stock/split?{range}"))
+            .await?;
+    let splits: Vec<Split> = serde_json::from_str(&body)?;
+
+    let body =
+        crate::server::provider_get(&format!("https://finnhub.io/api/v1/stock/dividend?{range}"))
+            .await?;
+    // Dividends need a paid plan; other keys get an error object back, which
+    // leaves total return equal to the split-adjusted series.
+    let dividends: Vec<Dividend> = serde_json::from_str(&body).unwrap_or_default();
+
+    Ok(CorporateActions {
+        symbol,
+        fetched: Some(today),
+        splits,
+        dividends,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Exchange;
+    use chrono::{DateTime, TimeZone};
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    fn bar(start: DateTime<Utc>, length: Duration, close: f64, volume: f64) -> Bar {
+        Bar {
+            start,
+            end: start + length,
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume,
+            ticks: 1,
+        }
+    }
+
+    /// A daily candle, stamped at midnight UTC on the session date.
+    fn daily(day: &str, close: f64) -> Bar {
+        let start = Utc.from_utc_datetime(&date(day).and_hms_opt(0, 0, 0).unwrap());
+        bar(start, Duration::days(1), close, 100.0)
+    }
+
+    fn actions(splits: &[(&str, f64, f64)], dividends: &[(&str, f64)]) -> CorporateActions {
+        CorporateActions {
+            symbol: String::from("TEST"),
+            fetched: None,
+            splits: splits
+                .iter()
+                .map(|(day, from_factor, to_factor)| Split {
+                    date: date(day),
+                    from_factor: *from_factor,
+                    to_factor: *to_factor,
+                })
+                .collect(),
+            dividends: dividends
+                .iter()
+                .map(|(day, amount)| Dividend {
+                    date: date(day),
+                    amount: *amount,
+                    currency: None,
+                })
+                .collect(),
+        }
+    }
+
+    fn adjusted(actions: &CorporateActions, bars: &[Bar], adjustment: Adjustment) -> Vec<Bar> {
+        actions.adjust(bars, Exchange::Us.calendar(), adjustment)
+    }
+
+    fn assert_closes(bars: &[Bar], expected: &[f64]) {
+        let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+        assert_eq!(closes.len(), expected.len(), "{closes:?}");
+        for (close, expected) in closes.iter().zip(expected) {
+            assert!(
+                (close - expected).abs() < 1e-9,
+                "{closes:?} != {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn two_for_one_split_halves_earlier_prices() {
+        let actions = actions(&[("2026-11-04", 1.0, 2.0)], &[]);
+        let bars = [
+            daily("2026-11-02", 100.0),
+            daily("2026-11-03", 102.0),
+            daily("2026-11-04", 51.0),
+            daily("2026-11-05", 52.0),
+        ];
+
+        let split = adjusted(&actions, &bars, Adjustment::Split);
+        assert_closes(&split, &[50.0, 51.0, 51.0, 52.0]);
+        let volumes: Vec<f64> = split.iter().map(|b| b.volume).collect();
+        assert_eq!(volumes, [200.0, 200.0, 100.0, 100.0]);
+        assert_eq!(split, adjusted(&actions, &bars, Adjustment::TotalReturn));
+        assert_eq!(adjusted(&actions, &bars, Adjustment::Raw), bars);
+    }
+
+    #[test]
+    fn dividends_scale_by_the_close_before_the_ex_date() {
+        let actions = actions(&[], &[("2026-11-04", 1.0)]);
+        let bars = [
+            daily("2026-11-02", 100.0),
+            daily("2026-11-03", 100.0),
+            daily("2026-11-04", 99.0),
+        ];
+
+        let total = adjusted(&actions, &bars, Adjustment::TotalReturn);
+        assert_closes(&total, &[99.0, 99.0, 99.0]);
+        assert_eq!(total[0].volume, 100.0);
+        assert_eq!(adjusted(&actions, &bars, Adjustment::Split), bars);
+    }
+
+    #[test]
+    fn splits_and_dividends_compound() {
+        // The dividend is paid per post-split share.
+        let actions = actions(&[("2026-11-04", 1.0, 2.0)], &[("2026-11-06", 0.5)]);
+        let bars = [
+            daily("2026-11-02", 100.0),
+            daily("2026-11-03", 100.0),
+            daily("2026-11-04", 50.0),
+            daily("2026-11-05", 50.0),
+            daily("2026-11-06", 49.5),
+        ];
+
+        let total = adjusted(&actions, &bars, Adjustment::TotalReturn);
+        assert_closes(&total, &[49.5, 49.5, 49.5, 49.5, 49.5]);
+        let split = adjusted(&actions, &bars, Adjustment::Split);
+        assert_closes(&split, &[50.0, 50.0, 50.0, 50.0, 49.5]);
+    }
+
+    #[test]
+    fn a_dividend_on_a_split_date_is_in_post_split_shares() {
+        let actions = actions(&[("2026-11-04", 1.0, 2.0)], &[("2026-11-04", 1.0)]);
+        let bars = [daily("2026-11-03", 100.0), daily("2026-11-04", 49.0)];
+
+        let total = adjusted(&actions, &bars, Adjustment::TotalReturn);
+        assert_closes(&total, &[49.0, 49.0]);
+    }
+
+    #[test]
+    fn dividends_at_or_above_the_close_are_skipped() {
+        let actions = actions(&[], &[("2026-11-04", 2.0), ("2026-11-05", 0.0)]);
+        let bars = [
+            daily("2026-11-03", 2.0),
+            daily("2026-11-04", 1.5),
+            daily("2026-11-05", 1.5),
+        ];
+
+        assert_eq!(adjusted(&actions, &bars, Adjustment::TotalReturn), bars);
+    }
+
+    #[test]
+    fn intraday_bars_belong_to_the_exchange_date() {
+        let actions = actions(&[], &[("2026-11-05", 1.0)]);
+        let bar = |hour, minute, close| {
+            let start = Utc.with_ymd_and_hms(2026, 11, 5, hour, minute, 0).unwrap();
+            bar(start, Duration::minutes(5), close, 100.0)
+        };
+        let bars = [
+            // 19:30 on the 4th in New York, after hours before the ex-date.
+            bar(0, 30, 100.0),
+            // The open on the ex-date.
+            bar(14, 30, 99.0),
+        ];
+
+        let total = adjusted(&actions, &bars, Adjustment::TotalReturn);
+        assert_closes(&total, &[99.0, 99.0]);
+    }
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Currency {
+    Usd,
+    Aud,
+    Gbp,
+    /// Pence sterling, the unit LSE prices are quoted in.
+    Gbx,
+    Jpy,
+}
+
+impl Currency {
+    pub const ALL: [Currency; 5] = [
+        Currency::Usd,
+        Currency::Aud,
+        Currency::Gbp,
+        Currency::Gbx,
+        Currency::Jpy,
+    ];
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            Currency::Usd => "USD",
+            Currency::Aud => "AUD",
+            Currency::Gbp => "GBP",
+            Currency::Gbx => "GBX",
+            Currency::Jpy => "JPY",
+        }
+    }
+
+    /// Parses provider currency codes, which spell pence as `GBp`.
+    pub fn from_code(code: &str) -> Option<Currency> {
+        match code {
+            "GBp" | "GBX" | "GBx" => Some(Currency::Gbx),
+            code => Currency::ALL
+                .into_iter()
+                .find(|c| c.code().eq_ignore_ascii_case(code)),
+        }
+    }
+
+    pub fn decimals(&self) -> usize {
+        match self {
+            Currency::Jpy => 0,
+            _ => 2,
+        }
+    }
+
+    pub fn format(&self, value: f64) -> String {
+        let sign = if value < 0.0 { "-" } else { "" };
+        let amount = group_thousands(&format!("{:.*}", self.decimals(), value.abs()));
+        match self {
+            Currency::Usd => format!("{sign}${amount}"),
+            Currency::Aud => format!("{sign}A${amount}"),
+            Currency::Gbp => format!("{sign}£{amount}"),
+            Currency::Gbx => format!("{sign}{amount}p"),
+            Currency::Jpy => format!("{sign}¥{amount}"),
+        }
+    }
+}
+
+/// Large figures with a magnitude suffix, e.g. `1.23B` or `-45.60M`.
+pub fn compact(value: f64) -> String {
+    let abs = value.abs();
+    let (scaled, suffix) = if abs >= 1e12 {
+        (value / 1e12, "T")
+    } else if abs >= 1e9 {
+        (value / 1e9, "B")
+    } else if abs >= 1e6 {
+        (value / 1e6, "M")
+    } else if abs >= 1e3 {
+        (value / 1e3, "K")
+    } else {
+        (value, "")
+    };
+    format!("{scaled:.2}{suffix}")
+}
+
+fn group_thousands(amount: &str) -> String {
+    let (int, frac) = amount.split_once('.').unwrap_or((amount, ""));
+    let mut grouped = String::new();
+    for (i, c) in int.chars().enumerate() {
+        if i > 0 && (int.len() - i) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(c);
+    }
+    if frac.is_empty() {
+        grouped
+    } else {
+        format!("{grouped}.{frac}")
+    }
+}
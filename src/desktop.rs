@@ -0,0 +1,220 @@
+//! The native window: launch configuration, menu bar and remembered window
+//! geometry.
+
+use crate::diagnostics::DIAGNOSTICS_OPEN;
+use crate::platform;
+use crate::quotes::sleep;
+use crate::store::{update_layout, Layout, Panel};
+use crate::Route;
+use dioxus::desktop::muda::{AboutMetadata, Menu, MenuItem, PredefinedMenuItem, Submenu};
+use dioxus::desktop::tao::event::{Event, WindowEvent};
+use dioxus::desktop::{
+    use_muda_event_handler, use_window, use_wry_event_handler, Config, DesktopContext,
+    LogicalPosition, LogicalSize, WindowBuilder,
+};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Where server functions run when the app is not served from one.
+const SERVER_URL: &str = match option_env!("FINORACLE_SERVER_URL") {
+    Some(url) => url,
+    None => "http://127.0.0.1:8080",
+};
+
+/// How often the window is checked for a settled move or resize.
+const SETTLE_MILLIS: u64 = 500;
+
+/// The window's last normal (unmaximized) size and position in logical
+/// pixels.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct Geometry {
+    x: Option<f64>,
+    y: Option<f64>,
+    width: f64,
+    height: f64,
+    maximized: bool,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Geometry {
+            x: None,
+            y: None,
+            width: 1600.0,
+            height: 1000.0,
+            maximized: false,
+        }
+    }
+}
+
+impl Geometry {
+    fn load() -> Geometry {
+        platform::config_dir()
+            .and_then(|dir| std::fs::read_to_string(dir.join("window.json")).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn store(&self) {
+        if let (Some(dir), Ok(json)) = (platform::config_dir(), serde_json::to_string(self)) {
+            let _ = std::fs::create_dir_all(&dir);
+            let _ = std::fs::write(dir.join("window.json"), json);
+        }
+    }
+
+    /// Updates from the window, keeping the normal size while maximized so
+    /// restoring it later goes back to where it was.
+    fn update(&mut self, window: &DesktopContext) {
+        self.maximized = window.window.is_maximized();
+        if self.maximized {
+            return;
+        }
+        let scale = window.window.scale_factor();
+        let size: LogicalSize<f64> = window.window.inner_size().to_logical(scale);
+        self.width = size.width;
+        self.height = size.height;
+        if let Ok(position) = window.window.outer_position() {
+            let position: LogicalPosition<f64> = position.to_logical(scale);
+            self.x = Some(position.x);
+            self.y = Some(position.y);
+        }
+    }
+}
+
+pub fn launch(app: fn() -> Element) {
+    dioxus::fullstack::prelude::server_fn::client::set_server_url(SERVER_URL);
+
+    let geometry = Geometry::load();
+    let mut window = WindowBuilder::new()
+        .with_title("FinOracle")
+        .with_inner_size(LogicalSize::new(geometry.width, geometry.height))
+        .with_maximized(geometry.maximized);
+    if let (Some(x), Some(y)) = (geometry.x, geometry.y) {
+        window = window.with_position(LogicalPosition::new(x, y));
+    }
+    let mut config = Config::new().with_window(window).with_menu(menu_bar());
+    if let Some(dir) = platform::data_dir() {
+        config = config.with_data_directory(dir.join("webview"));
+    }
+
+    dioxus::LaunchBuilder::desktop()
+        .with_cfg(config)
+        .launch(app);
+}
+
+fn panel_item_id(panel: Panel) -> String {
+    format!("maximize.{}", panel.label())
+}
+
+fn menu_bar() -> Menu {
+    let menu = Menu::new();
+
+    let file = Submenu::new("File", true);
+    let _ = file.append_items(&[
+        &MenuItem::with_id("workspace", "Workspace", true, None),
+        &MenuItem::with_id("screener", "Screener", true, None),
+        &PredefinedMenuItem::separator(),
+        &MenuItem::with_id("clear-cache", "Clear Cached Data", true, None),
+        &PredefinedMenuItem::separator(),
+        &PredefinedMenuItem::quit(None),
+    ]);
+
+    let view = Submenu::new("View", true);
+    let _ = view.append(&MenuItem::with_id(
+        "reset-layout",
+        "Reset Layout",
+        true,
+        None,
+    ));
+    let _ = view.append(&PredefinedMenuItem::separator());
+    for panel in Panel::ALL {
+        let label = format!("Maximize {}", panel.label());
+        let _ = view.append(&MenuItem::with_id(panel_item_id(panel), label, true, None));
+    }
+    let _ = view.append(&PredefinedMenuItem::separator());
+    let _ = view.append(&MenuItem::with_id(
+        "diagnostics",
+        "Toggle Diagnostics",
+        true,
+        None,
+    ));
+    let _ = view.append(&PredefinedMenuItem::fullscreen(None));
+
+    let help = Submenu::new("Help", true);
+    let _ = help.append(&PredefinedMenuItem::about(
+        None,
+        Some(AboutMetadata {
+            name: Some(String::from("FinOracle")),
+            version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            comments: Some(String::from("Market data from Finnhub.")),
+            ..Default::default()
+        }),
+    ));
+
+    let _ = menu.append_items(&[&file, &view, &help]);
+    menu
+}
+
+/// Handles the menu bar's items. Needs the router, for the pages in File.
+pub fn use_menu_events() {
+    let navigator = navigator();
+    use_muda_event_handler(move |event| match event.id().0.as_str() {
+        "workspace" => {
+            navigator.push(Route::Home {});
+        }
+        "screener" => {
+            navigator.push(Route::ScreenerPage {});
+        }
+        "clear-cache" => platform::clear_cache(),
+        "reset-layout" => update_layout(|l| *l = Layout::default()),
+        "diagnostics" => {
+            let open = !DIAGNOSTICS_OPEN();
+            *DIAGNOSTICS_OPEN.write() = open;
+        }
+        id => {
+            if let Some(panel) = Panel::ALL.into_iter().find(|p| panel_item_id(*p) == id) {
+                update_layout(|l| l.toggle_maximized(panel));
+            }
+        }
+    });
+}
+
+/// Follows the window's size and position as it is moved and resized and
+/// saves them for the next launch. File → Quit exits without a close request,
+/// so changes are saved once the window settles rather than only on close.
+pub fn use_window_geometry() {
+    let window = use_window();
+    let mut geometry = use_signal(Geometry::load);
+    let saved = use_signal(|| *geometry.peek());
+    use_wry_event_handler(move |event, _| match event {
+        Event::WindowEvent {
+            event: WindowEvent::Moved(_) | WindowEvent::Resized(_),
+            ..
+        } => geometry.write().update(&window),
+        Event::WindowEvent {
+            event: WindowEvent::CloseRequested,
+            ..
+        } => store_changed(*geometry.peek(), saved),
+        _ => (),
+    });
+    // Moves and resizes arrive continuously while dragging, so a change is
+    // only written once it has stopped changing for a check.
+    use_future(move || async move {
+        let mut last = *geometry.peek();
+        loop {
+            sleep(SETTLE_MILLIS).await;
+            let current = *geometry.peek();
+            if current == last {
+                store_changed(current, saved);
+            }
+            last = current;
+        }
+    });
+}
+
+fn store_changed(geometry: Geometry, mut saved: Signal<Geometry>) {
+    if geometry != *saved.peek() {
+        geometry.store();
+        saved.set(geometry);
+    }
+}
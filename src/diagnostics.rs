@@ -0,0 +1,213 @@
+//! The client's log buffer: recent provider requests, warnings and errors,
+//! and component render timings, kept for the diagnostics panel and also
+//! emitted through `tracing`. The buffer is a plain mutex rather than a
+//! signal because render timings are recorded while rendering; the panel
+//! polls it instead.
+
+use crate::platform;
+use chrono::{DateTime, Duration, Utc};
+use dioxus::logger::tracing::{self, Level};
+use dioxus::prelude::*;
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::Mutex;
+
+/// Entries kept per list; older ones are dropped.
+const CAPACITY: usize = 200;
+
+/// Calls per minute the provider's free tier allows.
+pub const PROVIDER_LIMIT_PER_MINUTE: usize = 60;
+
+/// What a server function fails with when the provider answers
+/// 429 Too Many Requests, or the server's own rate limit is reached.
+pub const RATE_LIMITED: &str = "provider rate limit reached";
+
+pub static DIAGNOSTICS_OPEN: GlobalSignal<bool> = Global::new(|| false);
+
+static BUFFER: Mutex<Buffer> = Mutex::new(Buffer {
+    requests: VecDeque::new(),
+    log: VecDeque::new(),
+    renders: BTreeMap::new(),
+    throttled_at: None,
+});
+
+/// One server-function call to the data provider.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequestRecord {
+    pub call: &'static str,
+    pub subject: String,
+    pub started: DateTime<Utc>,
+    pub millis: f64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogEntry {
+    pub time: DateTime<Utc>,
+    pub level: Level,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RenderStats {
+    pub count: u64,
+    pub last_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+}
+
+impl RenderStats {
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.total_ms / self.count as f64
+        }
+    }
+}
+
+struct Buffer {
+    requests: VecDeque<RequestRecord>,
+    log: VecDeque<LogEntry>,
+    renders: BTreeMap<&'static str, RenderStats>,
+    throttled_at: Option<DateTime<Utc>>,
+}
+
+/// A copy of the buffer for display, newest entries first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Snapshot {
+    pub requests: Vec<RequestRecord>,
+    pub log: Vec<LogEntry>,
+    pub renders: Vec<(&'static str, RenderStats)>,
+    pub calls_last_minute: usize,
+    /// When the provider last refused a call for exceeding its limit.
+    pub throttled_at: Option<DateTime<Utc>>,
+}
+
+fn push<T>(list: &mut VecDeque<T>, item: T) {
+    if list.len() == CAPACITY {
+        list.pop_front();
+    }
+    list.push_back(item);
+}
+
+/// Records a message and emits it through `tracing`.
+pub fn log(level: Level, message: impl Into<String>) {
+    let message = message.into();
+    if level == Level::ERROR {
+        tracing::error!("{message}");
+    } else if level == Level::WARN {
+        tracing::warn!("{message}");
+    } else if level == Level::INFO {
+        tracing::info!("{message}");
+    } else {
+        tracing::debug!("{message}");
+    }
+    if let Ok(mut buffer) = BUFFER.lock() {
+        push(
+            &mut buffer.log,
+            LogEntry {
+                time: Utc::now(),
+                level,
+                message,
+            },
+        );
+    }
+}
+
+/// Awaits a provider call, recording how long it took and whether it failed.
+/// `subject` is what the call was about, usually a symbol.
+pub async fn traced<T, E: Display>(
+    call: &'static str,
+    subject: &str,
+    request: impl Future<Output = Result<T, E>>,
+) -> Result<T, E> {
+    let started = Utc::now();
+    let start = platform::now_ms();
+    let result = request.await;
+    let millis = platform::now_ms() - start;
+    let error = result.as_ref().err().map(|e| e.to_string());
+
+    tracing::debug!(
+        call,
+        subject,
+        millis,
+        ok = error.is_none(),
+        "provider request"
+    );
+    if let Some(e) = &error {
+        log(Level::ERROR, format!("{call}({subject}): {e}"));
+    }
+    if let Ok(mut buffer) = BUFFER.lock() {
+        // Server-function errors wrap the message in their own text.
+        if error.as_ref().is_some_and(|e| e.contains(RATE_LIMITED)) {
+            buffer.throttled_at = Some(started);
+        }
+        push(
+            &mut buffer.requests,
+            RequestRecord {
+                call,
+                subject: subject.to_string(),
+                started,
+                millis,
+                error,
+            },
+        );
+    }
+    result
+}
+
+/// Times a component's render until dropped:
+/// `let _render = diagnostics::render_timer("ChartView");`
+pub fn render_timer(component: &'static str) -> RenderTimer {
+    RenderTimer {
+        component,
+        start: platform::now_ms(),
+    }
+}
+
+pub struct RenderTimer {
+    component: &'static str,
+    start: f64,
+}
+
+impl Drop for RenderTimer {
+    fn drop(&mut self) {
+        let ms = platform::now_ms() - self.start;
+        if let Ok(mut buffer) = BUFFER.lock() {
+            let stats = buffer.renders.entry(self.component).or_default();
+            stats.count += 1;
+            stats.last_ms = ms;
+            stats.max_ms = stats.max_ms.max(ms);
+            stats.total_ms += ms;
+        }
+    }
+}
+
+pub fn snapshot() -> Snapshot {
+    let Ok(buffer) = BUFFER.lock() else {
+        return Snapshot::default();
+    };
+    let minute_ago = Utc::now() - Duration::minutes(1);
+    Snapshot {
+        requests: buffer.requests.iter().rev().cloned().collect(),
+        log: buffer.log.iter().rev().cloned().collect(),
+        renders: buffer.renders.iter().map(|(k, v)| (*k, *v)).collect(),
+        calls_last_minute: buffer
+            .requests
+            .iter()
+            .filter(|r| r.started > minute_ago)
+            .count(),
+        throttled_at: buffer.throttled_at,
+    }
+}
+
+pub fn clear() {
+    if let Ok(mut buffer) = BUFFER.lock() {
+        buffer.requests.clear();
+        buffer.log.clear();
+        buffer.renders.clear();
+        buffer.throttled_at = None;
+    }
+}
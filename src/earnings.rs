@@ -0,0 +1,154 @@
+use crate::calendar::TradingCalendar;
+use crate::diagnostics::traced;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Earnings history and upcoming dates per symbol, oldest first.
+pub static EARNINGS: GlobalSignal<HashMap<String, Vec<EarningsEvent>>> = Global::new(HashMap::new);
+
+/// One earnings release, past or scheduled. Actuals are `None` until
+/// reported.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct EarningsEvent {
+    pub symbol: String,
+    pub date: NaiveDate,
+    /// `bmo` before market open, `amc` after market close, `dmh` during
+    /// market hours; often empty.
+    #[serde(default)]
+    pub hour: String,
+    #[serde(default)]
+    pub quarter: Option<u32>,
+    #[serde(default)]
+    pub year: Option<i32>,
+    #[serde(rename = "epsEstimate", default)]
+    pub eps_estimate: Option<f64>,
+    #[serde(rename = "epsActual", default)]
+    pub eps_actual: Option<f64>,
+    #[serde(rename = "revenueEstimate", default)]
+    pub revenue_estimate: Option<f64>,
+    #[serde(rename = "revenueActual", default)]
+    pub revenue_actual: Option<f64>,
+}
+
+/// Beat (positive) or miss (negative) as a percentage of the estimate.
+pub fn surprise_percent(actual: Option<f64>, estimate: Option<f64>) -> Option<f64> {
+    match (actual, estimate) {
+        (Some(a), Some(e)) if e != 0.0 => Some((a - e) / e.abs() * 100.0),
+        _ => None,
+    }
+}
+
+impl EarningsEvent {
+    pub fn is_reported(&self) -> bool {
+        self.eps_actual.is_some() || self.revenue_actual.is_some()
+    }
+
+    pub fn eps_surprise(&self) -> Option<f64> {
+        surprise_percent(self.eps_actual, self.eps_estimate)
+    }
+
+    pub fn revenue_surprise(&self) -> Option<f64> {
+        surprise_percent(self.revenue_actual, self.revenue_estimate)
+    }
+
+    /// e.g. `Q3 2025`, or the date when the provider gives no fiscal period.
+    pub fn period(&self) -> String {
+        match (self.quarter, self.year) {
+            (Some(q), Some(y)) => format!("Q{q} {y}"),
+            _ => self.date.to_string(),
+        }
+    }
+
+    pub fn timing(&self) -> &'static str {
+        match self.hour.as_str() {
+            "bmo" => "Before open",
+            "amc" => "After close",
+            "dmh" => "During hours",
+            _ => "",
+        }
+    }
+
+    /// When the release lands on the chart: the open for pre-market and
+    /// unknown times, the close for after-hours releases.
+    pub fn time(&self, calendar: &TradingCalendar) -> DateTime<Utc> {
+        match self.hour.as_str() {
+            "amc" => calendar.at(self.date, calendar.close_time(self.date)),
+            _ => calendar.at(self.date, calendar.regular.open),
+        }
+    }
+}
+
+/// Loads two years of releases and the next quarter's schedule for `symbol`
+/// into `EARNINGS`.
+pub async fn load_earnings(symbol: String, api_key: String) {
+    if symbol.is_empty() || EARNINGS().contains_key(&symbol) {
+        return;
+    }
+
+    let today = Utc::now().date_naive();
+    let request = get_earnings_calendar(
+        symbol.clone(),
+        today - Duration::days(2 * 365),
+        today + Duration::days(120),
+        api_key,
+    );
+    if let Ok(mut events) = traced("earnings_calendar", &symbol, request).await {
+        events.sort_by_key(|e| e.date);
+        events.dedup_by(|a, b| a.date == b.date);
+        EARNINGS.write().insert(symbol, events);
+    }
+}
+
+/// Scheduled releases on `[from, to]` for any of `symbols`, soonest first.
+/// Each symbol is asked for separately, since the unfiltered calendar only
+/// lists a sample of companies. Fails only if every request does.
+pub async fn upcoming_earnings(
+    symbols: Vec<String>,
+    from: NaiveDate,
+    to: NaiveDate,
+    api_key: String,
+) -> Result<Vec<EarningsEvent>, ServerFnError> {
+    let mut events = Vec::new();
+    let mut answered = false;
+    let mut error = None;
+    for symbol in &symbols {
+        let request = get_earnings_calendar(symbol.clone(), from, to, api_key.clone());
+        match traced("earnings_calendar", symbol, request).await {
+            Ok(found) => {
+                answered = true;
+                events.extend(found.into_iter().filter(|e| &e.symbol == symbol));
+            }
+            Err(e) => error = Some(e),
+        }
+    }
+    if let (false, Some(e)) = (answered, error) {
+        return Err(e);
+    }
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.symbol.cmp(&b.symbol)));
+    Ok(events)
+}
+
+/// `symbol`'s releases on `[from, to]`.
+#[server]
+async fn get_earnings_calendar(
+    symbol: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    api_key: String,
+) -> Result<Vec<EarningsEvent>, ServerFnError> {
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(rename = "earningsCalendar", default)]
+        earnings_calendar: Vec<EarningsEvent>,
+    }
+
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/calendar/earnings?from={from}&to={to}&symbol={}&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let response: Response = serde_json::from_str(&body)?;
+    Ok(response.earnings_calendar)
+}
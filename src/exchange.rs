@@ -0,0 +1,108 @@
+use crate::calendar::TradingCalendar;
+use crate::currency::Currency;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exchange {
+    Us,
+    Asx,
+    Lse,
+    Tse,
+}
+
+impl Exchange {
+    pub const ALL: [Exchange; 4] = [Exchange::Us, Exchange::Asx, Exchange::Lse, Exchange::Tse];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Exchange::Us => "NYSE / Nasdaq",
+            Exchange::Asx => "ASX",
+            Exchange::Lse => "LSE",
+            Exchange::Tse => "TSE",
+        }
+    }
+
+    /// The country code shown on the exchange selector.
+    pub fn country(&self) -> &'static str {
+        match self {
+            Exchange::Us => "US",
+            Exchange::Asx => "AU",
+            Exchange::Lse => "UK",
+            Exchange::Tse => "JP",
+        }
+    }
+
+    pub fn from_country(country: &str) -> Option<Exchange> {
+        Exchange::ALL.into_iter().find(|e| e.country() == country)
+    }
+
+    /// Finnhub's exchange code, as used by `/stock/symbol?exchange=`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Exchange::Us => "US",
+            Exchange::Asx => "AX",
+            Exchange::Lse => "L",
+            Exchange::Tse => "T",
+        }
+    }
+
+    /// Suffix Finnhub appends to tickers listed on this exchange.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Exchange::Us => "",
+            Exchange::Asx => ".AX",
+            Exchange::Lse => ".L",
+            Exchange::Tse => ".T",
+        }
+    }
+
+    /// The currency prices on this exchange are quoted in.
+    pub fn currency(&self) -> Currency {
+        match self {
+            Exchange::Us => Currency::Usd,
+            Exchange::Asx => Currency::Aud,
+            Exchange::Lse => Currency::Gbx,
+            Exchange::Tse => Currency::Jpy,
+        }
+    }
+
+    /// The exchange a provider symbol is listed on, judging by its suffix.
+    pub fn from_symbol(symbol: &str) -> Exchange {
+        Exchange::ALL
+            .into_iter()
+            .find(|e| !e.suffix().is_empty() && symbol.ends_with(e.suffix()))
+            .unwrap_or(Exchange::Us)
+    }
+
+    /// The bare ticker, e.g. `BHP` for `BHP.AX`.
+    pub fn display_symbol<'a>(&self, symbol: &'a str) -> &'a str {
+        symbol.strip_suffix(self.suffix()).unwrap_or(symbol)
+    }
+
+    /// The provider symbol for a bare ticker, e.g. `7203.T` for `7203`.
+    pub fn provider_symbol(&self, ticker: &str) -> String {
+        if ticker.ends_with(self.suffix()) {
+            ticker.to_string()
+        } else {
+            format!("{ticker}{}", self.suffix())
+        }
+    }
+
+    /// Name of the calendar data file under `assets/calendars`.
+    pub fn calendar_file(&self) -> &'static str {
+        match self {
+            Exchange::Us => "us.json",
+            Exchange::Asx => "asx.json",
+            Exchange::Lse => "lse.json",
+            Exchange::Tse => "tse.json",
+        }
+    }
+
+    pub fn calendar(&self) -> &'static TradingCalendar {
+        static CALENDARS: OnceLock<Vec<TradingCalendar>> = OnceLock::new();
+        let calendars =
+            CALENDARS.get_or_init(|| Exchange::ALL.iter().map(TradingCalendar::load).collect());
+        &calendars[*self as usize]
+    }
+}
@@ -0,0 +1,144 @@
+use crate::currency::Currency;
+use crate::diagnostics::traced;
+use crate::money::{decimal, Money};
+use crate::platform;
+use crate::store::{update, APP_STATE};
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Latest known exchange rates. Starts from the last cached table (or the
+/// bundled snapshot) so conversion works offline.
+pub static FX_RATES: GlobalSignal<FxTable> = Global::new(FxTable::cached);
+/// Currency to display money in; `None` shows each value in its native
+/// currency.
+pub static REPORTING_CURRENCY: GlobalMemo<Option<Currency>> =
+    Memo::global(|| APP_STATE.read().settings.reporting_currency);
+
+const STORAGE_KEY: &str = "finoracle.fx_rates";
+
+/// Units of each currency per one unit of `base`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FxTable {
+    pub base: Currency,
+    pub date: NaiveDate,
+    pub rates: HashMap<Currency, f64>,
+}
+
+impl FxTable {
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("../assets/fx_rates.json"))
+            .expect("bundled FX table is valid")
+    }
+
+    /// The table saved by the last successful fetch, if any.
+    pub fn cached() -> Self {
+        platform::load_cached(STORAGE_KEY).unwrap_or_else(FxTable::bundled)
+    }
+
+    pub fn store(&self) {
+        platform::save_cached(STORAGE_KEY, self);
+    }
+
+    fn per_base(&self, currency: Currency) -> Option<f64> {
+        match currency {
+            c if c == self.base => Some(1.0),
+            Currency::Gbx => self.per_base(Currency::Gbp).map(|r| r * 100.0),
+            c => self.rates.get(&c).copied(),
+        }
+    }
+
+    /// How many `to` one `from` buys.
+    pub fn rate(&self, from: Currency, to: Currency) -> Option<f64> {
+        if from == to {
+            return Some(1.0);
+        }
+        Some(self.per_base(to)? / self.per_base(from)?)
+    }
+
+    pub fn convert(&self, money: Money, to: Currency) -> Option<Money> {
+        self.rate(money.currency, to)
+            .map(|r| Money::new(money.amount, to) * decimal(r))
+    }
+}
+
+/// The currency values natively in `native` are displayed in.
+pub fn display_currency(native: Currency) -> Currency {
+    match REPORTING_CURRENCY() {
+        Some(to) if FX_RATES().rate(native, to).is_some() => to,
+        _ => native,
+    }
+}
+
+/// `money` in the reporting currency, or unchanged when none is selected or
+/// no rate is known.
+pub fn to_display(money: Money) -> Money {
+    FX_RATES()
+        .convert(money, display_currency(money.currency))
+        .unwrap_or(money)
+}
+
+pub fn display_money(money: Money) -> String {
+    to_display(money).format()
+}
+
+/// Fetches fresh rates once, keeping the cached table if that fails.
+pub fn use_fx_rates() {
+    use_future(|| async {
+        if let Ok(table) = traced("fx_rates", "", get_fx_rates()).await {
+            table.store();
+            *FX_RATES.write() = table;
+        }
+    });
+}
+
+#[component]
+pub fn CurrencySelect() -> Element {
+    rsx! {
+        select {class:"bg-[#000000] text-[#ffffff]", title:"Display currency", onchange:move |e| {
+                update(|state| state.settings.reporting_currency = Currency::from_code(&e.value()));
+            },
+            option {value:"", selected: REPORTING_CURRENCY().is_none(), "Native"}
+            for currency in Currency::ALL.into_iter().filter(|c| *c != Currency::Gbx) {
+                option {value:currency.code(), selected: REPORTING_CURRENCY() == Some(currency), {currency.code()}}
+            }
+        }
+    }
+}
+
+/// Rates from the ECB reference feed via frankfurter.app, which needs no API
+/// key.
+#[server]
+async fn get_fx_rates() -> Result<FxTable, ServerFnError> {
+    #[derive(Deserialize)]
+    struct Response {
+        date: NaiveDate,
+        rates: HashMap<String, f64>,
+    }
+
+    let base = Currency::Usd;
+    let quotes = Currency::ALL
+        .into_iter()
+        .filter(|c| *c != base && *c != Currency::Gbx)
+        .map(|c| c.code())
+        .collect::<Vec<_>>()
+        .join(",");
+    let body = crate::server::provider_get(&format!(
+        "https://api.frankfurter.app/latest?from={}&to={quotes}",
+        base.code()
+    ))
+    .await?;
+    let response: Response = serde_json::from_str(&body)?;
+
+    let rates = response
+        .rates
+        .iter()
+        .filter_map(|(code, rate)| Some((Currency::from_code(code)?, *rate)))
+        .collect();
+    Ok(FxTable {
+        base,
+        date: response.date,
+        rates,
+    })
+}
@@ -1,19 +1,108 @@
+mod bars;
+mod calendar;
 mod components;
-use crate::STOCK_INFO;
+mod corporate;
+mod currency;
+#[cfg(feature = "desktop")]
+mod desktop;
+mod diagnostics;
+mod earnings;
+mod exchange;
+mod fx;
+mod metrics;
+mod money;
+mod news;
+mod platform;
+mod quality;
+mod quotes;
+mod screener;
+mod search;
+mod sentiment;
+mod series;
+#[cfg(feature = "server")]
+mod server;
+mod statements;
+mod store;
 use components::*;
+use diagnostics::DIAGNOSTICS_OPEN;
 use dioxus::prelude::*;
+use exchange::Exchange;
+use fx::use_fx_rates;
+use platform::bounds;
+use quotes::use_quote_scheduler;
+use store::{update, use_app_state, Instrument, Panel, Split, APP_STATE};
 
-pub static COUNTRY: GlobalSignal<String> = Global::new(|| String::from("US"));
-pub static LOG: GlobalSignal<String> = Global::new(|| String::from(""));
-
-// use dioxus_desktop::{tao::window::Fullscreen, Config, WindowBuilder};
+/// The exchange whose directory is listed.
+pub static EXCHANGE: GlobalMemo<Exchange> =
+    Memo::global(|| APP_STATE.read().exchange.unwrap_or(Exchange::Us));
 
 #[derive(Debug, Clone, Routable, PartialEq)]
 #[rustfmt::skip]
 enum Route {
-    // #[layout(Navbar)]
-    #[route("/")]
-    Home {},
+    #[layout(Navbar)]
+        #[route("/")]
+        Home {},
+        #[route("/symbol/:exchange/:ticker?:tab&:series&:res&:range&:indicators")]
+        Symbol { exchange: String, ticker: String, tab: String, series: String, res: String, range: String, indicators: String },
+        #[route("/compare?:symbols&:tab&:series&:res&:range&:indicators")]
+        Compare { symbols: String, tab: String, series: String, res: String, range: String, indicators: String },
+        #[route("/screener")]
+        ScreenerPage {},
+        #[route("/backtest/:run_id")]
+        Backtest { run_id: String },
+        #[route("/portfolio")]
+        Portfolio {},
+}
+
+impl Route {
+    /// The page for `symbol`, with `chart` in the query, or for a comparison
+    /// when `comparison` is not empty.
+    fn for_symbol(symbol: &str, comparison: &[String], chart: &ChartQuery) -> Route {
+        if !comparison.is_empty() {
+            return Route::Compare {
+                symbols: std::iter::once(symbol.to_string())
+                    .chain(comparison.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join(","),
+                tab: chart.tab.to_owned(),
+                series: chart.series.to_owned(),
+                res: chart.resolution.to_owned(),
+                range: chart.range.to_owned(),
+                indicators: chart.indicators.join(","),
+            };
+        }
+        let exchange = Exchange::from_symbol(symbol);
+        Route::Symbol {
+            exchange: exchange.country().to_string(),
+            ticker: exchange.display_symbol(symbol).to_string(),
+            tab: chart.tab.to_owned(),
+            series: chart.series.to_owned(),
+            res: chart.resolution.to_owned(),
+            range: chart.range.to_owned(),
+            indicators: chart.indicators.join(","),
+        }
+    }
+}
+
+/// The chart state in a page's query.
+fn chart_query(
+    tab: String,
+    series: String,
+    res: String,
+    range: String,
+    indicators: &str,
+) -> ChartQuery {
+    ChartQuery {
+        tab,
+        series,
+        resolution: res,
+        range,
+        indicators: indicators
+            .split(',')
+            .filter(|i| !i.is_empty())
+            .map(str::to_string)
+            .collect(),
+    }
 }
 
 const FAVICON: Asset = asset!("/assets/favicon.ico");
@@ -21,41 +110,23 @@ const MAIN_CSS: Asset = asset!("/assets/main.css");
 const TAILWIND_CSS: Asset = asset!("/assets/tailwind.css");
 
 fn main() {
-    // #[cfg(feature = "desktop")]
-    // let _ = dioxus::LaunchBuilder::desktop()
-    //     .with_cfg(
-    //         Config::new().with_window(
-    //             WindowBuilder::new()
-    //                 .with_fullscreen(Some(Fullscreen::Borderless(None)))
-    //                 .with_title("FinOracle"),
-    //         ),
-    //     )
-    //     .launch(App);
-    //
-    // let env_var = std::fs::File::open("env.json");
-    // match env_var {
-    //     Ok(mut env_var) => {
-    //         *LOG.write() = String::from("env.json file successfully opened");
-    //         let mut json = String::new();
-    //         let res = env_var.read_to_string(&mut json);
-    //         if let Ok(_) = res {
-    //             if let Ok(res) = serde_json::from_str(&json) {
-    //                 *ENV.write() = res;
-    //             }
-    //         }
-    //     }
-    //     Err(e) => *LOG.write() = e.to_string(),
-    // }
-    // #[cfg(feature = "web")]
+    #[cfg(feature = "server")]
+    server::launch(App);
+    #[cfg(all(feature = "desktop", not(feature = "server")))]
+    desktop::launch(App);
+    #[cfg(not(any(feature = "desktop", feature = "server")))]
     dioxus::launch(App);
 }
 
 #[component]
 fn App() -> Element {
+    #[cfg(feature = "desktop")]
+    desktop::use_window_geometry();
+
     rsx! {
         document::Link { rel: "icon", href: FAVICON }
         document::Link { rel: "stylesheet", href: MAIN_CSS } document::Link { rel: "stylesheet", href: TAILWIND_CSS }
-        div {class:"w-[100%] h-[95vh] flex justify-center items-center p-8 text-[#ffffff] font-[sans-serif] bg-[#000000]",
+        div {class:"w-[100%] h-[95vh] flex flex-col justify-center items-center p-8 text-[#ffffff] font-[sans-serif] bg-[#000000]",
             Router::<Route> {}
         }
     }
@@ -64,29 +135,239 @@ fn App() -> Element {
 /// Home page
 #[component]
 fn Home() -> Element {
-    let mut symbol = use_signal(|| (String::from(""), String::from("")));
-    let size = use_signal(|| (0, 0));
+    rsx! {
+        Workspace {route_symbol: String::new(), comparison: Vec::new(), chart: ChartQuery::default()}
+    }
+}
+
+#[component]
+fn Symbol(
+    exchange: String,
+    ticker: String,
+    tab: String,
+    series: String,
+    res: String,
+    range: String,
+    indicators: String,
+) -> Element {
+    let exchange = Exchange::from_country(&exchange.to_uppercase()).unwrap_or(Exchange::Us);
+    let route_symbol = exchange.provider_symbol(&ticker.to_uppercase());
+    let chart = chart_query(tab, series, res, range, &indicators);
 
     rsx! {
-        main {id:"main", class:"w-[100%] h-[95vh] grid grid-cols-4 gap-x-[2rem]",
-            div { class:"flex flex-col w-[100%] h-[95vh] items-center justify-start relative gap-y-[2rem]",
-                div {height:"50%", class:"h-[50vh] w-[100%] flex flex-col items-start justify-start border-solid border-2 rounded-[0.75rem]",
-                    // button {class:"bg-[#000000] text-[#ffffff] hover:text-[#eeeeee] hover:translate-x-[-0.2rem] duration-[0.25s] border-none text-[2rem] m-[0px]" ,"⬅︎"}
-                    StockList{symbol}
-                }
-                div {height:"50vh", class:"border-[#ffffff] w-[100%] h-[50vh] flex flex-col justify-start border-solid border-2 rounded-[0.75rem]  overflow-x-hidden",
-                    StockView{symbol}
-                }
+        Workspace {route_symbol, comparison: Vec::new(), chart}
+    }
+}
+
+#[component]
+fn Compare(
+    symbols: String,
+    tab: String,
+    series: String,
+    res: String,
+    range: String,
+    indicators: String,
+) -> Element {
+    let mut symbols: Vec<String> = symbols
+        .split(',')
+        .map(|s| s.trim().to_uppercase())
+        .filter(|s| !s.is_empty())
+        .collect();
+    let route_symbol = if symbols.is_empty() {
+        String::new()
+    } else {
+        symbols.remove(0)
+    };
+    // Comparisons are drawn over prices, so a bare link opens the closes.
+    let tab = if tab.is_empty() {
+        String::from("price")
+    } else {
+        tab
+    };
+    let series = if series.is_empty() {
+        String::from("close")
+    } else {
+        series
+    };
+    let chart = chart_query(tab, series, res, range, &indicators);
+
+    rsx! {
+        Workspace {route_symbol, comparison: symbols, chart}
+    }
+}
+
+/// The main layout. Route parameters are applied when they change, and the
+/// selected symbol, comparison and chart state are written back to the URL.
+#[component]
+fn Workspace(route_symbol: String, comparison: Vec<String>, chart: ChartQuery) -> Element {
+    let _render = diagnostics::render_timer("Workspace");
+    let mut symbol = use_app_state(&route_symbol);
+
+    use_quote_scheduler(symbol);
+    use_fx_rates();
+
+    // The home page has no state of its own and keeps the saved session.
+    use_effect(use_reactive!(|(route_symbol, comparison, chart)| {
+        if route_symbol.is_empty() {
+            return;
+        }
+        if route_symbol != symbol.peek().symbol {
+            let instrument = Instrument::new(&route_symbol, "");
+            update(|state| state.exchange = Some(instrument.exchange));
+            // The directory fills in the description once loaded.
+            symbol.set(instrument);
+        }
+        if *COMPARISON.peek() != comparison {
+            update(|state| state.comparison = comparison);
+        }
+        if *CHART_STATE.peek() != chart {
+            *CHART_SELECTION.write() = Some(chart);
+        }
+    }));
+
+    use_effect(move || {
+        let sym = symbol().symbol;
+        let target = Route::for_symbol(&sym, &COMPARISON(), &CHART_STATE());
+        // Wait for a requested chart state to be shown before writing it back.
+        if sym.is_empty() || CHART_SELECTION.peek().is_some() {
+            return;
+        }
+        if router().current::<Route>() != target {
+            navigator().replace(target);
+        }
+    });
+
+    let layout = APP_STATE.read().layout.clone();
+    let dragging: Signal<Option<Panel>> = use_signal(|| None);
+    let mut resizing: Signal<Option<Split>> = use_signal(|| None);
+
+    let view = move |panel: Panel| match panel {
+        Panel::List => rsx! { StockList {symbol} },
+        Panel::Detail => rsx! { StockView {symbol} },
+        Panel::Chart => rsx! { ChartView {symbol} },
+        Panel::Watchlist => rsx! { Watchlist {symbol} },
+        Panel::News => rsx! { NewsPanel {symbol} },
+        Panel::Earnings => rsx! { EarningsPanel {symbol} },
+        Panel::Screener => rsx! { Screener {symbol} },
+    };
+
+    // The first pane of a column takes its saved share unless a pane in the
+    // column is minimized, in which case the open ones share the space.
+    let left_sized = !layout.left.iter().any(|p| layout.is_minimized(*p));
+    let bottom_open = layout.bottom.iter().any(|p| !layout.is_minimized(*p));
+    let main_sized = !layout.is_minimized(layout.main) && bottom_open;
+    let flex = |panel: Panel, share: Option<f64>| match (layout.is_minimized(panel), share) {
+        (true, _) => String::from("0 0 auto"),
+        (false, Some(share)) => format!("0 0 {share}%"),
+        (false, None) => String::from("1 1 0"),
+    };
+
+    let onmousemove = move |e: Event<MouseData>| async move {
+        let Some(split) = resizing() else {
+            return;
+        };
+        let point = e.client_coordinates();
+        let id = match split {
+            Split::LeftWidth => "workspace",
+            Split::LeftHeight => "workspace-left",
+            Split::MainHeight => "workspace-right",
+        };
+        if let Some(rect) = bounds(id).await {
+            let percent = match split {
+                Split::LeftWidth => (point.x - rect.left) / rect.width * 100.0,
+                _ => (point.y - rect.top) / rect.height * 100.0,
+            };
+            APP_STATE.write().layout.resize(split, percent);
+        }
+    };
+    let mut end_resize = move || {
+        if resizing.peek().is_some() {
+            resizing.set(None);
+            APP_STATE.peek().store();
+        }
+    };
+    let mut end_resize_on_leave = end_resize;
+
+    if let Some(panel) = layout.maximized {
+        return rsx! {
+            main {id:"workspace", class:"w-[100%] h-[95vh] flex flex-col",
+                PanelFrame {panel, flex:"1 1 0", dragging, {view(panel)}}
             }
-            div { class:"w-[100%] h-[95vh] col-span-3  gap-y-[2rem] flex flex-col justify-center items-center",
-                div {class:"px-[1rem] gap-y-[2rem] flex flex-col justify-start items-start h-[95vh] w-[100%]",
-                    div {class:"w-[100%] h-[50vh]　m-[0px] flex flex-col border-[#ffffff] border-solid border-2 rounded-[0.75rem] overflow-y-hidden",
-                        ChartView {symbol}
-                    }
-                    div {class:"border-[#ffffff] w-[100%] h-[50vh] flex flex-col border-solid border-2 rounded-[0.75rem] overflow-y-hidden",
+        };
+    }
+
+    rsx! {
+        main {id:"workspace", class:"w-[100%] h-[95vh] flex flex-row", user_select: if resizing().is_some() {"none"} else {"auto"},
+            onmousemove,
+            onmouseup:move |_| end_resize(),
+            onmouseleave:move |_| end_resize_on_leave(),
+            div {id:"workspace-left", class:"flex flex-col h-[100%] flex-none", width:"{layout.left_width}%",
+                for (i, panel) in layout.left.iter().copied().enumerate() {
+                    if i > 0 {
+                        if left_sized {
+                            Splitter {split: Split::LeftHeight, resizing}
+                        } else {
+                            div {class:"h-[0.75rem] flex-none"}
+                        }
                     }
+                    PanelFrame {panel, flex: flex(panel, (i == 0 && left_sized).then_some(layout.left_height)), dragging, {view(panel)}}
                 }
+            }
+            Splitter {split: Split::LeftWidth, resizing}
+            div {id:"workspace-right", class:"flex flex-col h-[100%] flex-1 min-w-[0px]",
+                PanelFrame {panel: layout.main, flex: flex(layout.main, main_sized.then_some(layout.main_height)), dragging, {view(layout.main)}}
+                if main_sized {
+                    Splitter {split: Split::MainHeight, resizing}
+                } else {
+                    div {class:"h-[0.75rem] flex-none"}
                 }
+                div {class:"flex flex-row min-h-[0px] gap-x-[1rem]", flex: if bottom_open {"1 1 0"} else {"0 0 auto"},
+                    for panel in layout.bottom.iter().copied() {
+                        PanelFrame {panel, flex: flex(panel, None), dragging, {view(panel)}}
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The screener on its own; choosing a result opens its symbol page.
+#[component]
+fn ScreenerPage() -> Element {
+    let symbol = use_signal(Instrument::default);
+
+    use_effect(move || {
+        let sym = symbol().symbol;
+        if !sym.is_empty() {
+            navigator().push(Route::for_symbol(&sym, &[], &ChartQuery::default()));
+        }
+    });
+
+    rsx! {
+        div {class:"w-[100%] h-[90vh] flex flex-col border-[#ffffff] border-solid border-2 rounded-[0.75rem] overflow-y-hidden",
+            Screener {symbol}
+        }
+    }
+}
+
+/// Backtest runs are not recorded yet, so a run link has nothing to show.
+#[component]
+fn Backtest(run_id: String) -> Element {
+    rsx! {
+        div {class:"w-[100%] h-[90vh] flex flex-col justify-center items-center",
+            h2 {class:"text-[1.5rem] font-bold", "Backtest {run_id}"}
+            p {class:"text-[#888888]", "No backtest run with this id is stored."}
+        }
+    }
+}
+
+/// Placeholder until positions can be recorded.
+#[component]
+fn Portfolio() -> Element {
+    rsx! {
+        div {class:"w-[100%] h-[90vh] flex flex-col justify-center items-center",
+            h2 {class:"text-[1.5rem] font-bold", "Portfolio"}
+            p {class:"text-[#888888]", "No positions recorded yet."}
         }
     }
 }
@@ -94,16 +375,37 @@ fn Home() -> Element {
 /// Shared navbar component.
 #[component]
 fn Navbar() -> Element {
+    #[cfg(feature = "desktop")]
+    desktop::use_menu_events();
+
+    let chart = CHART_STATE();
+    let instrument = APP_STATE.read().instrument.clone();
+    let current = (!instrument.is_empty())
+        .then(|| Route::for_symbol(&instrument.symbol, &COMPARISON(), &chart));
+
     rsx! {
-        div {
-            class: "w-full h-auto",
+        nav {
+            class: "w-[100%] h-[2rem] flex flex-row items-center gap-x-[1.5rem] px-[1rem]",
             id: "navbar",
-            Link {
-                to: Route::Home {},
-                "Home"
+            Link {class:"text-[#ffffff] no-underline", active_class:"font-bold underline", to: Route::Home {}, "Workspace"}
+            if let Some(route) = current {
+                Link {class:"text-[#ffffff] no-underline", active_class:"font-bold underline", to: route, "{instrument.symbol}"}
+            }
+            Link {class:"text-[#ffffff] no-underline", active_class:"font-bold underline", to: Route::ScreenerPage {}, "Screener"}
+            Link {class:"text-[#ffffff] no-underline", active_class:"font-bold underline", to: Route::Portfolio {}, "Portfolio"}
+            WorkspaceMenu {}
+            button {class:"bg-[#000000] text-[#ffffff] border-none cursor-pointer", title:"Requests, errors and render timings",
+                font_weight: if DIAGNOSTICS_OPEN() {"bold"} else {"normal"},
+                onclick:move |_| {
+                    let open = !DIAGNOSTICS_OPEN();
+                    *DIAGNOSTICS_OPEN.write() = open;
+                },
+                "Diagnostics"
             }
-
         }
         Outlet::<Route> {}
+        if DIAGNOSTICS_OPEN() {
+            DiagnosticsPanel {}
+        }
     }
 }
@@ -0,0 +1,370 @@
+use crate::currency::{compact, Currency};
+use crate::fx::{display_money, to_display};
+use crate::money::{Money, Quantity, MILLION};
+use serde_json::Value;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    Valuation,
+    Profitability,
+    Growth,
+    Leverage,
+    Dividends,
+    Price,
+    Other,
+}
+
+impl Category {
+    pub const ALL: [Category; 7] = [
+        Category::Valuation,
+        Category::Profitability,
+        Category::Growth,
+        Category::Leverage,
+        Category::Dividends,
+        Category::Price,
+        Category::Other,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Category::Valuation => "Valuation",
+            Category::Profitability => "Profitability",
+            Category::Growth => "Growth",
+            Category::Leverage => "Leverage & Liquidity",
+            Category::Dividends => "Dividends",
+            Category::Price => "Price & Volume",
+            Category::Other => "Other",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// Already scaled to percent by the provider, e.g. `12.5` for 12.5%.
+    Percent,
+    Ratio,
+    /// Price multiples such as P/E, shown with an `x`.
+    Multiple,
+    /// Per-share or absolute amount in the listing currency.
+    Amount,
+    /// Amount in millions of the listing currency.
+    AmountMillions,
+    /// Share counts or volumes in millions.
+    Millions,
+    Date,
+    Number,
+}
+
+impl Unit {
+    pub fn format(&self, value: &Value, currency: Currency) -> String {
+        let Some(v) = value.as_f64() else {
+            return match value {
+                Value::String(s) => s.to_owned(),
+                Value::Null => String::from("–"),
+                other => other.to_string(),
+            };
+        };
+        match self {
+            Unit::Percent => format!("{v:.2}%"),
+            Unit::Ratio => format!("{v:.2}"),
+            Unit::Multiple => format!("{v:.2}x"),
+            Unit::Amount => display_money(Money::from_f64(v, currency)),
+            Unit::AmountMillions => {
+                let money = to_display(Money::from_f64(v, currency) * MILLION);
+                format!("{} {}", money.currency.code(), compact(money.to_f64()))
+            }
+            Unit::Millions => compact((Quantity::from_f64(v) * MILLION).to_f64()),
+            Unit::Date => v.to_string(),
+            Unit::Number => format!("{v:.2}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricInfo {
+    pub key: String,
+    pub label: String,
+    pub category: Category,
+    pub unit: Unit,
+    pub description: String,
+}
+
+use Category::*;
+use Unit::*;
+
+/// `(key, label, category, unit, description)` for the `/stock/metric` keys.
+#[rustfmt::skip]
+const CATALOG: &[(&str, &str, Category, Unit, &str)] = &[
+    ("marketCapitalization", "Market Cap", Valuation, AmountMillions, "Share price times shares outstanding."),
+    ("enterpriseValue", "Enterprise Value", Valuation, AmountMillions, "Market cap plus net debt: the price of the whole business."),
+    ("peTTM", "P/E (TTM)", Valuation, Multiple, "Price over earnings per share for the trailing twelve months."),
+    ("peAnnual", "P/E (Annual)", Valuation, Multiple, "Price over earnings per share for the last fiscal year."),
+    ("peExclExtraTTM", "P/E excl. Extraordinary (TTM)", Valuation, Multiple, "P/E using earnings before extraordinary items."),
+    ("forwardPE", "Forward P/E", Valuation, Multiple, "Price over consensus earnings for the next twelve months."),
+    ("pbAnnual", "P/B (Annual)", Valuation, Multiple, "Price over book value per share."),
+    ("pbQuarterly", "P/B (Quarterly)", Valuation, Multiple, "Price over book value per share from the latest quarter."),
+    ("psTTM", "P/S (TTM)", Valuation, Multiple, "Price over revenue per share for the trailing twelve months."),
+    ("psAnnual", "P/S (Annual)", Valuation, Multiple, "Price over revenue per share for the last fiscal year."),
+    ("ptbvAnnual", "P/Tangible Book", Valuation, Multiple, "Price over tangible book value per share."),
+    ("pcfShareTTM", "P/Cash Flow (TTM)", Valuation, Multiple, "Price over operating cash flow per share."),
+    ("pfcfShareTTM", "P/Free Cash Flow (TTM)", Valuation, Multiple, "Price over free cash flow per share."),
+    ("evEbitdaTTM", "EV/EBITDA (TTM)", Valuation, Multiple, "Enterprise value over earnings before interest, tax, depreciation and amortisation."),
+    ("evRevenueTTM", "EV/Revenue (TTM)", Valuation, Multiple, "Enterprise value over trailing revenue."),
+    ("epsTTM", "EPS (TTM)", Valuation, Amount, "Earnings per share over the trailing twelve months."),
+    ("epsAnnual", "EPS (Annual)", Valuation, Amount, "Earnings per share for the last fiscal year."),
+    ("bookValuePerShareAnnual", "Book Value/Share", Valuation, Amount, "Shareholders' equity per share."),
+    ("cashPerSharePerShareAnnual", "Cash/Share", Valuation, Amount, "Cash and equivalents per share."),
+    ("revenuePerShareTTM", "Revenue/Share (TTM)", Valuation, Amount, "Trailing revenue per share."),
+    ("grossMarginTTM", "Gross Margin (TTM)", Profitability, Percent, "Revenue left after cost of goods sold."),
+    ("grossMarginAnnual", "Gross Margin (Annual)", Profitability, Percent, "Revenue left after cost of goods sold, last fiscal year."),
+    ("operatingMarginTTM", "Operating Margin (TTM)", Profitability, Percent, "Operating income as a share of revenue."),
+    ("operatingMarginAnnual", "Operating Margin (Annual)", Profitability, Percent, "Operating income as a share of revenue, last fiscal year."),
+    ("netProfitMarginTTM", "Net Margin (TTM)", Profitability, Percent, "Net income as a share of revenue."),
+    ("netProfitMarginAnnual", "Net Margin (Annual)", Profitability, Percent, "Net income as a share of revenue, last fiscal year."),
+    ("pretaxMarginTTM", "Pre-tax Margin (TTM)", Profitability, Percent, "Income before tax as a share of revenue."),
+    ("roeTTM", "Return on Equity (TTM)", Profitability, Percent, "Net income over shareholders' equity."),
+    ("roeRfy", "Return on Equity (FY)", Profitability, Percent, "Net income over shareholders' equity, last fiscal year."),
+    ("roaTTM", "Return on Assets (TTM)", Profitability, Percent, "Net income over total assets."),
+    ("roaRfy", "Return on Assets (FY)", Profitability, Percent, "Net income over total assets, last fiscal year."),
+    ("roiTTM", "Return on Investment (TTM)", Profitability, Percent, "Net income over invested capital."),
+    ("roiAnnual", "Return on Investment (Annual)", Profitability, Percent, "Net income over invested capital, last fiscal year."),
+    ("assetTurnoverTTM", "Asset Turnover (TTM)", Profitability, Ratio, "Revenue per unit of assets."),
+    ("inventoryTurnoverTTM", "Inventory Turnover (TTM)", Profitability, Ratio, "Cost of goods sold over average inventory."),
+    ("receivablesTurnoverTTM", "Receivables Turnover (TTM)", Profitability, Ratio, "Revenue over average receivables."),
+    ("netIncomeEmployeeTTM", "Net Income/Employee", Profitability, Amount, "Trailing net income per employee."),
+    ("revenueEmployeeTTM", "Revenue/Employee", Profitability, Amount, "Trailing revenue per employee."),
+    ("revenueGrowthTTMYoy", "Revenue Growth (TTM YoY)", Growth, Percent, "Trailing revenue against the same period a year earlier."),
+    ("revenueGrowthQuarterlyYoy", "Revenue Growth (Quarter YoY)", Growth, Percent, "Latest quarter's revenue against the same quarter a year earlier."),
+    ("revenueGrowth3Y", "Revenue Growth (3Y)", Growth, Percent, "Compound annual revenue growth over three years."),
+    ("revenueGrowth5Y", "Revenue Growth (5Y)", Growth, Percent, "Compound annual revenue growth over five years."),
+    ("epsGrowthTTMYoy", "EPS Growth (TTM YoY)", Growth, Percent, "Trailing EPS against the same period a year earlier."),
+    ("epsGrowthQuarterlyYoy", "EPS Growth (Quarter YoY)", Growth, Percent, "Latest quarter's EPS against the same quarter a year earlier."),
+    ("epsGrowth3Y", "EPS Growth (3Y)", Growth, Percent, "Compound annual EPS growth over three years."),
+    ("epsGrowth5Y", "EPS Growth (5Y)", Growth, Percent, "Compound annual EPS growth over five years."),
+    ("bookValueShareGrowth5Y", "Book Value Growth (5Y)", Growth, Percent, "Compound annual growth in book value per share."),
+    ("ebitdaCagr5Y", "EBITDA Growth (5Y)", Growth, Percent, "Compound annual EBITDA growth over five years."),
+    ("focfCagr5Y", "Free Cash Flow Growth (5Y)", Growth, Percent, "Compound annual free operating cash flow growth."),
+    ("capexCagr5Y", "Capex Growth (5Y)", Growth, Percent, "Compound annual growth in capital expenditure."),
+    ("netMarginGrowth5Y", "Net Margin Growth (5Y)", Growth, Percent, "Annual change in net margin over five years."),
+    ("tbvCagr5Y", "Tangible Book Growth (5Y)", Growth, Percent, "Compound annual growth in tangible book value."),
+    ("currentRatioQuarterly", "Current Ratio (Quarterly)", Leverage, Ratio, "Current assets over current liabilities."),
+    ("currentRatioAnnual", "Current Ratio (Annual)", Leverage, Ratio, "Current assets over current liabilities, last fiscal year."),
+    ("quickRatioQuarterly", "Quick Ratio (Quarterly)", Leverage, Ratio, "Liquid current assets over current liabilities."),
+    ("quickRatioAnnual", "Quick Ratio (Annual)", Leverage, Ratio, "Liquid current assets over current liabilities, last fiscal year."),
+    ("totalDebt/totalEquityQuarterly", "Debt/Equity (Quarterly)", Leverage, Ratio, "Total debt over shareholders' equity."),
+    ("totalDebt/totalEquityAnnual", "Debt/Equity (Annual)", Leverage, Ratio, "Total debt over shareholders' equity, last fiscal year."),
+    ("longTermDebt/equityQuarterly", "LT Debt/Equity (Quarterly)", Leverage, Ratio, "Long-term debt over shareholders' equity."),
+    ("longTermDebt/equityAnnual", "LT Debt/Equity (Annual)", Leverage, Ratio, "Long-term debt over shareholders' equity, last fiscal year."),
+    ("netInterestCoverageTTM", "Interest Coverage (TTM)", Leverage, Multiple, "Operating income over net interest expense."),
+    ("netInterestCoverageAnnual", "Interest Coverage (Annual)", Leverage, Multiple, "Operating income over net interest expense, last fiscal year."),
+    ("dividendYieldIndicatedAnnual", "Dividend Yield (Indicated)", Dividends, Percent, "Annualised current dividend over price."),
+    ("currentDividendYieldTTM", "Dividend Yield (TTM)", Dividends, Percent, "Dividends paid over the trailing year, over price."),
+    ("dividendPerShareAnnual", "Dividend/Share (Annual)", Dividends, Amount, "Dividends per share for the last fiscal year."),
+    ("dividendPerShareTTM", "Dividend/Share (TTM)", Dividends, Amount, "Dividends per share over the trailing year."),
+    ("dividendIndicatedAnnual", "Indicated Annual Dividend", Dividends, Amount, "Latest dividend annualised."),
+    ("dividendGrowthRate5Y", "Dividend Growth (5Y)", Dividends, Percent, "Compound annual growth in dividend per share."),
+    ("payoutRatioTTM", "Payout Ratio (TTM)", Dividends, Percent, "Share of trailing earnings paid as dividends."),
+    ("payoutRatioAnnual", "Payout Ratio (Annual)", Dividends, Percent, "Share of last fiscal year's earnings paid as dividends."),
+    ("52WeekHigh", "52-Week High", Price, Amount, "Highest price over the last year."),
+    ("52WeekHighDate", "52-Week High Date", Price, Date, "Day the 52-week high was set."),
+    ("52WeekLow", "52-Week Low", Price, Amount, "Lowest price over the last year."),
+    ("52WeekLowDate", "52-Week Low Date", Price, Date, "Day the 52-week low was set."),
+    ("52WeekPriceReturnDaily", "52-Week Return", Price, Percent, "Price change over the last year."),
+    ("26WeekPriceReturnDaily", "26-Week Return", Price, Percent, "Price change over the last six months."),
+    ("13WeekPriceReturnDaily", "13-Week Return", Price, Percent, "Price change over the last three months."),
+    ("5DayPriceReturnDaily", "5-Day Return", Price, Percent, "Price change over the last five sessions."),
+    ("monthToDatePriceReturnDaily", "Month-to-Date Return", Price, Percent, "Price change since the start of the month."),
+    ("yearToDatePriceReturnDaily", "Year-to-Date Return", Price, Percent, "Price change since the start of the year."),
+    ("priceRelativeToS&P50052Week", "Relative to S&P 500 (52W)", Price, Percent, "52-week return minus the S&P 500's."),
+    ("beta", "Beta", Price, Ratio, "Sensitivity of returns to the market; 1 moves with it."),
+    ("3MonthADReturnStd", "Volatility (3M)", Price, Percent, "Annualised standard deviation of daily returns over three months."),
+    ("10DayAverageTradingVolume", "Avg Volume (10D)", Price, Millions, "Average shares traded per day over ten sessions."),
+    ("3MonthAverageTradingVolume", "Avg Volume (3M)", Price, Millions, "Average shares traded per day over three months."),
+];
+
+/// Splits a camelCase key into words, e.g. `ebitdPerShareTTM` becomes
+/// `Ebitd Per Share TTM`.
+pub fn humanize(key: &str) -> String {
+    let mut label = String::new();
+    let mut prev: Option<char> = None;
+    for c in key.chars() {
+        if let Some(p) = prev {
+            let boundary = (p.is_lowercase() && (c.is_uppercase() || c.is_ascii_digit()))
+                || (p.is_ascii_digit() && c.is_alphabetic());
+            if boundary {
+                label.push(' ');
+            }
+            label.push(c);
+        } else {
+            label.extend(c.to_uppercase());
+        }
+        prev = Some(c);
+    }
+    label
+}
+
+/// Where `key` sits in the catalog; unknown keys sort last.
+pub fn catalog_position(key: &str) -> usize {
+    CATALOG
+        .iter()
+        .position(|(k, ..)| *k == key)
+        .unwrap_or(CATALOG.len())
+}
+
+/// Catalog entry for `key`, or one inferred from its name so new provider
+/// keys still show up readably.
+pub fn metric_info(key: &str) -> MetricInfo {
+    if let Some((key, label, category, unit, description)) =
+        CATALOG.iter().find(|(k, ..)| *k == key)
+    {
+        return MetricInfo {
+            key: key.to_string(),
+            label: label.to_string(),
+            category: *category,
+            unit: *unit,
+            description: description.to_string(),
+        };
+    }
+
+    let lower = key.to_lowercase();
+    let unit = if lower.ends_with("date") {
+        Date
+    } else if [
+        "margin", "growth", "cagr", "return", "yield", "roe", "roa", "roi",
+    ]
+    .iter()
+    .any(|w| lower.contains(w))
+    {
+        Percent
+    } else if lower.contains("ratio") || lower.contains('/') {
+        Ratio
+    } else {
+        Number
+    };
+    MetricInfo {
+        key: key.to_string(),
+        label: humanize(key),
+        category: Other,
+        unit,
+        description: format!("Provider metric `{key}`."),
+    }
+}
+
+/// Snapshot keys whose history is published under a different name in the
+/// `series` payload.
+#[rustfmt::skip]
+const SERIES_ALIASES: &[(&str, &str)] = &[
+    ("netProfitMarginTTM", "netMargin"),
+    ("netProfitMarginAnnual", "netMargin"),
+    ("grossMarginTTM", "grossMargin"),
+    ("grossMarginAnnual", "grossMargin"),
+    ("operatingMarginTTM", "operatingMargin"),
+    ("operatingMarginAnnual", "operatingMargin"),
+    ("pretaxMarginTTM", "pretaxMargin"),
+    ("pretaxMarginAnnual", "pretaxMargin"),
+    ("totalDebt/totalEquityQuarterly", "totalDebtToEquity"),
+    ("totalDebt/totalEquityAnnual", "totalDebtToEquity"),
+    ("longTermDebt/equityQuarterly", "longtermDebtTotalEquity"),
+    ("longTermDebt/equityAnnual", "longtermDebtTotalEquity"),
+    ("epsTTM", "eps"),
+    ("epsAnnual", "eps"),
+    ("bookValuePerShareQuarterly", "bookValue"),
+    ("bookValuePerShareAnnual", "bookValue"),
+    ("roeTTM", "roe"),
+    ("roaTTM", "roa"),
+    ("roiTTM", "roic"),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+    Flat,
+}
+
+impl Trend {
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Trend::Up => "▲",
+            Trend::Down => "▼",
+            Trend::Flat => "▶",
+        }
+    }
+
+    pub fn color(&self) -> &'static str {
+        match self {
+            Trend::Up => "#00cc44",
+            Trend::Down => "#ee0000",
+            Trend::Flat => "#888888",
+        }
+    }
+}
+
+/// A metric's past values as found in the `series` payload.
+#[derive(Debug, Clone, PartialEq)]
+pub struct History {
+    /// `annual` or `quarterly`, the `ChartView` tab holding the series.
+    pub tab: &'static str,
+    pub series: String,
+    /// Oldest first.
+    pub values: Vec<f64>,
+}
+
+impl History {
+    /// Direction of the latest value against the one before it; moves under
+    /// half a percent count as flat.
+    pub fn trend(&self) -> Trend {
+        match self.values.as_slice() {
+            [.., prev, last] => {
+                let change = (last - prev) / prev.abs().max(f64::EPSILON);
+                if change > 0.005 {
+                    Trend::Up
+                } else if change < -0.005 {
+                    Trend::Down
+                } else {
+                    Trend::Flat
+                }
+            }
+            _ => Trend::Flat,
+        }
+    }
+}
+
+/// Finds the history behind snapshot metric `key` in `series`, which maps
+/// `annual` and `quarterly` to `{name: [{period, v}]}`. Quarterly and TTM
+/// keys prefer the quarterly series.
+pub fn history(key: &str, series: &Value) -> Option<History> {
+    let (base, tabs) = if let Some(base) = key.strip_suffix("Quarterly") {
+        (base, ["quarterly", "annual"])
+    } else if let Some(base) = key.strip_suffix("TTM") {
+        (base, ["quarterly", "annual"])
+    } else if let Some(base) = key.strip_suffix("Annual") {
+        (base, ["annual", "quarterly"])
+    } else {
+        (key, ["annual", "quarterly"])
+    };
+    let alias = SERIES_ALIASES
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, name)| *name);
+    let names: Vec<&str> = [Some(key), alias, Some(base)]
+        .into_iter()
+        .flatten()
+        .collect();
+
+    tabs.into_iter().find_map(|tab| {
+        let (name, points) = names
+            .iter()
+            .find_map(|name| Some((*name, series.get(tab)?.get(*name)?.as_array()?)))?;
+        let mut points: Vec<(&str, f64)> = points
+            .iter()
+            .filter_map(|p| Some((p.get("period")?.as_str()?, p.get("v")?.as_f64()?)))
+            .collect();
+        points.sort_by(|a, b| a.0.cmp(b.0));
+        (points.len() > 1).then(|| History {
+            tab,
+            series: name.to_string(),
+            values: points.into_iter().map(|(_, v)| v).collect(),
+        })
+    })
+}
@@ -0,0 +1,320 @@
+use crate::currency::Currency;
+use crate::exchange::Exchange;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::ops::{Mul, Neg};
+use std::str::FromStr;
+
+/// Providers report large counts and amounts in millions.
+pub const MILLION: Decimal = Decimal::from_parts(1_000_000, 0, 0, false, 0);
+
+/// Converts a provider or chart `f64` into a decimal, keeping the shortest
+/// representation that round-trips rather than the binary expansion.
+pub fn decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/// A per-unit price. Kept decimal so it sits exactly on the exchange's tick
+/// grid; provider `f64`s are snapped onto it with `TickSize::round`.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+pub struct Price(pub Decimal);
+
+/// A number of shares or contracts. Fractional quantities are allowed.
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default,
+)]
+pub struct Quantity(pub Decimal);
+
+/// An amount in a specific currency.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Money {
+    pub amount: Decimal,
+    pub currency: Currency,
+}
+
+impl Price {
+    pub fn from_f64(value: f64) -> Self {
+        Price(decimal(value))
+    }
+}
+
+impl Quantity {
+    pub fn from_f64(value: f64) -> Self {
+        Quantity(decimal(value))
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.0.to_f64().unwrap_or_default()
+    }
+}
+
+impl Money {
+    pub fn new(amount: Decimal, currency: Currency) -> Self {
+        Money { amount, currency }
+    }
+
+    pub fn from_f64(amount: f64, currency: Currency) -> Self {
+        Money::new(decimal(amount), currency)
+    }
+
+    pub fn to_f64(self) -> f64 {
+        self.amount.to_f64().unwrap_or_default()
+    }
+
+    /// Sum of two amounts, or `None` if they are in different currencies.
+    pub fn checked_add(self, other: Money) -> Option<Money> {
+        (self.currency == other.currency)
+            .then(|| Money::new(self.amount + other.amount, self.currency))
+    }
+
+    pub fn checked_sub(self, other: Money) -> Option<Money> {
+        self.checked_add(-other)
+    }
+
+    /// Rounds to the currency's minor unit, half away from zero.
+    pub fn round(self) -> Money {
+        Money::new(
+            self.amount.round_dp_with_strategy(
+                self.currency.decimals() as u32,
+                RoundingStrategy::MidpointAwayFromZero,
+            ),
+            self.currency,
+        )
+    }
+
+    /// Rounded in decimal first, so a float never decides the last digit and
+    /// a tiny negative amount doesn't show as `-0.00`.
+    pub fn format(&self) -> String {
+        self.currency.format(self.round().to_f64())
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+
+    fn neg(self) -> Money {
+        Money::new(-self.amount, self.currency)
+    }
+}
+
+impl Mul<Decimal> for Money {
+    type Output = Money;
+
+    fn mul(self, rhs: Decimal) -> Money {
+        Money::new(self.amount * rhs, self.currency)
+    }
+}
+
+impl Mul<Decimal> for Quantity {
+    type Output = Quantity;
+
+    fn mul(self, rhs: Decimal) -> Quantity {
+        Quantity(self.0 * rhs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    Nearest,
+    /// Towards the lower tick, e.g. for the bottom of a range.
+    Down,
+    /// Towards the higher tick, e.g. for the top of a range.
+    Up,
+}
+
+/// One step of a tick ladder: prices from `from` up to, but excluding,
+/// `below` trade in multiples of `tick`. The top band has no upper bound.
+#[derive(Debug, Clone, PartialEq)]
+struct TickBand {
+    from: Decimal,
+    below: Option<Decimal>,
+    tick: Decimal,
+}
+
+impl TickBand {
+    fn contains(&self, price: Decimal) -> bool {
+        self.from <= price && self.below.is_none_or(|below| price < below)
+    }
+}
+
+/// Minimum price increment, stepping up with the price in contiguous bands,
+/// lowest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickSize(Vec<TickBand>);
+
+impl TickSize {
+    /// The exchange's standard tick table for ordinary shares.
+    pub fn for_exchange(exchange: Exchange) -> TickSize {
+        let ladder = |bands: &[(&str, Option<&str>, &str)]| {
+            let decimal = |s: &str| Decimal::from_str(s).expect("tick tables are valid decimals");
+            TickSize(
+                bands
+                    .iter()
+                    .map(|(from, below, tick)| TickBand {
+                        from: decimal(from),
+                        below: below.map(decimal),
+                        tick: decimal(tick),
+                    })
+                    .collect(),
+            )
+        };
+
+        match exchange {
+            // Reg NMS: sub-dollar stocks quote in hundredths of a cent.
+            Exchange::Us => ladder(&[("0", Some("1"), "0.0001"), ("1", None, "0.01")]),
+            Exchange::Asx => ladder(&[
+                ("0", Some("0.10"), "0.001"),
+                ("0.10", Some("2.00"), "0.005"),
+                ("2.00", None, "0.01"),
+            ]),
+            // MiFID II table for the most liquid band, in pence.
+            Exchange::Lse => ladder(&[
+                ("0", Some("0.1"), "0.0001"),
+                ("0.1", Some("0.2"), "0.0002"),
+                ("0.2", Some("0.5"), "0.0005"),
+                ("0.5", Some("1"), "0.001"),
+                ("1", Some("2"), "0.002"),
+                ("2", Some("5"), "0.005"),
+                ("5", Some("10"), "0.01"),
+                ("10", Some("20"), "0.02"),
+                ("20", Some("50"), "0.05"),
+                ("50", Some("100"), "0.1"),
+                ("100", Some("200"), "0.2"),
+                ("200", Some("500"), "0.5"),
+                ("500", Some("1000"), "1"),
+                ("1000", Some("2000"), "2"),
+                ("2000", Some("5000"), "5"),
+                ("5000", Some("10000"), "10"),
+                ("10000", None, "20"),
+            ]),
+            Exchange::Tse => ladder(&[
+                ("0", Some("3000"), "1"),
+                ("3000", Some("5000"), "5"),
+                ("5000", Some("30000"), "10"),
+                ("30000", Some("50000"), "50"),
+                ("50000", Some("300000"), "100"),
+                ("300000", Some("500000"), "500"),
+                ("500000", Some("3000000"), "1000"),
+                ("3000000", Some("5000000"), "5000"),
+                ("5000000", Some("30000000"), "10000"),
+                ("30000000", Some("50000000"), "50000"),
+                ("50000000", None, "100000"),
+            ]),
+        }
+    }
+
+    /// The tick for `price`, or `None` if no band covers it.
+    pub fn tick_at(&self, price: Price) -> Option<Decimal> {
+        self.0
+            .iter()
+            .find(|band| band.contains(price.0))
+            .map(|band| band.tick)
+    }
+
+    /// Snaps `price` onto the tick grid. Prices outside every band are
+    /// returned unchanged.
+    pub fn round(&self, price: Price, rounding: Rounding) -> Price {
+        let Some(tick) = self.tick_at(price).filter(|tick| !tick.is_zero()) else {
+            return price;
+        };
+        let ticks = price.0 / tick;
+        let ticks = match rounding {
+            Rounding::Nearest => {
+                ticks.round_dp_with_strategy(0, RoundingStrategy::MidpointAwayFromZero)
+            }
+            Rounding::Down => ticks.floor(),
+            Rounding::Up => ticks.ceil(),
+        };
+        Price((ticks * tick).normalize())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(s: &str) -> Price {
+        Price(Decimal::from_str(s).unwrap())
+    }
+
+    fn money(s: &str, currency: Currency) -> Money {
+        Money::new(Decimal::from_str(s).unwrap(), currency)
+    }
+
+    fn tick(exchange: Exchange, at: &str) -> Option<Decimal> {
+        TickSize::for_exchange(exchange).tick_at(price(at))
+    }
+
+    #[test]
+    fn ladder_bands_switch_at_their_lower_bound() {
+        let d = |s: &str| Some(Decimal::from_str(s).unwrap());
+        assert_eq!(tick(Exchange::Us, "0.9999"), d("0.0001"));
+        assert_eq!(tick(Exchange::Us, "1"), d("0.01"));
+        assert_eq!(tick(Exchange::Us, "25000"), d("0.01"));
+        assert_eq!(tick(Exchange::Asx, "0.099"), d("0.001"));
+        assert_eq!(tick(Exchange::Asx, "0.10"), d("0.005"));
+        assert_eq!(tick(Exchange::Asx, "2.00"), d("0.01"));
+        assert_eq!(tick(Exchange::Lse, "9999"), d("10"));
+        assert_eq!(tick(Exchange::Lse, "10000"), d("20"));
+        assert_eq!(tick(Exchange::Tse, "2999"), d("1"));
+        assert_eq!(tick(Exchange::Tse, "3000"), d("5"));
+        assert_eq!(tick(Exchange::Tse, "50000000"), d("100000"));
+        assert_eq!(tick(Exchange::Us, "-1"), None);
+    }
+
+    #[test]
+    fn rounding_modes() {
+        let us = TickSize::for_exchange(Exchange::Us);
+        let round = |p: &str, rounding| us.round(price(p), rounding);
+
+        assert_eq!(round("12.345", Rounding::Nearest), price("12.35"));
+        assert_eq!(round("12.344", Rounding::Nearest), price("12.34"));
+        assert_eq!(round("12.349", Rounding::Down), price("12.34"));
+        assert_eq!(round("12.341", Rounding::Up), price("12.35"));
+        for rounding in [Rounding::Nearest, Rounding::Down, Rounding::Up] {
+            assert_eq!(round("12.34", rounding), price("12.34"));
+        }
+        assert_eq!(round("0.12345", Rounding::Nearest), price("0.1235"));
+        assert_eq!(round("-1", Rounding::Nearest), price("-1"));
+    }
+
+    #[test]
+    fn arithmetic_refuses_mixed_currencies() {
+        let usd = |s| money(s, Currency::Usd);
+        assert_eq!(usd("0.1").checked_add(usd("0.2")), Some(usd("0.3")));
+        assert_eq!(usd("101.5").checked_sub(usd("102.25")), Some(usd("-0.75")));
+        assert_eq!(usd("1").checked_add(money("1", Currency::Aud)), None);
+        assert_eq!(usd("1").checked_sub(money("1", Currency::Gbx)), None);
+        assert_eq!(-usd("2.5"), usd("-2.5"));
+    }
+
+    #[test]
+    fn rounds_to_the_currency_minor_unit() {
+        assert_eq!(
+            money("1.005", Currency::Usd).round(),
+            money("1.01", Currency::Usd)
+        );
+        assert_eq!(
+            money("-1.005", Currency::Usd).round(),
+            money("-1.01", Currency::Usd)
+        );
+        assert_eq!(
+            money("1234.5", Currency::Jpy).round(),
+            money("1235", Currency::Jpy)
+        );
+        // 1.005 is 1.00499… as a float.
+        assert_eq!(money("1.005", Currency::Usd).format(), "$1.01");
+        assert_eq!(money("-0.001", Currency::Usd).format(), "$0.00");
+    }
+
+    #[test]
+    fn millions_scale_exactly() {
+        let cap = Money::from_f64(1234.567891, Currency::Usd) * MILLION;
+        assert_eq!(cap, money("1234567891", Currency::Usd));
+        let shares = Quantity::from_f64(0.000001) * MILLION;
+        assert_eq!(shares, Quantity(Decimal::ONE));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::diagnostics::traced;
+use crate::platform;
+use crate::store::APP_STATE;
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use chrono_tz::Tz;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Company news per symbol, newest first, merged across fetches.
+pub static NEWS: GlobalSignal<HashMap<String, Vec<Article>>> = Global::new(HashMap::new);
+/// Whether `ChartView` overlays daily news sentiment on prices.
+pub static SENTIMENT_OVERLAY: GlobalMemo<bool> =
+    Memo::global(|| APP_STATE.read().settings.sentiment_overlay);
+
+/// Articles kept per symbol in the local cache.
+const CACHE_LIMIT: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Article {
+    #[serde(default)]
+    pub id: i64,
+    /// Unix time of publication.
+    #[serde(default)]
+    pub datetime: i64,
+    #[serde(default)]
+    pub headline: String,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub url: String,
+    #[serde(default)]
+    pub image: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub related: String,
+}
+
+impl Article {
+    pub fn published(&self) -> Option<DateTime<Utc>> {
+        Utc.timestamp_opt(self.datetime, 0).single()
+    }
+
+    /// Headline reduced to lowercase letters and digits, so syndicated copies
+    /// of the same story match.
+    fn headline_key(&self) -> String {
+        self.headline
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    }
+}
+
+/// Newest first, dropping repeated ids and repeated headlines.
+pub fn dedup(mut articles: Vec<Article>) -> Vec<Article> {
+    articles.sort_by_key(|a| Reverse(a.datetime));
+    let mut ids = HashSet::new();
+    let mut headlines = HashSet::new();
+    articles.retain(|a| {
+        let new_id = a.id == 0 || ids.insert(a.id);
+        new_id && headlines.insert(a.headline_key())
+    });
+    articles
+}
+
+fn storage_key(symbol: &str) -> String {
+    format!("finoracle.news.{symbol}")
+}
+
+pub fn cached_news(symbol: &str) -> Vec<Article> {
+    platform::load_cached(&storage_key(symbol)).unwrap_or_default()
+}
+
+pub fn store_news(symbol: &str, articles: &[Article]) {
+    platform::save_cached(
+        &storage_key(symbol),
+        &articles[..articles.len().min(CACHE_LIMIT)],
+    );
+}
+
+/// Fetches `symbol`'s news for `[from, to]`, merges it into `NEWS` and the
+/// local cache, and falls back to the cache when the provider fails.
+pub async fn load_news(symbol: String, from: NaiveDate, to: NaiveDate, api_key: String) {
+    if symbol.is_empty() {
+        return;
+    }
+
+    let mut articles = NEWS()
+        .get(&symbol)
+        .cloned()
+        .unwrap_or_else(|| cached_news(&symbol));
+    let request = get_company_news(symbol.clone(), from, to, api_key);
+    if let Ok(fetched) = traced("company_news", &symbol, request).await {
+        articles.extend(fetched);
+    }
+    let articles = dedup(articles);
+    store_news(&symbol, &articles);
+    NEWS.write().insert(symbol, articles);
+}
+
+/// Articles for `symbol` published on `[from, to]`, as dates in `tz`, the
+/// same days `daily_sentiment` groups them by.
+pub fn news_between(symbol: &str, from: NaiveDate, to: NaiveDate, tz: Tz) -> Vec<Article> {
+    NEWS()
+        .get(symbol)
+        .map(|articles| {
+            articles
+                .iter()
+                .filter(|a| {
+                    a.published()
+                        .is_some_and(|t| (from..=to).contains(&t.with_timezone(&tz).date_naive()))
+                })
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[server]
+async fn get_company_news(
+    symbol: String,
+    from: NaiveDate,
+    to: NaiveDate,
+    api_key: String,
+) -> Result<Vec<Article>, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/company-news?symbol={}&from={from}&to={to}&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let articles: Vec<Article> = serde_json::from_str(&body)?;
+    Ok(articles)
+}
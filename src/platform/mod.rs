@@ -0,0 +1,52 @@
+//! Browser APIs the app needs outside of rsx: canvas drawing, element
+//! geometry and scrolling, and key-value storage. On the web they are called
+//! directly through `web_sys`; in a native window the page lives in a
+//! webview, so the same calls go through its JavaScript bridge and storage
+//! goes to files in the app's data directory.
+
+#[cfg(target_arch = "wasm32")]
+mod web;
+#[cfg(target_arch = "wasm32")]
+pub use web::*;
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native;
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// An element's box in client pixels.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Rect {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// The value saved under `key`, if any and still readable.
+pub fn load<T: DeserializeOwned>(key: &str) -> Option<T> {
+    serde_json::from_str(&load_string(key)?).ok()
+}
+
+/// Saves `value` under `key`. Failures such as a full quota are ignored.
+pub fn save<T: Serialize + ?Sized>(key: &str, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        save_string(key, &json);
+    }
+}
+
+/// The cached value under `key`, if any and still readable.
+pub fn load_cached<T: DeserializeOwned>(key: &str) -> Option<T> {
+    serde_json::from_str(&load_cached_string(key)?).ok()
+}
+
+/// Caches `value`, which can be fetched again, under `key`. Clearing the
+/// cache deletes it.
+pub fn save_cached<T: Serialize + ?Sized>(key: &str, value: &T) {
+    if let Ok(json) = serde_json::to_string(value) {
+        save_cached_string(key, &json);
+    }
+}
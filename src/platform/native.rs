@@ -0,0 +1,289 @@
+use super::Rect;
+use dioxus::prelude::*;
+use std::cell::RefCell;
+use std::fmt::Write;
+#[cfg(feature = "desktop")]
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+/// `s` as a JavaScript string literal.
+fn js_string(s: &str) -> String {
+    // Rust's escapes, `\u{..}` included, are valid in JavaScript.
+    format!("{s:?}")
+}
+
+/// `x` as a JavaScript number; infinities would be a syntax error.
+fn js_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        String::from("0")
+    }
+}
+
+/// `$XDG_DATA_HOME/finoracle`, falling back to `%LOCALAPPDATA%` and
+/// `~/.local/share`. Holds what is saved with [`save`](super::save), the
+/// webview's profile and the cache directory.
+#[cfg(feature = "desktop")]
+pub fn data_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+        })?;
+    Some(base.join("finoracle"))
+}
+
+/// `$XDG_CONFIG_HOME/finoracle`, falling back to `%APPDATA%` and
+/// `~/.config`. Holds the saved session and window geometry.
+#[cfg(feature = "desktop")]
+pub fn config_dir() -> Option<PathBuf> {
+    let base = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("finoracle"))
+}
+
+/// Where [`save_cached`](super::save_cached) puts values, all of which
+/// [`clear_cache`] may delete.
+#[cfg(feature = "desktop")]
+fn cache_dir() -> Option<PathBuf> {
+    Some(data_dir()?.join("cache"))
+}
+
+/// The file a key is stored in under `dir`. Keys embed symbols, so anything
+/// that is not safe in a file name is replaced.
+#[cfg(feature = "desktop")]
+fn storage_path(dir: Option<PathBuf>, key: &str) -> Option<PathBuf> {
+    let name: String = key
+        .chars()
+        .map(|c| match c {
+            'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect();
+    Some(dir?.join(format!("{name}.json")))
+}
+
+#[cfg(feature = "desktop")]
+fn write(path: PathBuf, value: &str) {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let _ = std::fs::write(path, value);
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(unused_variables))]
+pub fn load_string(key: &str) -> Option<String> {
+    #[cfg(feature = "desktop")]
+    if let Some(path) = storage_path(data_dir(), key) {
+        return std::fs::read_to_string(path).ok();
+    }
+
+    None
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(unused_variables))]
+pub fn save_string(key: &str, value: &str) {
+    #[cfg(feature = "desktop")]
+    if let Some(path) = storage_path(data_dir(), key) {
+        write(path, value);
+    }
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(unused_variables))]
+pub fn load_cached_string(key: &str) -> Option<String> {
+    #[cfg(feature = "desktop")]
+    if let Some(path) = storage_path(cache_dir(), key) {
+        return std::fs::read_to_string(path).ok();
+    }
+
+    None
+}
+
+#[cfg_attr(not(feature = "desktop"), allow(unused_variables))]
+pub fn save_cached_string(key: &str, value: &str) {
+    #[cfg(feature = "desktop")]
+    if let Some(path) = storage_path(cache_dir(), key) {
+        write(path, value);
+    }
+}
+
+/// Deletes every cached file; saved screens, the session and the webview's
+/// profile are kept.
+#[cfg(feature = "desktop")]
+pub fn clear_cache() {
+    if let Some(dir) = cache_dir() {
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
+
+/// Milliseconds on a monotonic clock, for timing.
+pub fn now_ms() -> f64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+pub async fn bounds(id: &str) -> Option<Rect> {
+    document::eval(&format!(
+        "const el = document.getElementById({});
+        if (!el) return null;
+        const r = el.getBoundingClientRect();
+        return {{ left: r.left, top: r.top, width: r.width, height: r.height }};",
+        js_string(id)
+    ))
+    .join::<Option<Rect>>()
+    .await
+    .ok()
+    .flatten()
+}
+
+/// `(scroll_top, client_height)` of a scrolling element.
+pub async fn scroll_metrics(id: &str) -> Option<(f64, f64)> {
+    document::eval(&format!(
+        "const el = document.getElementById({});
+        return el ? [el.scrollTop, el.clientHeight] : null;",
+        js_string(id)
+    ))
+    .join::<Option<(f64, f64)>>()
+    .await
+    .ok()
+    .flatten()
+}
+
+pub fn set_scroll_top(id: &str, top: f64) {
+    document::eval(&format!(
+        "const el = document.getElementById({});
+        if (el) el.scrollTop = {};",
+        js_string(id),
+        js_number(top)
+    ));
+}
+
+/// A `<canvas>` element's 2D context. Calls are collected into one script
+/// that runs in the webview when the canvas is dropped.
+pub struct Canvas {
+    width: f64,
+    height: f64,
+    script: RefCell<String>,
+}
+
+impl Canvas {
+    /// The canvas with `id`, resized (and so cleared) to `width` by `height`.
+    /// Whether it exists is only known in the webview, where a missing
+    /// canvas makes the drawing a no-op.
+    pub fn open(id: &str, width: f64, height: f64) -> Option<Canvas> {
+        let (width, height) = (width.max(1.0).floor(), height.max(1.0).floor());
+        let script = format!(
+            "const canvas = document.getElementById({});
+            if (canvas) {{
+            canvas.width = {width};
+            canvas.height = {height};
+            const ctx = canvas.getContext('2d');\n",
+            js_string(id)
+        );
+        Some(Canvas {
+            width,
+            height,
+            script: RefCell::new(script),
+        })
+    }
+
+    fn call(&self, method: &str, args: &[f64]) {
+        let args: Vec<String> = args.iter().map(|a| js_number(*a)).collect();
+        let _ = writeln!(
+            self.script.borrow_mut(),
+            "ctx.{method}({});",
+            args.join(", ")
+        );
+    }
+
+    fn set(&self, property: &str, value: &str) {
+        let _ = writeln!(
+            self.script.borrow_mut(),
+            "ctx.{property} = {};",
+            js_string(value)
+        );
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn height(&self) -> f64 {
+        self.height
+    }
+
+    pub fn clear_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+        self.call("clearRect", &[x, y, w, h]);
+    }
+
+    pub fn fill_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+        self.call("fillRect", &[x, y, w, h]);
+    }
+
+    pub fn begin_path(&self) {
+        self.call("beginPath", &[]);
+    }
+
+    pub fn move_to(&self, x: f64, y: f64) {
+        self.call("moveTo", &[x, y]);
+    }
+
+    pub fn line_to(&self, x: f64, y: f64) {
+        self.call("lineTo", &[x, y]);
+    }
+
+    pub fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+        self.call("arc", &[x, y, radius, start, end]);
+    }
+
+    pub fn stroke(&self) {
+        self.call("stroke", &[]);
+    }
+
+    pub fn fill(&self) {
+        self.call("fill", &[]);
+    }
+
+    pub fn fill_text(&self, text: &str, x: f64, y: f64) {
+        let _ = writeln!(
+            self.script.borrow_mut(),
+            "ctx.fillText({}, {}, {});",
+            js_string(text),
+            js_number(x),
+            js_number(y)
+        );
+    }
+
+    pub fn set_stroke_style_str(&self, style: &str) {
+        self.set("strokeStyle", style);
+    }
+
+    pub fn set_fill_style_str(&self, style: &str) {
+        self.set("fillStyle", style);
+    }
+
+    pub fn set_line_width(&self, width: f64) {
+        let _ = writeln!(
+            self.script.borrow_mut(),
+            "ctx.lineWidth = {};",
+            js_number(width)
+        );
+    }
+
+    pub fn set_font(&self, font: &str) {
+        self.set("font", font);
+    }
+}
+
+impl Drop for Canvas {
+    fn drop(&mut self) {
+        let script = self.script.get_mut();
+        script.push('}');
+        document::eval(script);
+    }
+}
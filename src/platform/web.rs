@@ -0,0 +1,141 @@
+use super::Rect;
+use web_sys::wasm_bindgen::JsCast;
+use web_sys::{CanvasRenderingContext2d, Element, HtmlCanvasElement, Storage};
+
+fn element(id: &str) -> Option<Element> {
+    web_sys::window()?.document()?.get_element_by_id(id)
+}
+
+fn storage() -> Option<Storage> {
+    web_sys::window()?.local_storage().ok().flatten()
+}
+
+pub fn load_string(key: &str) -> Option<String> {
+    storage()?.get_item(key).ok().flatten()
+}
+
+pub fn save_string(key: &str, value: &str) {
+    if let Some(storage) = storage() {
+        let _ = storage.set_item(key, value);
+    }
+}
+
+/// The browser evicts local storage itself, so caches share it.
+pub fn load_cached_string(key: &str) -> Option<String> {
+    load_string(key)
+}
+
+pub fn save_cached_string(key: &str, value: &str) {
+    save_string(key, value);
+}
+
+/// Milliseconds on a monotonic clock, for timing.
+pub fn now_ms() -> f64 {
+    web_sys::window()
+        .and_then(|w| w.performance())
+        .map_or(0.0, |p| p.now())
+}
+
+pub async fn bounds(id: &str) -> Option<Rect> {
+    let rect = element(id)?.get_bounding_client_rect();
+    Some(Rect {
+        left: rect.left(),
+        top: rect.top(),
+        width: rect.width(),
+        height: rect.height(),
+    })
+}
+
+/// `(scroll_top, client_height)` of a scrolling element.
+pub async fn scroll_metrics(id: &str) -> Option<(f64, f64)> {
+    let el = element(id)?;
+    Some((el.scroll_top() as f64, el.client_height() as f64))
+}
+
+pub fn set_scroll_top(id: &str, top: f64) {
+    if let Some(el) = element(id) {
+        el.set_scroll_top(top as i32);
+    }
+}
+
+/// A `<canvas>` element's 2D context.
+pub struct Canvas {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+}
+
+impl Canvas {
+    /// The canvas with `id`, resized (and so cleared) to `width` by `height`.
+    pub fn open(id: &str, width: f64, height: f64) -> Option<Canvas> {
+        let canvas = element(id)?.dyn_into::<HtmlCanvasElement>().ok()?;
+        canvas.set_width(width.max(1.0) as u32);
+        canvas.set_height(height.max(1.0) as u32);
+        let ctx = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()?
+            .dyn_into::<CanvasRenderingContext2d>()
+            .ok()?;
+        Some(Canvas { canvas, ctx })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.canvas.width() as f64
+    }
+
+    pub fn height(&self) -> f64 {
+        self.canvas.height() as f64
+    }
+
+    pub fn clear_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.clear_rect(x, y, w, h);
+    }
+
+    pub fn fill_rect(&self, x: f64, y: f64, w: f64, h: f64) {
+        self.ctx.fill_rect(x, y, w, h);
+    }
+
+    pub fn begin_path(&self) {
+        self.ctx.begin_path();
+    }
+
+    pub fn move_to(&self, x: f64, y: f64) {
+        self.ctx.move_to(x, y);
+    }
+
+    pub fn line_to(&self, x: f64, y: f64) {
+        self.ctx.line_to(x, y);
+    }
+
+    pub fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) {
+        let _ = self.ctx.arc(x, y, radius, start, end);
+    }
+
+    pub fn stroke(&self) {
+        self.ctx.stroke();
+    }
+
+    pub fn fill(&self) {
+        self.ctx.fill();
+    }
+
+    pub fn fill_text(&self, text: &str, x: f64, y: f64) {
+        let _ = self.ctx.fill_text(text, x, y);
+    }
+
+    pub fn set_stroke_style_str(&self, style: &str) {
+        self.ctx.set_stroke_style_str(style);
+    }
+
+    pub fn set_fill_style_str(&self, style: &str) {
+        self.ctx.set_fill_style_str(style);
+    }
+
+    pub fn set_line_width(&self, width: f64) {
+        self.ctx.set_line_width(width);
+    }
+
+    pub fn set_font(&self, font: &str) {
+        self.ctx.set_font(font);
+    }
+}
@@ -0,0 +1,550 @@
+use crate::calendar::TradingCalendar;
+use crate::series::DataPoint;
+use crate::store::APP_STATE;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// What to do with points that fail validation.
+pub static QUALITY_POLICY: GlobalMemo<Policy> =
+    Memo::global(|| APP_STATE.read().settings.quality_policy);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Policy {
+    /// Leave flagged points out of the series.
+    Drop,
+    /// Keep every plottable point and mark the flagged ones.
+    Flag,
+    /// Fix what can be fixed from neighbouring points, drop the rest.
+    Repair,
+}
+
+impl Policy {
+    pub const ALL: [Policy; 3] = [Policy::Drop, Policy::Flag, Policy::Repair];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Policy::Drop => "Drop",
+            Policy::Flag => "Flag",
+            Policy::Repair => "Repair",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub enum Issue {
+    /// The period could not be parsed, or the value is missing or not finite.
+    Invalid(String),
+    /// Superseded by a later point with the same period.
+    Duplicate,
+    /// Trading days, or intraday bars, missing before this point.
+    Gap(usize),
+    /// A move away from and straight back to the trend, as a log return.
+    Spike(f64),
+    /// Last of a run of this many identical values.
+    Stale(usize),
+    NonPositive,
+}
+
+impl Issue {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Issue::Invalid(_) => "invalid",
+            Issue::Duplicate => "duplicate",
+            Issue::Gap(_) => "gap",
+            Issue::Spike(_) => "spike",
+            Issue::Stale(_) => "stale",
+            Issue::NonPositive => "non-positive",
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::Invalid(reason) => format!("invalid: {reason}"),
+            Issue::Duplicate => String::from("duplicate period"),
+            Issue::Gap(n) => format!("{n} missing before"),
+            Issue::Spike(r) => format!("spike of {:+.1}%", (r.exp() - 1.0) * 100.0),
+            Issue::Stale(n) => format!("unchanged for {n} points"),
+            Issue::NonPositive => String::from("non-positive price"),
+        }
+    }
+
+    /// Whether the point itself is wrong, rather than just sitting next to
+    /// missing data.
+    fn is_bad(&self) -> bool {
+        !matches!(self, Issue::Gap(_))
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Flag {
+    /// Position in the raw series as received.
+    pub index: usize,
+    pub period: Option<DateTime<Utc>>,
+    pub value: Option<f64>,
+    pub issue: Issue,
+}
+
+/// Which checks apply to a series.
+#[derive(Debug, Clone)]
+pub struct Rules<'a> {
+    /// Counts missing sessions when set together with `interval`.
+    pub calendar: Option<&'a TradingCalendar>,
+    pub interval: Option<Duration>,
+    /// Values must be greater than zero, as prices are.
+    pub positive: bool,
+    /// Robust z-score a return must exceed, both ways, to count as a spike.
+    pub spike_threshold: f64,
+    /// Identical consecutive values before a run counts as stale; 0 disables.
+    pub stale_run: usize,
+}
+
+impl<'a> Rules<'a> {
+    pub fn prices(calendar: &'a TradingCalendar, interval: Duration) -> Self {
+        Rules {
+            calendar: Some(calendar),
+            interval: Some(interval),
+            positive: true,
+            spike_threshold: 10.0,
+            stale_run: 5,
+        }
+    }
+
+    /// Volumes are positive but legitimately repeat and jump around.
+    pub fn volumes(calendar: &'a TradingCalendar, interval: Duration) -> Self {
+        Rules {
+            positive: false,
+            spike_threshold: f64::INFINITY,
+            stale_run: 0,
+            ..Rules::prices(calendar, interval)
+        }
+    }
+
+    /// Reported fundamentals, which can be negative and have irregular periods.
+    pub fn fundamentals() -> Self {
+        Rules {
+            calendar: None,
+            interval: None,
+            positive: false,
+            spike_threshold: f64::INFINITY,
+            stale_run: 0,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Default)]
+pub struct QualityReport {
+    pub total: usize,
+    pub flags: Vec<Flag>,
+}
+
+impl QualityReport {
+    pub fn is_clean(&self) -> bool {
+        self.flags.is_empty()
+    }
+
+    pub fn counts(&self) -> BTreeMap<&'static str, usize> {
+        let mut counts = BTreeMap::new();
+        for flag in &self.flags {
+            *counts.entry(flag.issue.kind()).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// One line such as `2 duplicate, 1 spike in 1258 points`.
+    pub fn summary(&self) -> String {
+        if self.is_clean() {
+            return format!("{} points, no issues", self.total);
+        }
+        let counts = self
+            .counts()
+            .into_iter()
+            .map(|(kind, n)| format!("{n} {kind}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!("{counts} in {} points", self.total)
+    }
+}
+
+/// A series after validation and the chosen policy.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Cleaned {
+    pub points: Vec<DataPoint>,
+    /// Parallel to `points`: flagged, or repaired, points to highlight.
+    pub marked: Vec<bool>,
+    pub report: QualityReport,
+}
+
+/// Validates a raw `[{period, v}]` series and applies `policy` to it.
+pub fn clean(raw: &Value, rules: &Rules, policy: Policy) -> Cleaned {
+    let raw = raw.as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut report = QualityReport {
+        total: raw.len(),
+        flags: Vec::new(),
+    };
+
+    let mut points = Vec::with_capacity(raw.len());
+    for (index, value) in raw.iter().enumerate() {
+        match serde_json::from_value::<DataPoint>(value.clone()) {
+            Ok(dp) if dp.v.is_finite() => points.push((index, dp)),
+            Ok(dp) => report.flags.push(Flag {
+                index,
+                period: Some(dp.period),
+                value: None,
+                issue: Issue::Invalid(String::from("value is not a finite number")),
+            }),
+            Err(e) => report.flags.push(Flag {
+                index,
+                period: None,
+                value: None,
+                issue: Issue::Invalid(e.to_string()),
+            }),
+        }
+    }
+    points.sort_by_key(|(_, dp)| dp.period);
+
+    let issues = detect(&points, rules);
+    for ((index, dp), issues) in points.iter().zip(&issues) {
+        for issue in issues {
+            report.flags.push(Flag {
+                index: *index,
+                period: Some(dp.period),
+                value: Some(dp.v),
+                issue: issue.clone(),
+            });
+        }
+    }
+    report.flags.sort_by_key(|f| f.index);
+
+    let mut cleaned = Cleaned {
+        points: Vec::with_capacity(points.len()),
+        marked: Vec::with_capacity(points.len()),
+        report,
+    };
+    let bad: Vec<bool> = issues.iter().map(|i| i.iter().any(Issue::is_bad)).collect();
+    for (i, ((_, dp), issues)) in points.iter().zip(&issues).enumerate() {
+        let mut dp = dp.clone();
+        match policy {
+            Policy::Flag => {}
+            Policy::Drop if bad[i] => continue,
+            Policy::Drop => {}
+            Policy::Repair => {
+                if issues.contains(&Issue::Duplicate) {
+                    continue;
+                }
+                if issues
+                    .iter()
+                    .any(|i| matches!(i, Issue::Spike(_) | Issue::NonPositive))
+                {
+                    match interpolate(&points, &bad, i) {
+                        Some(v) => dp.v = v,
+                        None => continue,
+                    }
+                }
+            }
+        }
+        cleaned.points.push(dp);
+        cleaned.marked.push(!issues.is_empty());
+    }
+    cleaned
+}
+
+/// Per-point issues for points already sorted by period.
+fn detect(points: &[(usize, DataPoint)], rules: &Rules) -> Vec<Vec<Issue>> {
+    let mut issues = vec![Vec::new(); points.len()];
+
+    for i in 1..points.len() {
+        if points[i].1.period == points[i - 1].1.period {
+            issues[i - 1].push(Issue::Duplicate);
+        }
+    }
+
+    if rules.positive {
+        for (i, (_, dp)) in points.iter().enumerate() {
+            if dp.v <= 0.0 {
+                issues[i].push(Issue::NonPositive);
+            }
+        }
+    }
+
+    // Later checks compare each point with its neighbours, so they only look
+    // at the points that will survive de-duplication and are usable.
+    let usable: Vec<usize> = (0..points.len())
+        .filter(|&i| {
+            !issues[i]
+                .iter()
+                .any(|i| matches!(i, Issue::Duplicate | Issue::NonPositive))
+        })
+        .collect();
+
+    if let (Some(calendar), Some(interval)) = (rules.calendar, rules.interval) {
+        for pair in usable.windows(2) {
+            let (from, to) = (points[pair[0]].1.period, points[pair[1]].1.period);
+            let missing = missing_between(calendar, interval, from, to);
+            if missing > 0 {
+                issues[pair[1]].push(Issue::Gap(missing));
+            }
+        }
+    }
+
+    if rules.spike_threshold.is_finite() {
+        let returns: Vec<f64> = usable
+            .windows(2)
+            .map(|w| (points[w[1]].1.v / points[w[0]].1.v).ln())
+            .collect();
+        if let Some(bound) = spike_bound(&returns, rules.spike_threshold) {
+            // A spike jumps away on one return and straight back on the next;
+            // a single large move is a real repricing and is left alone.
+            for (k, pair) in returns.windows(2).enumerate() {
+                let (into, out) = (pair[0], pair[1]);
+                if into.abs() > bound && out.abs() > bound && into.signum() != out.signum() {
+                    issues[usable[k + 1]].push(Issue::Spike(into));
+                }
+            }
+        }
+    }
+
+    if rules.stale_run > 1 {
+        let mut run = 1;
+        for k in 1..=usable.len() {
+            let same = k < usable.len() && points[usable[k]].1.v == points[usable[k - 1]].1.v;
+            if same {
+                run += 1;
+                continue;
+            }
+            if run >= rules.stale_run {
+                issues[usable[k - 1]].push(Issue::Stale(run));
+            }
+            run = 1;
+        }
+    }
+
+    issues
+}
+
+/// Sessions, or bars within sessions, the calendar expected between two
+/// consecutive points.
+fn missing_between(
+    calendar: &TradingCalendar,
+    interval: Duration,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> usize {
+    if interval >= Duration::days(1) {
+        if interval > Duration::days(1) {
+            return 0;
+        }
+        // Daily candles are stamped at midnight UTC on the session date.
+        let (from, to) = (from.date_naive(), to.date_naive());
+        if !calendar.covers(from) || !calendar.covers(to) {
+            return 0;
+        }
+        let mut missing = 0;
+        let mut date: NaiveDate = calendar.next_trading_day(from);
+        while date < to {
+            missing += 1;
+            date = calendar.next_trading_day(date);
+        }
+        return missing;
+    }
+
+    if !calendar.covers(calendar.local_date(from)) {
+        return 0;
+    }
+    let bars = calendar.trading_duration(from, to).num_seconds() / interval.num_seconds().max(1);
+    bars.saturating_sub(1) as usize
+}
+
+/// How large a return must be to be unusual: `threshold` times the median
+/// absolute return, scaled like a standard deviation, so a few bad prints
+/// cannot inflate it.
+fn spike_bound(returns: &[f64], threshold: f64) -> Option<f64> {
+    let median = |mut xs: Vec<f64>| {
+        xs.sort_by(|a, b| a.total_cmp(b));
+        xs.get(xs.len() / 2).copied()
+    };
+    let mad = median(returns.iter().map(|r| r.abs()).collect())?;
+    (mad > 0.0).then_some(threshold * 1.4826 * mad)
+}
+
+/// Linear interpolation from the nearest good points either side of `i`.
+fn interpolate(points: &[(usize, DataPoint)], bad: &[bool], i: usize) -> Option<f64> {
+    let before = (0..i).rev().find(|&j| !bad[j]).map(|j| &points[j].1);
+    let after = (i + 1..points.len())
+        .find(|&j| !bad[j])
+        .map(|j| &points[j].1);
+    let at = points[i].1.period;
+    match (before, after) {
+        (Some(a), Some(b)) => {
+            let span = (b.period - a.period).num_seconds().max(1) as f64;
+            let w = (at - a.period).num_seconds() as f64 / span;
+            Some(a.v + (b.v - a.v) * w)
+        }
+        (Some(p), None) | (None, Some(p)) => Some(p.v),
+        (None, None) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::Exchange;
+    use chrono::TimeZone;
+    use serde_json::json;
+
+    fn day(date: &str) -> DateTime<Utc> {
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap();
+        Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+    }
+
+    fn utc(date: &str, hour: u32, minute: u32) -> DateTime<Utc> {
+        day(date) + Duration::hours(hour.into()) + Duration::minutes(minute.into())
+    }
+
+    fn series(points: &[(&str, f64)]) -> Value {
+        points
+            .iter()
+            .map(|(period, v)| json!({"period": period, "v": v}))
+            .collect()
+    }
+
+    #[test]
+    fn daily_gaps_skip_weekends_and_holidays() {
+        let us = Exchange::Us.calendar();
+        let missing = |from, to| missing_between(us, Duration::days(1), day(from), day(to));
+
+        // Friday to Monday.
+        assert_eq!(missing("2026-11-20", "2026-11-23"), 0);
+        // Around Thanksgiving; the half day after it still trades.
+        assert_eq!(missing("2026-11-25", "2026-11-27"), 0);
+        assert_eq!(missing("2026-11-25", "2026-11-30"), 1);
+        assert_eq!(missing("2026-11-17", "2026-11-24"), 4);
+        // Christmas to New Year spans two holidays and a weekend.
+        assert_eq!(missing("2026-12-24", "2027-01-04"), 4);
+    }
+
+    #[test]
+    fn gaps_are_not_counted_where_they_cannot_be_known() {
+        let us = Exchange::Us.calendar();
+        let (from, to) = (day("2026-11-02"), day("2026-11-30"));
+        assert_eq!(missing_between(us, Duration::weeks(1), from, to), 0);
+        // Past the end of the holiday list.
+        let (from, to) = (day("2035-01-02"), day("2035-01-12"));
+        assert_eq!(missing_between(us, Duration::days(1), from, to), 0);
+    }
+
+    #[test]
+    fn intraday_gaps_count_only_trading_time() {
+        let us = Exchange::Us.calendar();
+        let five = Duration::minutes(5);
+        // Last bar before Thanksgiving to the first after it.
+        let (from, to) = (utc("2026-11-25", 20, 55), utc("2026-11-27", 14, 30));
+        assert_eq!(missing_between(us, five, from, to), 0);
+        // The half day closes at 13:00 New York time.
+        let (from, to) = (utc("2026-11-27", 17, 55), utc("2026-11-30", 14, 30));
+        assert_eq!(missing_between(us, five, from, to), 0);
+        let (from, to) = (utc("2026-11-30", 15, 0), utc("2026-11-30", 15, 30));
+        assert_eq!(missing_between(us, five, from, to), 5);
+    }
+
+    #[test]
+    fn spike_bound_scales_the_median_absolute_return() {
+        let bound = spike_bound(&[0.01, -0.01, 0.02, -0.02, 0.01], 10.0).unwrap();
+        assert!((bound - 0.14826).abs() < 1e-12);
+        // One bad print can't widen it.
+        let bound = spike_bound(&[0.01, -0.01, 2.0, -2.0, 0.01], 10.0).unwrap();
+        assert!((bound - 0.14826).abs() < 1e-12);
+        assert_eq!(spike_bound(&[], 10.0), None);
+        assert_eq!(spike_bound(&[0.0, 0.0, 0.0], 10.0), None);
+    }
+
+    /// A week and a half of closes with a spike, a superseded duplicate, a
+    /// zero price and a missing Friday.
+    fn dirty() -> Value {
+        series(&[
+            ("2026-11-02", 100.0),
+            ("2026-11-03", 101.0),
+            ("2026-11-04", 100.0),
+            ("2026-11-05", 150.0),
+            ("2026-11-06", 101.0),
+            ("2026-11-09", 100.0),
+            ("2026-11-09", 100.5),
+            ("2026-11-10", 0.0),
+            ("2026-11-11", 101.0),
+            ("2026-11-12", 100.0),
+            ("2026-11-16", 101.0),
+        ])
+    }
+
+    fn clean_dirty(policy: Policy) -> Cleaned {
+        let rules = Rules::prices(Exchange::Us.calendar(), Duration::days(1));
+        clean(&dirty(), &rules, policy)
+    }
+
+    fn values(cleaned: &Cleaned) -> Vec<f64> {
+        cleaned.points.iter().map(|dp| dp.v).collect()
+    }
+
+    #[test]
+    fn reports_each_issue_once() {
+        let report = clean_dirty(Policy::Flag).report;
+        let flags: Vec<(usize, Issue)> = report
+            .flags
+            .into_iter()
+            .map(|f| (f.index, f.issue))
+            .collect();
+        assert_eq!(
+            flags,
+            vec![
+                (3, Issue::Spike((150.0f64 / 100.0).ln())),
+                (5, Issue::Duplicate),
+                (7, Issue::NonPositive),
+                // The zero price doesn't count as the 10th's close.
+                (8, Issue::Gap(1)),
+                (10, Issue::Gap(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn flag_keeps_every_point() {
+        let cleaned = clean_dirty(Policy::Flag);
+        assert_eq!(
+            values(&cleaned),
+            [100.0, 101.0, 100.0, 150.0, 101.0, 100.0, 100.5, 0.0, 101.0, 100.0, 101.0]
+        );
+        let marked: Vec<usize> = (0..cleaned.marked.len())
+            .filter(|&i| cleaned.marked[i])
+            .collect();
+        assert_eq!(marked, [3, 5, 7, 8, 10]);
+    }
+
+    #[test]
+    fn drop_leaves_out_bad_points_but_keeps_those_after_gaps() {
+        let cleaned = clean_dirty(Policy::Drop);
+        assert_eq!(
+            values(&cleaned),
+            [100.0, 101.0, 100.0, 101.0, 100.5, 101.0, 100.0, 101.0]
+        );
+        assert_eq!(
+            cleaned.marked,
+            [false, false, false, false, false, true, false, true]
+        );
+    }
+
+    #[test]
+    fn repair_interpolates_spikes_and_bad_prices() {
+        let cleaned = clean_dirty(Policy::Repair);
+        assert_eq!(
+            values(&cleaned),
+            [100.0, 101.0, 100.0, 100.5, 101.0, 100.5, 100.75, 101.0, 100.0, 101.0]
+        );
+        assert_eq!(
+            cleaned.marked,
+            [false, false, false, true, false, false, true, true, false, true]
+        );
+        assert_eq!(cleaned.report.total, 11);
+    }
+}
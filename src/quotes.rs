@@ -0,0 +1,201 @@
+use crate::calendar::Session;
+use crate::components::WATCHLIST;
+use crate::currency::Currency;
+use crate::diagnostics::traced;
+use crate::exchange::Exchange;
+use crate::money::Money;
+use crate::store::{Instrument, APP_STATE};
+use chrono::{DateTime, Duration, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Latest quote per symbol, kept fresh by `use_quote_scheduler`.
+pub static QUOTES: GlobalSignal<HashMap<String, QuoteState>> = Global::new(HashMap::new);
+/// Seconds between quote refreshes while the exchange is open.
+pub static QUOTE_REFRESH_SECS: GlobalMemo<u64> =
+    Memo::global(|| APP_STATE.read().settings.quote_refresh_secs);
+/// Trading session of every exchange as of the last scheduler tick.
+pub static MARKET_SESSIONS: GlobalSignal<HashMap<Exchange, Session>> = Global::new(sessions);
+
+/// How long a price change stays highlighted.
+const FLASH_MILLIS: u64 = 1200;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Quote {
+    #[serde(rename = "c", default)]
+    pub current: f64,
+    #[serde(rename = "d", default)]
+    pub change: Option<f64>,
+    #[serde(rename = "dp", default)]
+    pub change_percent: Option<f64>,
+    #[serde(rename = "h", default)]
+    pub high: f64,
+    #[serde(rename = "l", default)]
+    pub low: f64,
+    #[serde(rename = "o", default)]
+    pub open: f64,
+    #[serde(rename = "pc", default)]
+    pub previous_close: f64,
+    #[serde(rename = "t", default)]
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceMove {
+    Up,
+    Down,
+}
+
+impl PriceMove {
+    pub fn color(&self) -> &'static str {
+        match self {
+            PriceMove::Up => "#00cc44",
+            PriceMove::Down => "#ee0000",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteState {
+    pub quote: Quote,
+    pub exchange: Exchange,
+    pub currency: Currency,
+    pub updated: DateTime<Utc>,
+    /// Set for a moment after a refresh changed the price.
+    pub flash: Option<PriceMove>,
+}
+
+impl QuoteState {
+    /// An amount from this quote in the instrument's currency.
+    pub fn money(&self, amount: f64) -> Money {
+        Money::from_f64(amount, self.currency)
+    }
+
+    /// The day's change, worked out in decimal from the price and previous
+    /// close rather than taken from the provider's float. `None` until the
+    /// provider knows a previous close.
+    pub fn change(&self) -> Option<Money> {
+        if self.quote.previous_close == 0.0 {
+            return None;
+        }
+        self.money(self.quote.current)
+            .checked_sub(self.money(self.quote.previous_close))
+    }
+
+    /// A quote is stale once it has missed a few refreshes while the market
+    /// is open.
+    pub fn is_stale(&self) -> bool {
+        let interval = Duration::seconds(QUOTE_REFRESH_SECS() as i64);
+        MARKET_SESSIONS().get(&self.exchange) == Some(&Session::Regular)
+            && Utc::now() - self.updated > interval * 3
+    }
+}
+
+/// Refreshes the selected symbol and every watchlist symbol on
+/// `QUOTE_REFRESH_SECS`, but only while the exchange each one is listed on is
+/// open. Symbols without a quote yet are always fetched straight away.
+pub fn use_quote_scheduler(symbol: Signal<Instrument>) {
+    let api_key: &'static str = env!("FINNHUB_API_KEY");
+
+    use_effect(move || {
+        let symbols = watched(symbol(), WATCHLIST());
+        spawn(async move {
+            let missing = symbols
+                .into_iter()
+                .filter(|s| !QUOTES.peek().contains_key(s))
+                .collect();
+            refresh(missing, api_key).await;
+        });
+    });
+
+    use_future(move || async move {
+        loop {
+            let current = sessions();
+            if *MARKET_SESSIONS.peek() != current {
+                *MARKET_SESSIONS.write() = current.clone();
+            }
+
+            let symbols = watched(symbol.peek().clone(), WATCHLIST.peek().clone())
+                .into_iter()
+                .filter(|s| current.get(&Exchange::from_symbol(s)) == Some(&Session::Regular))
+                .collect();
+            refresh(symbols, api_key).await;
+
+            sleep(*QUOTE_REFRESH_SECS.peek() * 1000).await;
+        }
+    });
+}
+
+fn sessions() -> HashMap<Exchange, Session> {
+    let now = Utc::now();
+    Exchange::ALL
+        .into_iter()
+        .map(|e| (e, e.calendar().session_at(now)))
+        .collect()
+}
+
+fn watched(selected: Instrument, watchlist: Vec<Instrument>) -> Vec<String> {
+    let mut symbols: Vec<String> = std::iter::once(selected)
+        .chain(watchlist)
+        .map(|i| i.symbol)
+        .filter(|s| !s.is_empty())
+        .collect();
+    symbols.sort();
+    symbols.dedup();
+    symbols
+}
+
+async fn refresh(symbols: Vec<String>, api_key: &'static str) {
+    for symbol in symbols {
+        let request = get_stock_quote(symbol.clone(), api_key.into());
+        let Ok(quote) = traced("quote", &symbol, request).await else {
+            continue;
+        };
+
+        let previous = QUOTES.peek().get(&symbol).map(|s| s.quote.current);
+        let flash = match previous {
+            Some(p) if quote.current > p => Some(PriceMove::Up),
+            Some(p) if quote.current < p => Some(PriceMove::Down),
+            _ => None,
+        };
+
+        QUOTES.write().insert(
+            symbol.clone(),
+            QuoteState {
+                quote,
+                exchange: Exchange::from_symbol(&symbol),
+                currency: Exchange::from_symbol(&symbol).currency(),
+                updated: Utc::now(),
+                flash,
+            },
+        );
+
+        if flash.is_some() {
+            spawn(async move {
+                sleep(FLASH_MILLIS).await;
+                if let Some(state) = QUOTES.write().get_mut(&symbol) {
+                    state.flash = None;
+                }
+            });
+        }
+    }
+}
+
+pub async fn sleep(millis: u64) {
+    #[cfg(target_arch = "wasm32")]
+    gloo_timers::future::TimeoutFuture::new(u32::try_from(millis).unwrap_or(u32::MAX)).await;
+    #[cfg(not(target_arch = "wasm32"))]
+    tokio::time::sleep(std::time::Duration::from_millis(millis)).await;
+}
+
+#[server]
+pub async fn get_stock_quote(symbol: String, api_key: String) -> Result<Quote, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/quote?symbol={}&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let quote: Quote = serde_json::from_str(&body)?;
+    Ok(quote)
+}
@@ -0,0 +1,716 @@
+use crate::components::{fetch_symbols, WATCHLIST};
+use crate::diagnostics::traced;
+use crate::exchange::Exchange;
+use crate::metrics::{metric_info, Unit};
+use crate::platform;
+use crate::quotes::sleep;
+use chrono::{DateTime, Duration, Utc};
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering as AtomicOrdering};
+
+/// Basic-financial metrics per symbol, loaded from the local store and
+/// refreshed in batches by `refresh_metrics`. Holds at most
+/// `STORED_SYMBOLS`, as the local store does.
+pub static METRIC_STORE: GlobalSignal<HashMap<String, StoredMetrics>> = Global::new(stored_metrics);
+/// `(done, total)` while a batch refresh runs.
+pub static REFRESH_PROGRESS: GlobalSignal<Option<(usize, usize)>> = Global::new(|| None);
+/// Screens saved by the user, persisted locally.
+pub static SCREENS: GlobalSignal<Vec<Screen>> = Global::new(saved_screens);
+
+/// Kept under the provider's free-tier limit of 60 so quotes and charts
+/// still get through while a refresh runs.
+const REQUESTS_PER_MINUTE: u64 = 30;
+/// Metrics older than this are fetched again on refresh.
+const MAX_AGE_DAYS: i64 = 7;
+const SCREENS_KEY: &str = "finoracle.screens";
+const METRICS_KEY: &str = "finoracle.metrics";
+/// Symbols whose metrics are kept, in memory and in the local store alike.
+/// The least recently fetched are dropped first. At roughly 5KB each this
+/// stays well inside a browser's quota, so screens over a larger universe
+/// only cover the symbols fetched most recently.
+pub const STORED_SYMBOLS: usize = 300;
+/// Fetches between saves of the local store during a refresh.
+const SAVE_EVERY: usize = 10;
+
+/// Bumped by every refresh and every cancel, so a cancelled refresh still
+/// sleeping between requests stops even if a new one has started.
+static REFRESH_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Shorthand field names accepted in rules.
+const FIELD_ALIASES: &[(&str, &str)] = &[
+    ("marketCap", "marketCapitalization"),
+    ("pe", "peTTM"),
+    ("pb", "pbQuarterly"),
+    ("ps", "psTTM"),
+    ("roe", "roeTTM"),
+    ("roa", "roaTTM"),
+    ("dividendYield", "dividendYieldIndicatedAnnual"),
+    ("debtToEquity", "totalDebt/totalEquityQuarterly"),
+];
+
+/// The provider key a rule field refers to.
+pub fn resolve_field(field: &str) -> &str {
+    FIELD_ALIASES
+        .iter()
+        .find(|(alias, _)| alias.eq_ignore_ascii_case(field))
+        .map(|(_, key)| *key)
+        .unwrap_or(field)
+}
+
+/// `key` from `metrics` in the base units rules compare against: amounts
+/// and counts in whole units rather than millions, percentages as fractions.
+pub fn base_value(key: &str, metrics: &Map<String, Value>) -> Option<f64> {
+    let v = metrics.get(key)?.as_f64()?;
+    Some(match metric_info(key).unit {
+        Unit::AmountMillions | Unit::Millions => v * 1e6,
+        Unit::Percent => v / 100.0,
+        _ => v,
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+    Ne,
+}
+
+impl Op {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Op::Lt => "<",
+            Op::Le => "<=",
+            Op::Gt => ">",
+            Op::Ge => ">=",
+            Op::Eq => "=",
+            Op::Ne => "!=",
+        }
+    }
+
+    fn apply(&self, a: f64, b: f64) -> bool {
+        match self {
+            Op::Lt => a < b,
+            Op::Le => a <= b,
+            Op::Gt => a > b,
+            Op::Ge => a >= b,
+            Op::Eq => (a - b).abs() <= f64::EPSILON * a.abs().max(b.abs()).max(1.0),
+            Op::Ne => !Op::Eq.apply(a, b),
+        }
+    }
+}
+
+/// A parsed screen such as `peTTM < 15 AND roeTTM > 0.15 AND marketCap > 2e9`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    Compare { field: String, op: Op, value: f64 },
+    And(Vec<Rule>),
+    Or(Vec<Rule>),
+    Not(Box<Rule>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Field(String),
+    Number(f64),
+    Op(Op),
+    And,
+    Or,
+    Not,
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Field(field) => write!(f, "`{field}`"),
+            Token::Number(n) => write!(f, "`{n}`"),
+            Token::Op(op) => write!(f, "`{}`", op.symbol()),
+            Token::And => write!(f, "`AND`"),
+            Token::Or => write!(f, "`OR`"),
+            Token::Not => write!(f, "`NOT`"),
+            Token::Open => write!(f, "`(`"),
+            Token::Close => write!(f, "`)`"),
+        }
+    }
+}
+
+/// Numbers may carry a `K`, `M`, `B` or `T` suffix, e.g. `2B`.
+fn number(word: &str) -> Option<f64> {
+    if let Ok(n) = word.parse::<f64>() {
+        return Some(n);
+    }
+    let suffix = word.chars().last()?;
+    let scale = match suffix {
+        'K' | 'k' => 1e3,
+        'M' | 'm' => 1e6,
+        'B' | 'b' => 1e9,
+        'T' | 't' => 1e12,
+        _ => return None,
+    };
+    let digits = &word[..word.len() - suffix.len_utf8()];
+    digits.parse::<f64>().ok().map(|n| n * scale)
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::Open);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::Close);
+                i += 1;
+            }
+            '<' | '>' | '=' | '!' => {
+                let (op, len) = match (c, next) {
+                    ('<', Some('=')) => (Op::Le, 2),
+                    ('>', Some('=')) => (Op::Ge, 2),
+                    ('!', Some('=')) => (Op::Ne, 2),
+                    ('=', Some('=')) => (Op::Eq, 2),
+                    ('<', _) => (Op::Lt, 1),
+                    ('>', _) => (Op::Gt, 1),
+                    ('=', _) => (Op::Eq, 1),
+                    _ => return Err(format!("expected `!=` at {}", i + 1)),
+                };
+                tokens.push(Token::Op(op));
+                i += len;
+            }
+            '&' if next == Some('&') => {
+                tokens.push(Token::And);
+                i += 2;
+            }
+            '|' if next == Some('|') => {
+                tokens.push(Token::Or);
+                i += 2;
+            }
+            c if c.is_alphanumeric() || matches!(c, '.' | '_' | '-' | '+') => {
+                let start = i;
+                i += 1;
+                while let Some(&c) = chars.get(i) {
+                    // Keep the sign of an exponent such as `1e-3` in the word.
+                    let exponent = matches!(c, '+' | '-')
+                        && matches!(chars[i - 1], 'e' | 'E')
+                        && chars[start..i - 1]
+                            .iter()
+                            .all(|c| c.is_ascii_digit() || *c == '.');
+                    // Provider keys such as `priceRelativeToS&P50052Week`
+                    // contain a lone `&`; `&&` still ends the word.
+                    let ampersand = c == '&' && chars.get(i + 1) != Some(&'&');
+                    if c.is_alphanumeric() || matches!(c, '.' | '_' | '/') || exponent || ampersand
+                    {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                let word: String = chars[start..i].iter().collect();
+                tokens.push(match word.to_uppercase().as_str() {
+                    "AND" => Token::And,
+                    "OR" => Token::Or,
+                    "NOT" => Token::Not,
+                    _ => match number(&word) {
+                        Some(n) => Token::Number(n),
+                        None => Token::Field(word),
+                    },
+                });
+            }
+            c => return Err(format!("unexpected `{c}` at {}", i + 1)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn or(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.and()?];
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            rules.push(self.and()?);
+        }
+        Ok(if rules.len() == 1 {
+            rules.remove(0)
+        } else {
+            Rule::Or(rules)
+        })
+    }
+
+    fn and(&mut self) -> Result<Rule, String> {
+        let mut rules = vec![self.unary()?];
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            rules.push(self.unary()?);
+        }
+        Ok(if rules.len() == 1 {
+            rules.remove(0)
+        } else {
+            Rule::And(rules)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Rule, String> {
+        match self.next() {
+            Some(Token::Not) => Ok(Rule::Not(Box::new(self.unary()?))),
+            Some(Token::Open) => {
+                let rule = self.or()?;
+                match self.next() {
+                    Some(Token::Close) => Ok(rule),
+                    _ => Err(String::from("missing `)`")),
+                }
+            }
+            Some(Token::Field(field)) => match (self.next(), self.next()) {
+                (Some(Token::Op(op)), Some(Token::Number(value))) => Ok(Rule::Compare {
+                    field: resolve_field(&field).to_string(),
+                    op,
+                    value,
+                }),
+                _ => Err(format!(
+                    "expected a comparison such as `{field} > 10` after `{field}`"
+                )),
+            },
+            Some(token) => Err(format!("unexpected {token}")),
+            None => Err(String::from("rule is incomplete")),
+        }
+    }
+}
+
+impl Rule {
+    pub fn parse(input: &str) -> Result<Rule, String> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+        };
+        if parser.tokens.is_empty() {
+            return Err(String::from("rule is empty"));
+        }
+        let rule = parser.or()?;
+        match parser.peek() {
+            None => Ok(rule),
+            Some(token) => Err(format!("unexpected {token} after a complete rule")),
+        }
+    }
+
+    /// A missing metric never satisfies a comparison.
+    pub fn matches(&self, metrics: &Map<String, Value>) -> bool {
+        match self {
+            Rule::Compare { field, op, value } => {
+                base_value(field, metrics).is_some_and(|v| op.apply(v, *value))
+            }
+            Rule::And(rules) => rules.iter().all(|r| r.matches(metrics)),
+            Rule::Or(rules) => rules.iter().any(|r| r.matches(metrics)),
+            Rule::Not(rule) => !rule.matches(metrics),
+        }
+    }
+
+    /// Provider keys the rule reads, in order of first use.
+    pub fn fields(&self) -> Vec<String> {
+        let mut fields = Vec::new();
+        self.collect_fields(&mut fields);
+        fields
+    }
+
+    fn collect_fields(&self, fields: &mut Vec<String>) {
+        match self {
+            Rule::Compare { field, .. } => {
+                if !fields.contains(field) {
+                    fields.push(field.to_owned());
+                }
+            }
+            Rule::And(rules) | Rule::Or(rules) => {
+                rules.iter().for_each(|r| r.collect_fields(fields))
+            }
+            Rule::Not(rule) => rule.collect_fields(fields),
+        }
+    }
+}
+
+/// The symbols a screen runs over.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Universe {
+    /// Every symbol in an exchange's directory.
+    Exchange(Exchange),
+    Watchlist,
+    /// Symbols pasted or imported by the user.
+    Imported(Vec<String>),
+}
+
+impl Universe {
+    /// `(symbol, description)` for each member.
+    pub async fn symbols(&self, api_key: String) -> Vec<(String, String)> {
+        match self {
+            Universe::Exchange(exchange) => {
+                let request = fetch_symbols(*exchange, api_key);
+                traced("symbols", exchange.code(), request)
+                    .await
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|s| {
+                        let symbol = s.get("symbol")?.as_str()?.to_string();
+                        let description = s
+                            .get("description")
+                            .and_then(|d| d.as_str())
+                            .unwrap_or_default()
+                            .to_string();
+                        Some((symbol, description))
+                    })
+                    .collect()
+            }
+            Universe::Watchlist => WATCHLIST()
+                .into_iter()
+                .map(|i| (i.symbol, i.description))
+                .collect(),
+            Universe::Imported(symbols) => symbols
+                .iter()
+                .map(|s| (s.to_owned(), String::new()))
+                .collect(),
+        }
+    }
+}
+
+/// Symbols from pasted text or a CSV file: the first column of delimited
+/// lines, otherwise every whitespace-separated word. Upper-cased, header
+/// and repeats dropped.
+pub fn parse_symbols(text: &str) -> Vec<String> {
+    let mut symbols: Vec<String> = Vec::new();
+    for line in text.lines() {
+        let words: Vec<&str> = match line.find([',', ';', '\t']) {
+            Some(end) => vec![&line[..end]],
+            None => line.split_whitespace().collect(),
+        };
+        for word in words {
+            let symbol = word.trim().trim_matches('"').to_uppercase();
+            if !symbol.is_empty()
+                && !["SYMBOL", "TICKER"].contains(&symbol.as_str())
+                && !symbols.contains(&symbol)
+            {
+                symbols.push(symbol);
+            }
+        }
+    }
+    symbols
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Screen {
+    pub name: String,
+    pub rule: String,
+    pub universe: Universe,
+}
+
+fn saved_screens() -> Vec<Screen> {
+    platform::load(SCREENS_KEY).unwrap_or_default()
+}
+
+/// Saves `screen`, replacing any screen with the same name.
+pub fn save_screen(screen: Screen) {
+    let mut screens = SCREENS.write();
+    match screens.iter_mut().find(|s| s.name == screen.name) {
+        Some(existing) => *existing = screen,
+        None => screens.push(screen),
+    }
+    store_screens(&screens);
+}
+
+pub fn delete_screen(name: &str) {
+    let mut screens = SCREENS.write();
+    screens.retain(|s| s.name != name);
+    store_screens(&screens);
+}
+
+fn store_screens(screens: &[Screen]) {
+    platform::save(SCREENS_KEY, screens);
+}
+
+/// One symbol's `metric` object as last fetched.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StoredMetrics {
+    pub fetched: DateTime<Utc>,
+    pub metric: Map<String, Value>,
+}
+
+impl StoredMetrics {
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now - self.fetched > Duration::days(MAX_AGE_DAYS)
+    }
+}
+
+fn stored_metrics() -> HashMap<String, StoredMetrics> {
+    platform::load_cached(METRICS_KEY).unwrap_or_default()
+}
+
+/// Adds `metrics` for `symbol`, dropping the least recently fetched entries
+/// beyond `limit`.
+fn insert_bounded(
+    store: &mut HashMap<String, StoredMetrics>,
+    symbol: String,
+    metrics: StoredMetrics,
+    limit: usize,
+) {
+    store.insert(symbol, metrics);
+    while store.len() > limit {
+        let oldest = store
+            .iter()
+            .min_by_key(|(_, m)| m.fetched)
+            .map(|(s, _)| s.to_owned());
+        if let Some(symbol) = oldest {
+            store.remove(&symbol);
+        }
+    }
+}
+
+/// Saves `METRIC_STORE` under one key.
+fn save_metrics() {
+    platform::save_cached(METRICS_KEY, &*METRIC_STORE.peek());
+}
+
+/// Fetches metrics for the first `STORED_SYMBOLS` symbols in `symbols` that
+/// have none or only stale ones, paced to `REQUESTS_PER_MINUTE`. Stops early
+/// when `cancel_refresh` is called.
+pub async fn refresh_metrics(symbols: Vec<String>, api_key: String) {
+    if REFRESH_PROGRESS.peek().is_some() {
+        return;
+    }
+    let generation = REFRESH_GENERATION.fetch_add(1, AtomicOrdering::Relaxed) + 1;
+    let cancelled = || REFRESH_GENERATION.load(AtomicOrdering::Relaxed) != generation;
+
+    let now = Utc::now();
+    let stale: Vec<String> = symbols
+        .into_iter()
+        .filter(|s| METRIC_STORE.peek().get(s).is_none_or(|m| m.is_stale(now)))
+        .take(STORED_SYMBOLS)
+        .collect();
+    let total = stale.len();
+    *REFRESH_PROGRESS.write() = Some((0, total));
+
+    for (i, symbol) in stale.into_iter().enumerate() {
+        if cancelled() {
+            break;
+        }
+        let request = get_metrics(symbol.clone(), api_key.clone());
+        if let Ok(metric) = traced("metrics", &symbol, request).await {
+            let stored = StoredMetrics {
+                fetched: Utc::now(),
+                metric,
+            };
+            insert_bounded(&mut METRIC_STORE.write(), symbol, stored, STORED_SYMBOLS);
+        }
+        if (i + 1) % SAVE_EVERY == 0 {
+            save_metrics();
+        }
+        if cancelled() {
+            break;
+        }
+        *REFRESH_PROGRESS.write() = Some((i + 1, total));
+        sleep(60_000 / REQUESTS_PER_MINUTE).await;
+    }
+
+    save_metrics();
+    if !cancelled() {
+        *REFRESH_PROGRESS.write() = None;
+    }
+}
+
+pub fn cancel_refresh() {
+    REFRESH_GENERATION.fetch_add(1, AtomicOrdering::Relaxed);
+    *REFRESH_PROGRESS.write() = None;
+}
+
+/// A symbol passing a screen, with the raw values of the rule's fields.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenMatch {
+    pub symbol: String,
+    pub description: String,
+    pub values: Vec<Option<Value>>,
+}
+
+/// Members of `universe` whose stored metrics satisfy `rule`.
+pub fn run_screen(
+    rule: &Rule,
+    universe: &[(String, String)],
+    store: &HashMap<String, StoredMetrics>,
+) -> Vec<ScreenMatch> {
+    let fields = rule.fields();
+    universe
+        .iter()
+        .filter_map(|(symbol, description)| {
+            let metrics = &store.get(symbol)?.metric;
+            rule.matches(metrics).then(|| ScreenMatch {
+                symbol: symbol.to_owned(),
+                description: description.to_owned(),
+                values: fields.iter().map(|f| metrics.get(f).cloned()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Orders matches by the value in `column`, missing values last either way.
+pub fn sort_matches(matches: &mut [ScreenMatch], column: usize, descending: bool) {
+    matches.sort_by(|a, b| {
+        let value = |m: &ScreenMatch| {
+            m.values
+                .get(column)
+                .cloned()
+                .flatten()
+                .and_then(|v| v.as_f64())
+        };
+        match (value(a), value(b)) {
+            (Some(x), Some(y)) => {
+                let order = x.partial_cmp(&y).unwrap_or(Ordering::Equal);
+                if descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            }
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => a.symbol.cmp(&b.symbol),
+        }
+    });
+}
+
+#[server]
+async fn get_metrics(symbol: String, api_key: String) -> Result<Map<String, Value>, ServerFnError> {
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/stock/metric?symbol={}&metric=all&token={api_key}",
+        symbol
+    ))
+    .await?;
+    let financials: Value = serde_json::from_str(&body)?;
+    Ok(financials
+        .get("metric")
+        .and_then(|m| m.as_object())
+        .cloned()
+        .unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn metrics(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap_or_default()
+    }
+
+    #[test]
+    fn tokenizes_operators_words_and_numbers() {
+        assert_eq!(
+            tokenize("(pe<=15&&marketCap>2B)||NOT roe != 1e-3").unwrap(),
+            vec![
+                Token::Open,
+                Token::Field(String::from("pe")),
+                Token::Op(Op::Le),
+                Token::Number(15.0),
+                Token::And,
+                Token::Field(String::from("marketCap")),
+                Token::Op(Op::Gt),
+                Token::Number(2e9),
+                Token::Close,
+                Token::Or,
+                Token::Not,
+                Token::Field(String::from("roe")),
+                Token::Op(Op::Ne),
+                Token::Number(1e-3),
+            ]
+        );
+        assert!(tokenize("pe ! 3").is_err());
+        assert!(tokenize("pe > 3 & roe > 1").is_err());
+    }
+
+    #[test]
+    fn fields_may_contain_an_ampersand() {
+        let rule = Rule::parse("priceRelativeToS&P50052Week > 0.05&&pe < 20").unwrap();
+        assert_eq!(
+            rule.fields(),
+            vec![
+                String::from("priceRelativeToS&P50052Week"),
+                String::from("peTTM")
+            ]
+        );
+        // The provider reports percent; rules compare fractions.
+        let beating = metrics(json!({"priceRelativeToS&P50052Week": 12.0, "peTTM": 18.0}));
+        let lagging = metrics(json!({"priceRelativeToS&P50052Week": 3.0, "peTTM": 18.0}));
+        assert!(rule.matches(&beating));
+        assert!(!rule.matches(&lagging));
+    }
+
+    #[test]
+    fn suffixes_after_non_ascii_characters_are_not_numbers() {
+        assert_eq!(number("2B"), Some(2e9));
+        assert_eq!(number("5é"), None);
+        assert_eq!(number("é"), None);
+        assert!(Rule::parse("pe < 5é").is_err());
+        assert_eq!(
+            Rule::parse("prixé > 2k").unwrap().fields(),
+            vec![String::from("prixé")]
+        );
+    }
+
+    #[test]
+    fn store_drops_the_least_recently_fetched() {
+        let mut store = HashMap::new();
+        let now = Utc::now();
+        for (i, symbol) in ["AAPL", "MSFT", "BHP.AX"].into_iter().enumerate() {
+            let metrics = StoredMetrics {
+                fetched: now - Duration::minutes(10 - i as i64),
+                metric: Map::new(),
+            };
+            insert_bounded(&mut store, symbol.to_string(), metrics, 2);
+        }
+        let mut kept: Vec<_> = store.keys().cloned().collect();
+        kept.sort();
+        assert_eq!(kept, vec![String::from("BHP.AX"), String::from("MSFT")]);
+    }
+
+    #[test]
+    fn and_binds_tighter_than_or() {
+        let rule = Rule::parse("pe < 10 OR pe > 30 AND roe > 0.2").unwrap();
+        let Rule::Or(rules) = &rule else {
+            panic!("expected OR at the top, got {rule:?}");
+        };
+        assert!(matches!(rules[1], Rule::And(_)));
+        assert!(rule.matches(&metrics(json!({"peTTM": 8.0, "roeTTM": 10.0}))));
+        assert!(rule.matches(&metrics(json!({"peTTM": 40.0, "roeTTM": 25.0}))));
+        assert!(!rule.matches(&metrics(json!({"peTTM": 40.0, "roeTTM": 10.0}))));
+        // A missing metric fails its comparison rather than the whole rule.
+        assert!(!rule.matches(&metrics(json!({"roeTTM": 30.0}))));
+        assert!(rule.matches(&metrics(json!({"peTTM": 5.0}))));
+    }
+
+    #[test]
+    fn reports_incomplete_rules() {
+        for input in ["", "pe <", "(pe > 1", "pe > 1)", "pe > 1 AND", "> 3"] {
+            assert!(Rule::parse(input).is_err(), "{input:?} should not parse");
+        }
+    }
+}
@@ -0,0 +1,421 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::cmp::Reverse;
+use std::ops::Range;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SecurityType {
+    CommonStock,
+    Etf,
+    Adr,
+    Reit,
+    Other,
+}
+
+impl SecurityType {
+    pub const ALL: [SecurityType; 5] = [
+        SecurityType::CommonStock,
+        SecurityType::Etf,
+        SecurityType::Adr,
+        SecurityType::Reit,
+        SecurityType::Other,
+    ];
+
+    /// Maps the directory's `type` field, e.g. `Common Stock` or `ETP`.
+    pub fn from_provider(kind: &str) -> SecurityType {
+        match kind.to_uppercase().as_str() {
+            "COMMON STOCK" | "EQS" => SecurityType::CommonStock,
+            "ETP" | "ETF" => SecurityType::Etf,
+            "ADR" | "GDR" => SecurityType::Adr,
+            "REIT" => SecurityType::Reit,
+            _ => SecurityType::Other,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SecurityType::CommonStock => "Common stock",
+            SecurityType::Etf => "ETF",
+            SecurityType::Adr => "ADR",
+            SecurityType::Reit => "REIT",
+            SecurityType::Other => "Other",
+        }
+    }
+}
+
+/// One entry of an exchange's symbol directory.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Listing {
+    pub symbol: String,
+    pub display_symbol: String,
+    pub description: String,
+    pub currency: String,
+    pub kind: SecurityType,
+    pub figi: String,
+    pub share_class_figi: String,
+    pub isin: String,
+}
+
+impl Listing {
+    pub fn from_directory(entry: &Map<String, Value>) -> Option<Listing> {
+        let field = |key: &str| {
+            entry
+                .get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let symbol = field("symbol");
+        if symbol.is_empty() {
+            return None;
+        }
+        Some(Listing {
+            display_symbol: match field("displaySymbol") {
+                d if d.is_empty() => symbol.to_owned(),
+                d => d,
+            },
+            symbol,
+            description: field("description"),
+            currency: field("currency"),
+            kind: SecurityType::from_provider(&field("type")),
+            figi: field("figi"),
+            share_class_figi: field("shareClassFIGI"),
+            isin: field("isin"),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Filters {
+    pub kind: Option<SecurityType>,
+    pub currency: Option<String>,
+}
+
+impl Filters {
+    fn accepts(&self, listing: &Listing) -> bool {
+        self.kind.is_none_or(|k| k == listing.kind)
+            && self
+                .currency
+                .as_ref()
+                .is_none_or(|c| *c == listing.currency)
+    }
+}
+
+/// Where a listing matched and how well; spans are char ranges into the
+/// listing's display symbol and description.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub index: usize,
+    pub score: u32,
+    pub symbol_spans: Vec<Range<usize>>,
+    pub name_spans: Vec<Range<usize>>,
+}
+
+/// Lower-cased chars, one per source char so positions carry over to the
+/// original text.
+fn fold(text: &str) -> Vec<char> {
+    text.chars()
+        .map(|c| c.to_lowercase().next().unwrap_or(c))
+        .collect()
+}
+
+fn find(haystack: &[char], needle: &[char]) -> Option<usize> {
+    if needle.is_empty() || needle.len() > haystack.len() {
+        return None;
+    }
+    (0..=haystack.len() - needle.len()).find(|&i| haystack[i..i + needle.len()] == *needle)
+}
+
+fn span(range: Range<usize>) -> Vec<Range<usize>> {
+    vec![range]
+}
+
+fn is_word_start(text: &[char], i: usize) -> bool {
+    i == 0 || !text[i - 1].is_alphanumeric()
+}
+
+/// Matches `needle` as a subsequence of `haystack`, preferring word starts
+/// and runs of adjacent chars. Returns a score and the matched spans.
+fn fuzzy(haystack: &[char], needle: &[char]) -> Option<(u32, Vec<Range<usize>>)> {
+    let mut positions = Vec::with_capacity(needle.len());
+    let mut from = 0;
+    for c in needle.iter().filter(|c| !c.is_whitespace()) {
+        // Jump to the next word starting with `c` when there is one, so
+        // `intl bus mach` lands on the initials of each word.
+        let next = (from..haystack.len())
+            .find(|&i| {
+                haystack[i] == *c
+                    && (is_word_start(haystack, i)
+                        || positions.last() == Some(&(i.wrapping_sub(1))))
+            })
+            .or_else(|| (from..haystack.len()).find(|&i| haystack[i] == *c))?;
+        positions.push(next);
+        from = next + 1;
+    }
+    if positions.is_empty() {
+        return None;
+    }
+
+    let mut score = 0u32;
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    for (n, &i) in positions.iter().enumerate() {
+        let adjacent = n > 0 && positions[n - 1] + 1 == i;
+        score += 1 + 2 * u32::from(adjacent) + 3 * u32::from(is_word_start(haystack, i));
+        match spans.last_mut() {
+            Some(span) if span.end == i => span.end = i + 1,
+            _ => spans.push(i..i + 1),
+        }
+    }
+    // Scattered matches are noise; require most chars to sit in runs or on
+    // word starts.
+    (score as usize >= positions.len() * 3).then_some((score, spans))
+}
+
+/// A directory indexed for search, built once per load.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SearchIndex {
+    pub listings: Vec<Listing>,
+    symbols: Vec<Vec<char>>,
+    names: Vec<Vec<char>>,
+}
+
+impl SearchIndex {
+    pub fn build(directory: &[Map<String, Value>]) -> SearchIndex {
+        let mut listings: Vec<Listing> = directory
+            .iter()
+            .filter_map(Listing::from_directory)
+            .collect();
+        listings.sort_by(|a, b| a.display_symbol.cmp(&b.display_symbol));
+        listings.dedup_by(|a, b| a.symbol == b.symbol);
+        SearchIndex {
+            symbols: listings.iter().map(|l| fold(&l.display_symbol)).collect(),
+            names: listings.iter().map(|l| fold(&l.description)).collect(),
+            listings,
+        }
+    }
+
+    /// Currencies present in the directory, for the filter choices.
+    pub fn currencies(&self) -> Vec<String> {
+        let mut currencies: Vec<String> = self
+            .listings
+            .iter()
+            .map(|l| l.currency.to_owned())
+            .filter(|c| !c.is_empty())
+            .collect();
+        currencies.sort();
+        currencies.dedup();
+        currencies
+    }
+
+    /// Listings matching `query`, best first: exact ticker, ISIN or FIGI,
+    /// then ticker prefixes, then name prefixes, substrings and fuzzy
+    /// matches. An empty query lists everything passing `filters`.
+    pub fn search(&self, query: &str, filters: &Filters) -> Vec<SearchHit> {
+        let query = fold(query.trim());
+        let mut hits: Vec<SearchHit> = self
+            .listings
+            .iter()
+            .enumerate()
+            .filter(|(_, listing)| filters.accepts(listing))
+            .filter_map(|(index, listing)| {
+                if query.is_empty() {
+                    return Some(SearchHit {
+                        index,
+                        score: 0,
+                        symbol_spans: Vec::new(),
+                        name_spans: Vec::new(),
+                    });
+                }
+                self.score(index, listing, &query)
+            })
+            .collect();
+        // Stable, so equal scores keep ticker order.
+        hits.sort_by_key(|hit| Reverse(hit.score));
+        hits
+    }
+
+    fn score(&self, index: usize, listing: &Listing, query: &[char]) -> Option<SearchHit> {
+        let symbol = &self.symbols[index];
+        let name = &self.names[index];
+        let hit = |score: u32, symbol_spans: Vec<Range<usize>>, name_spans: Vec<Range<usize>>| {
+            Some(SearchHit {
+                index,
+                score,
+                symbol_spans,
+                name_spans,
+            })
+        };
+        let whole_symbol = span(0..symbol.len());
+
+        if symbol == query || fold(&listing.symbol) == query {
+            return hit(10_000, whole_symbol, Vec::new());
+        }
+        let code: String = query.iter().collect::<String>().to_uppercase();
+        if code.len() >= 12
+            && [&listing.isin, &listing.figi, &listing.share_class_figi].contains(&&code)
+        {
+            return hit(9_000, Vec::new(), Vec::new());
+        }
+        if symbol.starts_with(query) {
+            // Shorter tickers first: `A` before `AA` before `AAPL`.
+            return hit(
+                8_000 - symbol.len() as u32,
+                span(0..query.len()),
+                Vec::new(),
+            );
+        }
+        if name.starts_with(query) {
+            return hit(6_000, Vec::new(), span(0..query.len()));
+        }
+        if let Some(i) = find(name, query) {
+            let base = if is_word_start(name, i) { 5_000 } else { 4_000 };
+            return hit(
+                base - i.min(999) as u32,
+                Vec::new(),
+                span(i..i + query.len()),
+            );
+        }
+        if let Some(i) = find(symbol, query) {
+            return hit(3_500, span(i..i + query.len()), Vec::new());
+        }
+        if let Some((score, spans)) = fuzzy(name, query) {
+            return hit(1_000 + score, Vec::new(), spans);
+        }
+        None
+    }
+}
+
+/// Splits `text` into `(segment, highlighted)` runs for rendering.
+pub fn highlight(text: &str, spans: &[Range<usize>]) -> Vec<(String, bool)> {
+    let mut segments: Vec<(String, bool)> = Vec::new();
+    for (i, c) in text.chars().enumerate() {
+        let marked = spans.iter().any(|s| s.contains(&i));
+        match segments.last_mut() {
+            Some((segment, m)) if *m == marked => segment.push(c),
+            _ => segments.push((c.to_string(), marked)),
+        }
+    }
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn index(listings: &[(&str, &str)]) -> SearchIndex {
+        let directory: Vec<Map<String, Value>> = listings
+            .iter()
+            .map(|(symbol, description)| {
+                let mut entry = json!({
+                    "symbol": symbol,
+                    "description": description,
+                    "currency": "USD",
+                    "type": "Common Stock",
+                });
+                if *symbol == "AAPL" {
+                    entry["isin"] = json!("US0378331005");
+                    entry["figi"] = json!("BBG000B9XRY4");
+                }
+                entry.as_object().cloned().unwrap()
+            })
+            .collect();
+        SearchIndex::build(&directory)
+    }
+
+    fn symbols(index: &SearchIndex, query: &str) -> Vec<String> {
+        index
+            .search(query, &Filters::default())
+            .iter()
+            .map(|hit| index.listings[hit.index].symbol.to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn tiers_rank_exact_then_prefixes_then_substrings_then_fuzzy() {
+        let index = index(&[
+            ("ZZZ", "Alpha Prime Partners"),
+            ("SNAP", "Snappy Corp"),
+            ("MAPP", "Map Holdings"),
+            ("XYZ", "Big App Co"),
+            ("AAPL", "Apple Inc"),
+            ("APPS", "Digital Turbine"),
+            ("APP", "AppLovin Corp"),
+            ("AA", "Alcoa Corp"),
+        ]);
+        assert_eq!(
+            symbols(&index, "app"),
+            ["APP", "APPS", "AAPL", "XYZ", "SNAP", "MAPP", "ZZZ"]
+        );
+    }
+
+    #[test]
+    fn shorter_tickers_come_first_among_prefixes() {
+        let index = index(&[
+            ("AAPL", "Apple Inc"),
+            ("AA", "Alcoa Corp"),
+            ("A", "Agilent"),
+        ]);
+        assert_eq!(symbols(&index, "a"), ["A", "AA", "AAPL"]);
+    }
+
+    #[test]
+    fn codes_rank_above_names_mentioning_them() {
+        let index = index(&[
+            ("AAPL", "Apple Inc"),
+            ("TRAK", "Tracker of US0378331005"),
+            ("BBG", "BBG000B9XRY4 Basket"),
+        ]);
+        assert_eq!(symbols(&index, "us0378331005"), ["AAPL", "TRAK"]);
+        assert_eq!(symbols(&index, "BBG000B9XRY4"), ["AAPL", "BBG"]);
+    }
+
+    #[test]
+    fn highlights_non_ascii_names_by_char() {
+        let index = index(&[
+            ("GLE.PA", "Société Générale"),
+            ("KCHOL.IS", "İstanbul Holding"),
+        ]);
+        let hits = index.search("gén", &Filters::default());
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].name_spans, span(8..11));
+        assert_eq!(
+            highlight("Société Générale", &hits[0].name_spans),
+            [
+                (String::from("Société "), false),
+                (String::from("Gén"), true),
+                (String::from("érale"), false),
+            ]
+        );
+
+        // `İ` lower-cases to two chars; spans still count it as one.
+        let hits = index.search("ist", &Filters::default());
+        assert_eq!(hits[0].name_spans, span(0..3));
+        assert_eq!(
+            highlight("İstanbul Holding", &hits[0].name_spans),
+            [
+                (String::from("İst"), true),
+                (String::from("anbul Holding"), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn highlights_fuzzy_matches_in_runs() {
+        let name = "Nestlé Société Anonyme";
+        let index = index(&[("NESN.SW", name)]);
+        let hits = index.search("nsa", &Filters::default());
+        assert_eq!(hits[0].name_spans, [0..1, 7..8, 15..16]);
+        assert_eq!(
+            highlight(name, &hits[0].name_spans),
+            [
+                (String::from("N"), true),
+                (String::from("estlé "), false),
+                (String::from("S"), true),
+                (String::from("ociété "), false),
+                (String::from("A"), true),
+                (String::from("nonyme"), false),
+            ]
+        );
+    }
+}
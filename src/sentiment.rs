@@ -0,0 +1,123 @@
+use crate::news::Article;
+use chrono::NaiveDate;
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::OnceLock;
+
+/// Scores a piece of text from -1 (negative) to 1 (positive). Implement this
+/// to plug in a different model.
+pub trait SentimentScorer {
+    fn name(&self) -> &'static str;
+    fn score(&self, text: &str) -> f64;
+}
+
+/// Word-list scorer with negation and intensifier handling. Runs locally with
+/// no external service.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct LexiconScorer {
+    /// Word weights, positive or negative.
+    pub words: HashMap<String, f64>,
+    /// Words that flip the sentiment of the next few words.
+    pub negations: HashSet<String>,
+    /// Multipliers for the word that follows.
+    pub intensifiers: HashMap<String, f64>,
+}
+
+/// How many words after a negation it still applies to.
+const NEGATION_SCOPE: usize = 3;
+/// Normalisation constant: a headline needs a raw score around this before
+/// it approaches the ends of the scale.
+const ALPHA: f64 = 4.0;
+
+impl LexiconScorer {
+    pub fn bundled() -> Self {
+        serde_json::from_str(include_str!("../assets/sentiment_lexicon.json"))
+            .expect("bundled sentiment lexicon is valid")
+    }
+}
+
+impl SentimentScorer for LexiconScorer {
+    fn name(&self) -> &'static str {
+        "Lexicon"
+    }
+
+    fn score(&self, text: &str) -> f64 {
+        let tokens: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric() && c != '\'')
+            .filter(|t| !t.is_empty())
+            .map(|t| t.to_lowercase())
+            .collect();
+
+        let mut raw = 0.0;
+        let mut negated_until = 0;
+        let mut boost = 1.0;
+        for (i, token) in tokens.iter().enumerate() {
+            if self.negations.contains(token) || token.ends_with("n't") {
+                negated_until = i + NEGATION_SCOPE + 1;
+                continue;
+            }
+            if let Some(m) = self.intensifiers.get(token) {
+                boost = *m;
+                continue;
+            }
+            if let Some(weight) = self.words.get(token) {
+                let sign = if i < negated_until { -1.0 } else { 1.0 };
+                raw += sign * boost * weight;
+            }
+            boost = 1.0;
+        }
+
+        raw / (raw * raw + ALPHA * ALPHA).sqrt()
+    }
+}
+
+/// The scorer used when none is chosen.
+pub fn default_scorer() -> &'static LexiconScorer {
+    static SCORER: OnceLock<LexiconScorer> = OnceLock::new();
+    SCORER.get_or_init(LexiconScorer::bundled)
+}
+
+/// Headline weighted double against the summary, which is often boilerplate.
+pub fn score_article(scorer: &dyn SentimentScorer, article: &Article) -> f64 {
+    let headline = scorer.score(&article.headline);
+    if article.summary.is_empty() {
+        return headline;
+    }
+    (2.0 * headline + scorer.score(&article.summary)) / 3.0
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DailySentiment {
+    /// Exchange-local date the articles were published on.
+    pub date: NaiveDate,
+    pub mean: f64,
+    pub count: usize,
+}
+
+/// Mean article sentiment per exchange-local day, oldest first.
+pub fn daily_sentiment(
+    scorer: &dyn SentimentScorer,
+    articles: &[Article],
+    tz: Tz,
+) -> Vec<DailySentiment> {
+    let mut days: BTreeMap<NaiveDate, (f64, usize)> = BTreeMap::new();
+    for article in articles {
+        let Some(published) = article.published() else {
+            continue;
+        };
+        let day = days
+            .entry(published.with_timezone(&tz).date_naive())
+            .or_insert((0.0, 0));
+        day.0 += score_article(scorer, article);
+        day.1 += 1;
+    }
+
+    days.into_iter()
+        .map(|(date, (sum, count))| DailySentiment {
+            date,
+            mean: sum / count as f64,
+            count,
+        })
+        .collect()
+}
@@ -0,0 +1,346 @@
+use crate::store::APP_STATE;
+use chrono::{
+    DateTime, Datelike, Duration, Local, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeZone,
+    Timelike, Utc,
+};
+use chrono_tz::Tz;
+use dioxus::prelude::*;
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Whether chart times are shown in the exchange's time zone or the viewer's.
+pub static TIME_DISPLAY: GlobalMemo<TimeDisplay> =
+    Memo::global(|| APP_STATE.read().settings.time_display);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeDisplay {
+    Exchange,
+    Local,
+}
+
+impl TimeDisplay {
+    pub fn label(&self) -> &'static str {
+        match self {
+            TimeDisplay::Exchange => "Exchange time",
+            TimeDisplay::Local => "Local time",
+        }
+    }
+
+    pub fn toggled(&self) -> TimeDisplay {
+        match self {
+            TimeDisplay::Exchange => TimeDisplay::Local,
+            TimeDisplay::Local => TimeDisplay::Exchange,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+pub struct DataPoint {
+    #[serde(deserialize_with = "str_to_datetime")]
+    pub period: DateTime<Utc>,
+    pub v: f64,
+}
+
+impl Eq for DataPoint {}
+
+impl Ord for DataPoint {
+    fn cmp(&self, other: &DataPoint) -> Ordering {
+        self.period.cmp(&other.period)
+    }
+}
+
+impl PartialOrd for DataPoint {
+    fn partial_cmp(&self, other: &DataPoint) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Parses the period formats providers use: RFC 3339 timestamps, naive
+/// `YYYY-MM-DD HH:MM:SS` (taken as UTC) and plain dates (midnight UTC).
+pub fn parse_period(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(value) {
+        return Some(t.with_timezone(&Utc));
+    }
+    if let Ok(t) = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        return Some(t.and_utc());
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|d| d.and_time(NaiveTime::MIN).and_utc())
+}
+
+fn str_to_datetime<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct Struct(PhantomData<fn() -> DateTime<Utc>>);
+
+    impl<'de> Visitor<'de> for Struct {
+        type Value = DateTime<Utc>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a date, timestamp or unix time")
+        }
+
+        fn visit_str<E>(self, value: &str) -> Result<DateTime<Utc>, E>
+        where
+            E: de::Error,
+        {
+            parse_period(value).ok_or_else(|| E::custom(format!("invalid period `{value}`")))
+        }
+
+        fn visit_i64<E>(self, value: i64) -> Result<DateTime<Utc>, E>
+        where
+            E: de::Error,
+        {
+            Utc.timestamp_opt(value, 0)
+                .single()
+                .ok_or_else(|| E::custom(format!("invalid unix time {value}")))
+        }
+
+        fn visit_u64<E>(self, value: u64) -> Result<DateTime<Utc>, E>
+        where
+            E: de::Error,
+        {
+            self.visit_i64(value as i64)
+        }
+    }
+
+    deserializer.deserialize_any(Struct(PhantomData))
+}
+
+impl From<DataPoint> for String {
+    fn from(value: DataPoint) -> Self {
+        value.period.to_rfc3339()
+    }
+}
+
+/// Step between x-axis ticks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickUnit {
+    Minutes(u32),
+    Hours(u32),
+    Days(u32),
+    Months(u32),
+    Years(u32),
+}
+
+impl TickUnit {
+    const CANDIDATES: [TickUnit; 24] = [
+        TickUnit::Minutes(1),
+        TickUnit::Minutes(2),
+        TickUnit::Minutes(5),
+        TickUnit::Minutes(10),
+        TickUnit::Minutes(15),
+        TickUnit::Minutes(30),
+        TickUnit::Hours(1),
+        TickUnit::Hours(2),
+        TickUnit::Hours(3),
+        TickUnit::Hours(6),
+        TickUnit::Hours(12),
+        TickUnit::Days(1),
+        TickUnit::Days(2),
+        TickUnit::Days(7),
+        TickUnit::Days(14),
+        TickUnit::Months(1),
+        TickUnit::Months(3),
+        TickUnit::Months(6),
+        TickUnit::Years(1),
+        TickUnit::Years(2),
+        TickUnit::Years(5),
+        TickUnit::Years(10),
+        TickUnit::Years(25),
+        TickUnit::Years(50),
+    ];
+
+    /// The finest unit that puts at most `max_ticks` ticks across `span`.
+    pub fn for_span(span: Duration, max_ticks: usize) -> TickUnit {
+        TickUnit::CANDIDATES
+            .into_iter()
+            .find(|u| span.num_seconds() / u.approx().num_seconds().max(1) < max_ticks as i64)
+            .unwrap_or(TickUnit::Years(100))
+    }
+
+    fn approx(&self) -> Duration {
+        match *self {
+            TickUnit::Minutes(n) => Duration::minutes(n as i64),
+            TickUnit::Hours(n) => Duration::hours(n as i64),
+            TickUnit::Days(n) => Duration::days(n as i64),
+            TickUnit::Months(n) => Duration::days(30 * n as i64),
+            TickUnit::Years(n) => Duration::days(365 * n as i64),
+        }
+    }
+
+    pub fn format(&self) -> &'static str {
+        match self {
+            TickUnit::Minutes(_) | TickUnit::Hours(_) => "%H:%M",
+            TickUnit::Days(_) => "%b %d",
+            TickUnit::Months(_) => "%b %Y",
+            TickUnit::Years(_) => "%Y",
+        }
+    }
+
+    /// The last unit boundary at or before `t`.
+    fn floor(&self, t: NaiveDateTime) -> NaiveDateTime {
+        let date = t.date();
+        match *self {
+            TickUnit::Minutes(n) => date
+                .and_hms_opt(t.hour(), t.minute() - t.minute() % n, 0)
+                .unwrap_or(t),
+            TickUnit::Hours(n) => date.and_hms_opt(t.hour() - t.hour() % n, 0, 0).unwrap_or(t),
+            TickUnit::Days(_) => date.and_time(NaiveTime::MIN),
+            TickUnit::Months(n) => {
+                let month0 = date.month0() - date.month0() % n;
+                NaiveDate::from_ymd_opt(date.year(), month0 + 1, 1)
+                    .unwrap_or(date)
+                    .and_time(NaiveTime::MIN)
+            }
+            TickUnit::Years(n) => {
+                NaiveDate::from_ymd_opt(date.year() - date.year().rem_euclid(n as i32), 1, 1)
+                    .unwrap_or(date)
+                    .and_time(NaiveTime::MIN)
+            }
+        }
+    }
+
+    fn step(&self, t: NaiveDateTime) -> Option<NaiveDateTime> {
+        match *self {
+            TickUnit::Minutes(n) => Some(t + Duration::minutes(n as i64)),
+            TickUnit::Hours(n) => Some(t + Duration::hours(n as i64)),
+            TickUnit::Days(n) => Some(t + Duration::days(n as i64)),
+            TickUnit::Months(n) => t.checked_add_months(Months::new(n)),
+            TickUnit::Years(n) => t.checked_add_months(Months::new(12 * n)),
+        }
+    }
+
+    /// Tick instants in `[from, to]`, on unit boundaries of the wall clock in
+    /// `tz`, each with its label.
+    pub fn ticks<Z>(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        tz: &Z,
+    ) -> Vec<(DateTime<Utc>, String)>
+    where
+        Z: TimeZone,
+        Z::Offset: fmt::Display,
+    {
+        let mut ticks = Vec::new();
+        let mut local = self.floor(from.with_timezone(tz).naive_local());
+        for _ in 0..500 {
+            // Boundaries inside a DST gap have no instant and are skipped.
+            if let Some(t) = tz.from_local_datetime(&local).earliest() {
+                let utc = t.with_timezone(&Utc);
+                if utc > to {
+                    break;
+                }
+                if utc >= from {
+                    ticks.push((utc, t.format(self.format()).to_string()));
+                }
+            }
+            match self.step(local) {
+                Some(next) => local = next,
+                None => break,
+            }
+        }
+        ticks
+    }
+}
+
+/// Labelled x-axis ticks for `[from, to]`, in the exchange zone `tz` or the
+/// viewer's local zone.
+pub fn axis_ticks(
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    max_ticks: usize,
+    tz: Tz,
+    display: TimeDisplay,
+) -> Vec<(DateTime<Utc>, String)> {
+    let unit = TickUnit::for_span(to - from, max_ticks);
+    match display {
+        TimeDisplay::Exchange => unit.ticks(from, to, &tz),
+        TimeDisplay::Local => unit.ticks(from, to, &Local),
+    }
+}
+
+/// Spans the chart can be narrowed to, ending at the newest point.
+pub const RANGES: [&str; 6] = ["1M", "3M", "6M", "1Y", "5Y", "MAX"];
+
+pub fn range_label(range: &str) -> &'static str {
+    match range {
+        "1M" => "1 month",
+        "3M" => "3 months",
+        "6M" => "6 months",
+        "1Y" => "1 year",
+        "5Y" => "5 years",
+        _ => "All",
+    }
+}
+
+/// The earliest time shown for `range` when the newest point is at `last`,
+/// or `None` to show everything.
+pub fn range_start(range: &str, last: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let months = match range {
+        "1M" => 1,
+        "3M" => 3,
+        "6M" => 6,
+        "1Y" => 12,
+        "5Y" => 60,
+        _ => return None,
+    };
+    last.checked_sub_months(Months::new(months))
+}
+
+/// Overlays drawn over the price series.
+pub const INDICATORS: [&str; 3] = ["sma20", "sma50", "ema20"];
+
+pub fn indicator_label(indicator: &str) -> &'static str {
+    match indicator {
+        "sma20" => "SMA 20",
+        "sma50" => "SMA 50",
+        "ema20" => "EMA 20",
+        _ => "",
+    }
+}
+
+/// `indicator` over `points`, which are in time order. Moving averages start
+/// once their window is full.
+pub fn indicator(indicator: &str, points: &[DataPoint]) -> Vec<DataPoint> {
+    match indicator {
+        "sma20" => simple_moving_average(points, 20),
+        "sma50" => simple_moving_average(points, 50),
+        "ema20" => exponential_moving_average(points, 20),
+        _ => Vec::new(),
+    }
+}
+
+fn simple_moving_average(points: &[DataPoint], window: usize) -> Vec<DataPoint> {
+    points
+        .windows(window)
+        .map(|w| DataPoint {
+            period: w[window - 1].period,
+            v: w.iter().map(|dp| dp.v).sum::<f64>() / window as f64,
+        })
+        .collect()
+}
+
+/// Seeded with the simple average of the first window.
+fn exponential_moving_average(points: &[DataPoint], window: usize) -> Vec<DataPoint> {
+    let Some(seed) = simple_moving_average(points, window).into_iter().next() else {
+        return Vec::new();
+    };
+    let alpha = 2.0 / (window as f64 + 1.0);
+    let mut average = seed.v;
+    let mut out = vec![seed];
+    for dp in &points[window..] {
+        average += alpha * (dp.v - average);
+        out.push(DataPoint {
+            period: dp.period,
+            v: average,
+        });
+    }
+    out
+}
@@ -0,0 +1,593 @@
+use super::cache::Cache;
+use super::config::ServerConfig;
+use super::metrics::{LimitStats, METRICS};
+use crate::bars::{get_candles, resolution_lookback, Bar, RESOLUTIONS};
+use crate::components::fetch_symbols;
+use crate::currency::Currency;
+use crate::diagnostics::RATE_LIMITED;
+use crate::exchange::Exchange;
+use crate::quotes::get_stock_quote;
+use crate::search::Listing;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing;
+use dioxus::prelude::ServerFnError;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Names Finnhub in logs and metrics.
+const FINNHUB: &str = "finnhub";
+
+/// Set when the server starts, so the app's server functions share the
+/// API's caches and rate limit.
+static SHARED: OnceLock<Arc<ApiState>> = OnceLock::new();
+
+/// The data layer behind both the REST API and the app's server functions:
+/// the provider settings, a cache for each kind of response and the rate
+/// limit.
+pub struct ApiState {
+    pub config: ServerConfig,
+    quotes: Cache<String>,
+    series: Cache<String>,
+    symbols: Cache<String>,
+    throttle: Throttle,
+}
+
+impl ApiState {
+    pub fn new(config: ServerConfig) -> ApiState {
+        let dir = config.storage.data_dir.join("cache");
+        ApiState {
+            quotes: Cache::new("quotes", config.cache.quotes, &dir),
+            series: Cache::new("series", config.cache.series, &dir),
+            symbols: Cache::new("symbols", config.cache.symbols, &dir),
+            throttle: Throttle::new(config.providers.finnhub.requests_per_minute),
+            config,
+        }
+    }
+
+    /// Loads the responses cached by an earlier run.
+    pub async fn restore(&self) {
+        self.quotes.restore().await;
+        self.series.restore().await;
+        self.symbols.restore().await;
+    }
+
+    /// Routes every server function's provider calls through this state.
+    pub fn share(self: &Arc<Self>) {
+        let _ = SHARED.set(Arc::clone(self));
+    }
+
+    /// The configured API key, which the API uses for its own calls.
+    fn api_key(&self) -> Result<String, ApiError> {
+        self.config
+            .providers
+            .finnhub
+            .api_key()
+            .ok_or_else(|| ApiError::Unavailable(String::from("no Finnhub API key")))
+    }
+
+    fn cache(&self, endpoint: &str) -> Option<&Cache<String>> {
+        match endpoint {
+            "quote" => Some(&self.quotes),
+            "stock/candle" => Some(&self.series),
+            "stock/symbol" => Some(&self.symbols),
+            _ => None,
+        }
+    }
+
+    /// The body of a provider response. Quotes, candles and symbol
+    /// directories come from their caches while fresh. Every other Finnhub
+    /// call counts against the rate limit.
+    async fn get(&self, url: &str) -> Result<String, ServerFnError> {
+        let call = ProviderCall::parse(url)?;
+        let cache = self.cache(&call.endpoint);
+        if let Some(body) = cache.and_then(|c| c.get(&call.key)) {
+            return Ok(body);
+        }
+        if call.provider == FINNHUB {
+            self.throttle.acquire().map_err(|retry_after| {
+                tracing::warn!(provider = FINNHUB, retry_after, "rate limit reached");
+                METRICS.throttled();
+                ServerFnError::new(RATE_LIMITED)
+            })?;
+        }
+        let (status, body) = self.fetch(&call, url).await?;
+        // Error bodies are passed on for the caller to report, not kept.
+        if let Some(cache) = cache.filter(|_| status.is_success()) {
+            cache.insert(&call.key, body.clone()).await;
+        }
+        Ok(body)
+    }
+
+    /// Awaits one upstream call, logging and measuring its latency.
+    async fn fetch(
+        &self,
+        call: &ProviderCall,
+        url: &str,
+    ) -> Result<(StatusCode, String), ServerFnError> {
+        let _in_flight = METRICS.start_upstream();
+        let started = Instant::now();
+        let result = request(url).await;
+        let elapsed = started.elapsed().as_secs_f64();
+        let ok = matches!(&result, Ok((status, _)) if status.is_success());
+        METRICS.observe_upstream(call.provider, &call.endpoint, elapsed, ok);
+        let latency_ms = elapsed * 1000.0;
+        let (provider, endpoint) = (call.provider, call.endpoint.as_str());
+        match &result {
+            Ok((status, _)) => {
+                let status = status.as_u16();
+                tracing::info!(provider, endpoint, status, latency_ms, "upstream call")
+            }
+            Err(e) => {
+                tracing::warn!(provider, endpoint, latency_ms, error = %e, "upstream call failed")
+            }
+        }
+        result
+    }
+
+    /// The API's answer when a data-layer call failed.
+    fn failed(&self, e: ServerFnError) -> ApiError {
+        let message = match e {
+            ServerFnError::ServerError(message) => message,
+            e => e.to_string(),
+        };
+        if message == RATE_LIMITED {
+            ApiError::Throttled(self.throttle.retry_after())
+        } else {
+            ApiError::Upstream(message)
+        }
+    }
+
+    /// Checks that a file can be written under the data directory.
+    async fn storage_writable(&self) -> bool {
+        let dir = &self.config.storage.data_dir;
+        let probe = dir.join(".ready");
+        let ok = tokio::fs::create_dir_all(dir).await.is_ok()
+            && tokio::fs::write(&probe, b"").await.is_ok();
+        let _ = tokio::fs::remove_file(probe).await;
+        ok
+    }
+}
+
+/// The body of a provider response, through the shared data layer once the
+/// server has started. Fails with [`RATE_LIMITED`] over either the server's
+/// limit or the provider's.
+pub async fn provider_get(url: &str) -> Result<String, ServerFnError> {
+    match SHARED.get() {
+        Some(state) => state.get(url).await,
+        None => Ok(request(url).await?.1),
+    }
+}
+
+/// One request to a provider. Errors leave out the URL, since it carries
+/// the API key.
+async fn request(url: &str) -> Result<(StatusCode, String), ServerFnError> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(reqwest::Error::without_url)?;
+    let status = response.status();
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        return Err(ServerFnError::new(RATE_LIMITED));
+    }
+    let body = response.text().await.map_err(reqwest::Error::without_url)?;
+    Ok((status, body))
+}
+
+/// Who a provider URL calls, and its cache key: the endpoint and query
+/// without the API key, so every client's calls share entries.
+struct ProviderCall {
+    provider: &'static str,
+    endpoint: String,
+    key: String,
+}
+
+impl ProviderCall {
+    fn parse(url: &str) -> Result<ProviderCall, ServerFnError> {
+        let url = reqwest::Url::parse(url)?;
+        let provider = match url.host_str() {
+            Some("api.frankfurter.app") => "frankfurter",
+            _ => FINNHUB,
+        };
+        let endpoint = url
+            .path()
+            .trim_start_matches("/api/v1")
+            .trim_matches('/')
+            .to_string();
+        let query: Vec<String> = url
+            .query_pairs()
+            .filter(|(name, _)| name != "token")
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        Ok(ProviderCall {
+            provider,
+            key: format!("{endpoint}?{}", query.join("&")),
+            endpoint,
+        })
+    }
+}
+
+/// A fixed one-minute window of upstream calls.
+struct Throttle {
+    per_minute: u32,
+    window: Mutex<(Instant, u32)>,
+}
+
+impl Throttle {
+    fn new(per_minute: u32) -> Throttle {
+        Throttle {
+            per_minute,
+            window: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Counts a call, or returns the seconds until the next window opens.
+    fn acquire(&self) -> Result<(), u64> {
+        let mut window = self.window.lock().unwrap();
+        let elapsed = window.0.elapsed();
+        if elapsed >= Duration::from_secs(60) {
+            *window = (Instant::now(), 0);
+        } else if window.1 >= self.per_minute {
+            return Err(60 - elapsed.as_secs());
+        }
+        window.1 += 1;
+        Ok(())
+    }
+
+    /// Seconds until the current window closes, at least one.
+    fn retry_after(&self) -> u64 {
+        let window = self.window.lock().unwrap();
+        60u64.saturating_sub(window.0.elapsed().as_secs()).max(1)
+    }
+
+    /// Calls counted in the current window.
+    fn used(&self) -> u32 {
+        let window = self.window.lock().unwrap();
+        if window.0.elapsed() >= Duration::from_secs(60) {
+            0
+        } else {
+            window.1
+        }
+    }
+}
+
+pub enum ApiError {
+    BadRequest(String),
+    NotFound(String),
+    Unavailable(String),
+    /// Over the provider rate limit; retry after this many seconds.
+    Throttled(u64),
+    Upstream(String),
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let (status, message) = match self {
+            ApiError::BadRequest(message) => (StatusCode::BAD_REQUEST, message),
+            ApiError::NotFound(message) => (StatusCode::NOT_FOUND, message),
+            ApiError::Unavailable(message) => (StatusCode::SERVICE_UNAVAILABLE, message),
+            ApiError::Throttled(secs) => {
+                let body = Json(serde_json::json!({ "error": RATE_LIMITED }));
+                return (
+                    StatusCode::TOO_MANY_REQUESTS,
+                    [(header::RETRY_AFTER, secs.to_string())],
+                    body,
+                )
+                    .into_response();
+            }
+            ApiError::Upstream(message) => (StatusCode::BAD_GATEWAY, message),
+        };
+        (status, Json(serde_json::json!({ "error": message }))).into_response()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QuoteResponse {
+    pub symbol: String,
+    /// The exchange's country code, as in `/api/v1/symbols/:exchange`.
+    pub exchange: String,
+    pub currency: Currency,
+    pub current: f64,
+    pub change: Option<f64>,
+    pub change_percent: Option<f64>,
+    pub high: f64,
+    pub low: f64,
+    pub open: f64,
+    pub previous_close: f64,
+    pub time: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SeriesResponse {
+    pub symbol: String,
+    pub resolution: String,
+    pub from: i64,
+    pub to: i64,
+    pub bars: Vec<Bar>,
+}
+
+#[derive(Deserialize, Debug)]
+struct SeriesQuery {
+    resolution: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Readiness {
+    ready: bool,
+    api_key: bool,
+    storage: bool,
+    cached: Cached,
+}
+
+#[derive(Serialize, Debug)]
+struct Cached {
+    quotes: usize,
+    series: usize,
+    symbols: usize,
+}
+
+pub fn routes() -> Router<Arc<ApiState>> {
+    Router::new()
+        .route("/quote/:symbol", get(quote))
+        .route("/series/:symbol", get(series))
+        .route("/symbols/:exchange", get(symbols))
+}
+
+/// Liveness: the process is up and serving.
+pub async fn healthz() -> &'static str {
+    "ok"
+}
+
+/// Readiness: the provider key is set and the cache can be written.
+pub async fn readyz(State(state): State<Arc<ApiState>>) -> (StatusCode, Json<Readiness>) {
+    let api_key = state.config.providers.finnhub.api_key().is_some();
+    let storage = state.storage_writable().await;
+    let ready = api_key && storage;
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let cached = Cached {
+        quotes: state.quotes.len(),
+        series: state.series.len(),
+        symbols: state.symbols.len(),
+    };
+    (
+        status,
+        Json(Readiness {
+            ready,
+            api_key,
+            storage,
+            cached,
+        }),
+    )
+}
+
+/// Prometheus metrics: upstream calls, caches and the rate limit.
+pub async fn metrics(State(state): State<Arc<ApiState>>) -> impl IntoResponse {
+    let caches = [
+        state.quotes.stats(),
+        state.series.stats(),
+        state.symbols.stats(),
+    ];
+    let limit = LimitStats {
+        per_minute: state.throttle.per_minute,
+        used: state.throttle.used(),
+    };
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        METRICS.render(&caches, limit),
+    )
+}
+
+async fn quote(
+    State(state): State<Arc<ApiState>>,
+    Path(symbol): Path<String>,
+) -> Result<Json<QuoteResponse>, ApiError> {
+    let symbol = symbol.to_uppercase();
+    let api_key = state.api_key()?;
+    let quote = get_stock_quote(symbol.clone(), api_key)
+        .await
+        .map_err(|e| state.failed(e))?;
+    // The provider answers unknown symbols with an all-zero quote.
+    if quote.timestamp == 0 {
+        return Err(ApiError::NotFound(format!("no quote for {symbol}")));
+    }
+    let exchange = Exchange::from_symbol(&symbol);
+    let response = QuoteResponse {
+        exchange: exchange.country().to_string(),
+        currency: exchange.currency(),
+        current: quote.current,
+        change: quote.change,
+        change_percent: quote.change_percent,
+        high: quote.high,
+        low: quote.low,
+        open: quote.open,
+        previous_close: quote.previous_close,
+        time: DateTime::from_timestamp(quote.timestamp, 0).unwrap_or_default(),
+        symbol,
+    };
+    Ok(Json(response))
+}
+
+/// Bars for `?resolution=` (default `D`) between the unix times `?from=`
+/// and `?to=`. Without `to` the series ends at the start of the current
+/// cache period, so repeated open-ended requests share an entry; without
+/// `from` it covers the chart's usual lookback.
+async fn series(
+    State(state): State<Arc<ApiState>>,
+    Path(symbol): Path<String>,
+    Query(query): Query<SeriesQuery>,
+) -> Result<Json<SeriesResponse>, ApiError> {
+    let symbol = symbol.to_uppercase();
+    let resolution = query.resolution.unwrap_or_else(|| String::from("D"));
+    if !RESOLUTIONS.contains(&resolution.as_str()) {
+        return Err(ApiError::BadRequest(format!(
+            "resolution must be one of {}",
+            RESOLUTIONS.join(", ")
+        )));
+    }
+    let to = query.to.unwrap_or_else(|| {
+        let now = Utc::now().timestamp();
+        let period = state.config.cache.series.ttl_secs.max(1) as i64;
+        now - now % period
+    });
+    let from = query
+        .from
+        .unwrap_or_else(|| to - resolution_lookback(&resolution).num_seconds());
+    if from >= to {
+        return Err(ApiError::BadRequest(String::from("from must be before to")));
+    }
+
+    let api_key = state.api_key()?;
+    let bars = get_candles(symbol.clone(), resolution.clone(), from, to, api_key)
+        .await
+        .map_err(|e| state.failed(e))?;
+    Ok(Json(SeriesResponse {
+        symbol,
+        resolution,
+        from,
+        to,
+        bars,
+    }))
+}
+
+/// The symbol directory of an exchange, by country code (`US`, `AU`, `UK`,
+/// `JP`).
+async fn symbols(
+    State(state): State<Arc<ApiState>>,
+    Path(exchange): Path<String>,
+) -> Result<Json<Vec<Listing>>, ApiError> {
+    let Some(exchange) = Exchange::from_country(&exchange.to_uppercase()) else {
+        let known: Vec<_> = Exchange::ALL.iter().map(|e| e.country()).collect();
+        return Err(ApiError::NotFound(format!(
+            "unknown exchange {exchange}; expected one of {}",
+            known.join(", ")
+        )));
+    };
+    let api_key = state.api_key()?;
+    let listings: Vec<Listing> = fetch_symbols(exchange, api_key)
+        .await
+        .map_err(|e| state.failed(e))?
+        .iter()
+        .filter_map(Listing::from_directory)
+        .collect();
+    if listings.is_empty() {
+        return Err(ApiError::Upstream(format!(
+            "no symbols returned for {}",
+            exchange.name()
+        )));
+    }
+    Ok(Json(listings))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::config::Storage;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    fn state(test: &str) -> ApiState {
+        let data_dir =
+            std::env::temp_dir().join(format!("finoracle-api-{test}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&data_dir);
+        ApiState::new(ServerConfig {
+            storage: Storage { data_dir },
+            ..ServerConfig::default()
+        })
+    }
+
+    #[test]
+    fn calls_are_keyed_without_the_api_key() {
+        let call = ProviderCall::parse(
+            "https://finnhub.io/api/v1/stock/candle?symbol=BHP.AX&resolution=D&from=1&to=2&token=SECRET",
+        )
+        .unwrap();
+        assert_eq!(call.provider, FINNHUB);
+        assert_eq!(call.endpoint, "stock/candle");
+        assert_eq!(
+            call.key,
+            "stock/candle?symbol=BHP.AX&resolution=D&from=1&to=2"
+        );
+
+        let call =
+            ProviderCall::parse("https://api.frankfurter.app/latest?from=USD&to=EUR").unwrap();
+        assert_eq!(
+            (call.provider, call.endpoint.as_str()),
+            ("frankfurter", "latest")
+        );
+    }
+
+    #[test]
+    fn cached_bodies_answer_every_client() {
+        let state = state("shared");
+        block_on(state.quotes.insert("quote?symbol=AAPL", String::from("{}")));
+        let body = block_on(state.get("https://finnhub.io/api/v1/quote?symbol=AAPL&token=OTHER"));
+        assert_eq!(body.unwrap(), "{}");
+        assert_eq!(state.throttle.used(), 0);
+    }
+
+    #[test]
+    fn failed_calls_do_not_reveal_the_api_key() {
+        let state = state("failed");
+        // Nothing listens on port 1, so the call fails before any response.
+        let error = block_on(state.get("http://127.0.0.1:1/api/v1/quote?symbol=AAPL&token=SECRET"))
+            .unwrap_err();
+        assert!(!error.to_string().contains("SECRET"), "{error}");
+        assert_eq!(state.throttle.used(), 1);
+
+        let response = state.failed(error).into_response();
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        let body = block_on(axum::body::to_bytes(response.into_body(), usize::MAX)).unwrap();
+        let body = String::from_utf8_lossy(&body);
+        assert!(!body.contains("SECRET"), "{body}");
+    }
+
+    #[test]
+    fn rate_limit_errors_answer_429() {
+        let state = state("limited");
+        let response = state
+            .failed(ServerFnError::new(RATE_LIMITED))
+            .into_response();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(response.headers().contains_key(header::RETRY_AFTER));
+    }
+
+    #[test]
+    fn throttle_refuses_calls_beyond_the_limit() {
+        let throttle = Throttle::new(3);
+        for _ in 0..3 {
+            assert_eq!(throttle.acquire(), Ok(()));
+        }
+        assert_eq!(throttle.used(), 3);
+        let retry_after = throttle.acquire().unwrap_err();
+        assert!((1..=60).contains(&retry_after), "{retry_after}");
+        // A refused call is not counted.
+        assert_eq!(throttle.used(), 3);
+    }
+
+    #[test]
+    fn throttle_opens_a_new_window_after_a_minute() {
+        let throttle = Throttle::new(1);
+        assert_eq!(throttle.acquire(), Ok(()));
+        assert!(throttle.acquire().is_err());
+        throttle.window.lock().unwrap().0 = Instant::now() - Duration::from_secs(61);
+        assert_eq!(throttle.used(), 0);
+        assert_eq!(throttle.acquire(), Ok(()));
+        assert_eq!(throttle.used(), 1);
+    }
+}
@@ -0,0 +1,285 @@
+use super::config::CacheSize;
+use super::metrics::CacheStats;
+use chrono::{DateTime, Utc};
+use dioxus::logger::tracing;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tokio::fs;
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Entry<V> {
+    /// File names replace unsafe characters, so the key is kept in the file.
+    key: String,
+    fetched: DateTime<Utc>,
+    value: V,
+}
+
+/// Upstream responses kept for the configured TTL. Up to the configured
+/// count live in memory, each mirrored to a file in the data directory so a
+/// restarted server keeps serving what it already fetched. An entry dropped
+/// from memory is deleted from disk too.
+pub struct Cache<V> {
+    name: &'static str,
+    size: CacheSize,
+    dir: PathBuf,
+    entries: Mutex<HashMap<String, Entry<V>>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<V: Clone + Serialize + DeserializeOwned> Cache<V> {
+    /// A cache stored in `name` under `dir`.
+    pub fn new(name: &'static str, size: CacheSize, dir: &Path) -> Cache<V> {
+        Cache {
+            name,
+            size,
+            dir: dir.join(name),
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            name: self.name,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries: self.len(),
+        }
+    }
+
+    /// Loads what an earlier run saved. Expired or unreadable files are
+    /// deleted, as are the oldest beyond the configured count, so the
+    /// directory holds what memory does.
+    pub async fn restore(&self) {
+        let Ok(mut dir) = fs::read_dir(&self.dir).await else {
+            return;
+        };
+        let mut restored = Vec::new();
+        let mut stale = Vec::new();
+        while let Ok(Some(file)) = dir.next_entry().await {
+            let path = file.path();
+            let entry = fs::read_to_string(&path)
+                .await
+                .ok()
+                .and_then(|json| serde_json::from_str::<Entry<V>>(&json).ok());
+            match entry {
+                Some(entry) if self.is_fresh(&entry) && self.path(&entry.key) == path => {
+                    restored.push(entry)
+                }
+                _ => stale.push(path),
+            }
+        }
+        restored.sort_by_key(|e| Reverse(e.fetched));
+        let kept = restored.len().min(self.size.entries);
+        stale.extend(restored.split_off(kept).iter().map(|e| self.path(&e.key)));
+        for path in &stale {
+            let _ = fs::remove_file(path).await;
+        }
+        tracing::info!(
+            cache = self.name,
+            restored = restored.len(),
+            removed = stale.len()
+        );
+        let mut entries = self.entries.lock().unwrap();
+        for entry in restored {
+            entries.insert(entry.key.clone(), entry);
+        }
+    }
+
+    /// A fresh value for `key`. Memory holds everything on disk, so only
+    /// memory is searched.
+    pub fn get(&self, key: &str) -> Option<V> {
+        let value = self
+            .entries
+            .lock()
+            .unwrap()
+            .get(key)
+            .filter(|e| self.is_fresh(e))
+            .map(|e| e.value.clone());
+        let counter = if value.is_some() {
+            &self.hits
+        } else {
+            &self.misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        tracing::info!(cache = self.name, key, cache_hit = value.is_some());
+        value
+    }
+
+    pub async fn insert(&self, key: &str, value: V) {
+        self.insert_entry(Entry {
+            key: key.to_string(),
+            fetched: Utc::now(),
+            value,
+        })
+        .await
+    }
+
+    async fn insert_entry(&self, entry: Entry<V>) {
+        let path = self.path(&entry.key);
+        let json = serde_json::to_string(&entry).ok();
+        let evicted = {
+            let mut entries = self.entries.lock().unwrap();
+            let evicted = self.evict(&mut entries);
+            entries.insert(entry.key.clone(), entry);
+            evicted
+        };
+        if let Some(json) = json {
+            let _ = fs::create_dir_all(&self.dir).await;
+            let _ = fs::write(path, json).await;
+        }
+        for key in evicted {
+            // Another request may have fetched it again in the meantime.
+            if !self.entries.lock().unwrap().contains_key(&key) {
+                let _ = fs::remove_file(self.path(&key)).await;
+            }
+        }
+    }
+
+    fn is_fresh(&self, entry: &Entry<V>) -> bool {
+        Utc::now()
+            .signed_duration_since(entry.fetched)
+            .to_std()
+            .is_ok_and(|age| age < self.size.ttl())
+    }
+
+    /// Drops expired entries, then the oldest ones, until there is room for
+    /// one more. Returns the keys dropped, whose files are to be deleted.
+    fn evict(&self, entries: &mut HashMap<String, Entry<V>>) -> Vec<String> {
+        let mut evicted = Vec::new();
+        entries.retain(|key, e| {
+            let fresh = self.is_fresh(e);
+            if !fresh {
+                evicted.push(key.clone());
+            }
+            fresh
+        });
+        while !entries.is_empty() && entries.len() >= self.size.entries {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, e)| e.fetched)
+                .map(|(k, _)| k.clone());
+            if let Some(key) = oldest {
+                entries.remove(&key);
+                evicted.push(key);
+            }
+        }
+        evicted
+    }
+
+    /// Keys embed symbols, so anything that is not safe in a file name is
+    /// replaced.
+    fn path(&self, key: &str) -> PathBuf {
+        let name: String = key
+            .chars()
+            .map(|c| match c {
+                'A'..='Z' | 'a'..='z' | '0'..='9' | '.' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+        self.dir.join(format!("{name}.json"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn block_on<F: std::future::Future>(future: F) -> F::Output {
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(future)
+    }
+
+    /// An empty directory of its own for each test.
+    fn scratch(test: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("finoracle-cache-{test}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn files(cache: &Cache<u32>) -> usize {
+        std::fs::read_dir(&cache.dir).map_or(0, |d| d.count())
+    }
+
+    fn size(entries: usize) -> CacheSize {
+        CacheSize {
+            entries,
+            ttl_secs: 60,
+        }
+    }
+
+    fn aged(key: &str, value: u32, seconds: i64) -> Entry<u32> {
+        Entry {
+            key: key.to_string(),
+            fetched: Utc::now() - Duration::seconds(seconds),
+            value,
+        }
+    }
+
+    #[test]
+    fn evicts_the_oldest_from_memory_and_disk() {
+        let dir = scratch("evict");
+        let cache: Cache<u32> = Cache::new("test", size(2), &dir);
+        block_on(async {
+            cache.insert_entry(aged("a", 1, 30)).await;
+            cache.insert_entry(aged("b", 2, 20)).await;
+            cache.insert_entry(aged("c", 3, 10)).await;
+        });
+        assert_eq!(cache.get("a"), None);
+        assert_eq!(cache.get("b"), Some(2));
+        assert_eq!(cache.get("c"), Some(3));
+        assert_eq!(files(&cache), 2);
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (2, 1, 2));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn expired_entries_are_misses_and_are_deleted() {
+        let dir = scratch("expire");
+        let cache: Cache<u32> = Cache::new("test", size(10), &dir);
+        block_on(cache.insert_entry(aged("old", 1, 61)));
+        assert_eq!(cache.get("old"), None);
+        block_on(cache.insert("new", 2));
+        assert_eq!(cache.len(), 1);
+        assert_eq!(files(&cache), 1);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn restores_the_newest_fresh_files() {
+        let dir = scratch("restore");
+        let earlier: Cache<u32> = Cache::new("test", size(10), &dir);
+        block_on(async {
+            earlier.insert_entry(aged("AAPL", 1, 61)).await;
+            earlier.insert_entry(aged("BHP.AX", 2, 30)).await;
+            earlier.insert_entry(aged("7203:T", 3, 20)).await;
+            earlier.insert_entry(aged("VOD.L", 4, 10)).await;
+        });
+        std::fs::write(earlier.dir.join("junk.json"), "{").unwrap();
+
+        let cache: Cache<u32> = Cache::new("test", size(2), &dir);
+        block_on(cache.restore());
+        assert_eq!(cache.get("VOD.L"), Some(4));
+        assert_eq!(cache.get("7203:T"), Some(3));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(files(&cache), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
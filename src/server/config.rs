@@ -0,0 +1,226 @@
+use serde::Deserialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Read when neither `--config` nor `FINORACLE_CONFIG` names a file. Unlike
+/// a named file, it may be missing, in which case the defaults apply.
+const DEFAULT_PATH: &str = "finoracle.toml";
+
+/// The server's settings, read from a TOML file. Every section and key is
+/// optional; see `finoracle.example.toml` for the defaults.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub listen: Listen,
+    pub providers: Providers,
+    pub cache: Caches,
+    pub storage: Storage,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Listen {
+    pub host: IpAddr,
+    pub port: u16,
+}
+
+impl Default for Listen {
+    fn default() -> Self {
+        Listen {
+            host: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 8080,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Providers {
+    pub finnhub: Finnhub,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Finnhub {
+    /// Falls back to `FINNHUB_API_KEY` at run time, then at build time.
+    pub api_key: Option<String>,
+    /// Finnhub calls allowed per minute, shared by the app's server functions
+    /// and the JSON API. Cache hits don't count.
+    pub requests_per_minute: u32,
+}
+
+impl Default for Finnhub {
+    fn default() -> Self {
+        Finnhub {
+            api_key: None,
+            requests_per_minute: 60,
+        }
+    }
+}
+
+impl Finnhub {
+    pub fn api_key(&self) -> Option<String> {
+        self.api_key
+            .clone()
+            .or_else(|| std::env::var("FINNHUB_API_KEY").ok())
+            .or_else(|| option_env!("FINNHUB_API_KEY").map(String::from))
+            .filter(|key| !key.is_empty())
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Caches {
+    pub quotes: CacheSize,
+    pub series: CacheSize,
+    pub symbols: CacheSize,
+}
+
+impl Default for Caches {
+    fn default() -> Self {
+        Caches {
+            quotes: CacheSize {
+                entries: 1000,
+                ttl_secs: 15,
+            },
+            series: CacheSize {
+                entries: 500,
+                ttl_secs: 300,
+            },
+            symbols: CacheSize {
+                entries: 8,
+                ttl_secs: 24 * 60 * 60,
+            },
+        }
+    }
+}
+
+/// How many responses a cache holds and for how long.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(deny_unknown_fields)]
+pub struct CacheSize {
+    pub entries: usize,
+    pub ttl_secs: u64,
+}
+
+impl CacheSize {
+    pub fn ttl(&self) -> Duration {
+        Duration::from_secs(self.ttl_secs)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Storage {
+    /// Where cached responses are written so a restart starts warm.
+    pub data_dir: PathBuf,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Storage {
+            data_dir: PathBuf::from("data"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "cannot read {}: {e}", path.display()),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl ServerConfig {
+    /// Loads the file named by `--config <path>` or `FINORACLE_CONFIG`,
+    /// otherwise `finoracle.toml` if there is one.
+    pub fn load() -> Result<ServerConfig, ConfigError> {
+        let named = std::env::args()
+            .skip_while(|arg| arg != "--config")
+            .nth(1)
+            .or_else(|| std::env::var("FINORACLE_CONFIG").ok());
+        match named {
+            Some(path) => ServerConfig::read(Path::new(&path)),
+            None if Path::new(DEFAULT_PATH).exists() => ServerConfig::read(Path::new(DEFAULT_PATH)),
+            None => Ok(ServerConfig::default()),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<ServerConfig, ConfigError> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// The configured address, unless `dx serve` picked one for us.
+    pub fn address(&self) -> SocketAddr {
+        match dioxus::cli_config::server_port() {
+            Some(_) => dioxus::cli_config::fullstack_address_or_localhost(),
+            None => SocketAddr::new(self.listen.host, self.listen.port),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_file_gives_the_defaults() {
+        let config: ServerConfig = toml::from_str("").unwrap();
+        assert_eq!(config.listen.port, 8080);
+        assert_eq!(config.providers.finnhub.requests_per_minute, 60);
+        assert_eq!(config.cache.quotes.entries, 1000);
+        assert_eq!(config.storage.data_dir, PathBuf::from("data"));
+    }
+
+    #[test]
+    fn sections_override_only_what_they_set() {
+        let config: ServerConfig = toml::from_str(
+            r#"
+            [listen]
+            port = 9000
+
+            [cache.series]
+            entries = 20
+            ttl_secs = 60
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.listen.port, 9000);
+        assert_eq!(config.listen.host, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(config.cache.series.entries, 20);
+        assert_eq!(config.cache.series.ttl(), Duration::from_secs(60));
+        assert_eq!(config.cache.quotes.ttl_secs, 15);
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        for toml in [
+            "verbose = true",
+            "[listen]\nadress = \"0.0.0.0\"",
+            "[providers.finnhub]\nrequests_per_min = 30",
+            "[cache.quotes]\nentries = 10\nttl_secs = 5\nttl = 5",
+            "[cache.news]\nentries = 10\nttl_secs = 5",
+        ] {
+            assert!(toml::from_str::<ServerConfig>(toml).is_err(), "{toml}");
+        }
+    }
+
+    #[test]
+    fn cache_sizes_need_both_keys() {
+        assert!(toml::from_str::<ServerConfig>("[cache.quotes]\nentries = 10").is_err());
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Upper bounds of the upstream latency buckets, in seconds.
+const BUCKETS: [f64; 10] = [0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Process-wide counters behind `/metrics`. The caches and the rate limit
+/// keep their own and are read when the page is rendered.
+pub static METRICS: Metrics = Metrics {
+    upstream: Mutex::new(BTreeMap::new()),
+    in_flight: AtomicI64::new(0),
+    throttled: AtomicU64::new(0),
+};
+
+pub struct Metrics {
+    /// By `(provider, endpoint)`.
+    upstream: Mutex<BTreeMap<(&'static str, String), Upstream>>,
+    in_flight: AtomicI64,
+    throttled: AtomicU64,
+}
+
+#[derive(Default)]
+struct Upstream {
+    errors: u64,
+    /// Per bucket, not cumulative; the last slot counts the overflow.
+    buckets: [u64; BUCKETS.len() + 1],
+    sum: f64,
+    count: u64,
+}
+
+/// Counts an upstream call as in flight until dropped.
+pub struct InFlight;
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        METRICS.in_flight.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// One cache's counters, as read when rendering.
+pub struct CacheStats {
+    pub name: &'static str,
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+/// The rate limit's state, as read when rendering.
+pub struct LimitStats {
+    pub per_minute: u32,
+    pub used: u32,
+}
+
+impl Metrics {
+    pub fn start_upstream(&self) -> InFlight {
+        self.in_flight.fetch_add(1, Ordering::Relaxed);
+        InFlight
+    }
+
+    pub fn observe_upstream(&self, provider: &'static str, endpoint: &str, seconds: f64, ok: bool) {
+        let mut upstream = self.upstream.lock().unwrap();
+        let entry = upstream
+            .entry((provider, endpoint.to_string()))
+            .or_default();
+        let bucket = BUCKETS
+            .iter()
+            .position(|le| seconds <= *le)
+            .unwrap_or(BUCKETS.len());
+        entry.buckets[bucket] += 1;
+        entry.sum += seconds;
+        entry.count += 1;
+        if !ok {
+            entry.errors += 1;
+        }
+    }
+
+    pub fn throttled(&self) {
+        self.throttled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// The Prometheus text exposition of every metric.
+    pub fn render(&self, caches: &[CacheStats], limit: LimitStats) -> String {
+        let mut out = String::new();
+        let upstream = self.upstream.lock().unwrap();
+
+        header(
+            &mut out,
+            "finoracle_upstream_requests_total",
+            "counter",
+            "Calls to data providers.",
+        );
+        for ((provider, endpoint), u) in upstream.iter() {
+            let labels = labels(provider, endpoint);
+            let _ = writeln!(
+                out,
+                "finoracle_upstream_requests_total{{{labels}}} {}",
+                u.count
+            );
+        }
+        header(
+            &mut out,
+            "finoracle_upstream_errors_total",
+            "counter",
+            "Calls to data providers that failed.",
+        );
+        for ((provider, endpoint), u) in upstream.iter() {
+            let labels = labels(provider, endpoint);
+            let _ = writeln!(
+                out,
+                "finoracle_upstream_errors_total{{{labels}}} {}",
+                u.errors
+            );
+        }
+        header(
+            &mut out,
+            "finoracle_upstream_request_duration_seconds",
+            "histogram",
+            "Latency of calls to data providers.",
+        );
+        for ((provider, endpoint), u) in upstream.iter() {
+            let labels = labels(provider, endpoint);
+            let name = "finoracle_upstream_request_duration_seconds";
+            let mut cumulative = 0;
+            for (le, n) in BUCKETS.iter().zip(u.buckets) {
+                cumulative += n;
+                let _ = writeln!(out, "{name}_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "{name}_bucket{{{labels},le=\"+Inf\"}} {}", u.count);
+            let _ = writeln!(out, "{name}_sum{{{labels}}} {}", u.sum);
+            let _ = writeln!(out, "{name}_count{{{labels}}} {}", u.count);
+        }
+        header(
+            &mut out,
+            "finoracle_upstream_in_flight",
+            "gauge",
+            "Calls to data providers awaiting a response.",
+        );
+        let _ = writeln!(
+            out,
+            "finoracle_upstream_in_flight {}",
+            self.in_flight.load(Ordering::Relaxed)
+        );
+
+        header(
+            &mut out,
+            "finoracle_cache_hits_total",
+            "counter",
+            "Lookups answered from the cache.",
+        );
+        for c in caches {
+            let _ = writeln!(
+                out,
+                "finoracle_cache_hits_total{{cache=\"{}\"}} {}",
+                c.name, c.hits
+            );
+        }
+        header(
+            &mut out,
+            "finoracle_cache_misses_total",
+            "counter",
+            "Lookups that had to call the provider.",
+        );
+        for c in caches {
+            let _ = writeln!(
+                out,
+                "finoracle_cache_misses_total{{cache=\"{}\"}} {}",
+                c.name, c.misses
+            );
+        }
+        header(
+            &mut out,
+            "finoracle_cache_hit_ratio",
+            "gauge",
+            "Share of lookups answered from the cache since start.",
+        );
+        for c in caches {
+            let lookups = c.hits + c.misses;
+            let ratio = if lookups == 0 {
+                0.0
+            } else {
+                c.hits as f64 / lookups as f64
+            };
+            let _ = writeln!(
+                out,
+                "finoracle_cache_hit_ratio{{cache=\"{}\"}} {ratio}",
+                c.name
+            );
+        }
+        header(
+            &mut out,
+            "finoracle_cache_entries",
+            "gauge",
+            "Responses held in memory.",
+        );
+        for c in caches {
+            let _ = writeln!(
+                out,
+                "finoracle_cache_entries{{cache=\"{}\"}} {}",
+                c.name, c.entries
+            );
+        }
+
+        header(
+            &mut out,
+            "finoracle_rate_limit_per_minute",
+            "gauge",
+            "Provider calls the server allows per minute.",
+        );
+        let _ = writeln!(out, "finoracle_rate_limit_per_minute {}", limit.per_minute);
+        header(
+            &mut out,
+            "finoracle_rate_limit_used",
+            "gauge",
+            "Provider calls made in the current one-minute window.",
+        );
+        let _ = writeln!(out, "finoracle_rate_limit_used {}", limit.used);
+        header(
+            &mut out,
+            "finoracle_rate_limited_total",
+            "counter",
+            "Provider calls refused for exceeding the rate limit.",
+        );
+        let _ = writeln!(
+            out,
+            "finoracle_rate_limited_total {}",
+            self.throttled.load(Ordering::Relaxed)
+        );
+        out
+    }
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn labels(provider: &str, endpoint: &str) -> String {
+    let endpoint = endpoint
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("provider=\"{provider}\",endpoint=\"{endpoint}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics() -> Metrics {
+        Metrics {
+            upstream: Mutex::new(BTreeMap::new()),
+            in_flight: AtomicI64::new(0),
+            throttled: AtomicU64::new(0),
+        }
+    }
+
+    fn render(metrics: &Metrics) -> String {
+        let caches = [CacheStats {
+            name: "quotes",
+            hits: 3,
+            misses: 1,
+            entries: 2,
+        }];
+        let limit = LimitStats {
+            per_minute: 60,
+            used: 7,
+        };
+        metrics.render(&caches, limit)
+    }
+
+    fn lines<'a>(text: &'a str, prefix: &str) -> Vec<&'a str> {
+        text.lines().filter(|l| l.starts_with(prefix)).collect()
+    }
+
+    #[test]
+    fn histogram_buckets_are_cumulative() {
+        let metrics = metrics();
+        for (seconds, ok) in [(0.03, true), (0.2, true), (45.0, false)] {
+            metrics.observe_upstream("finnhub", "quote", seconds, ok);
+        }
+        let text = render(&metrics);
+
+        let labels = r#"provider="finnhub",endpoint="quote""#;
+        let name = "finoracle_upstream_request_duration_seconds";
+        let counts: Vec<&str> = lines(&text, &format!("{name}_bucket"))
+            .iter()
+            .map(|l| l.rsplit(' ').next().unwrap())
+            .collect();
+        assert_eq!(
+            counts,
+            ["0", "1", "1", "2", "2", "2", "2", "2", "2", "2", "3"]
+        );
+        assert!(text.contains(&format!("{name}_bucket{{{labels},le=\"+Inf\"}} 3\n")));
+        assert!(text.contains(&format!("{name}_count{{{labels}}} 3\n")));
+        assert!(text.contains(&format!("{name}_sum{{{labels}}} 45.23\n")));
+        assert!(text.contains(&format!(
+            "finoracle_upstream_requests_total{{{labels}}} 3\n"
+        )));
+        assert!(text.contains(&format!("finoracle_upstream_errors_total{{{labels}}} 1\n")));
+    }
+
+    #[test]
+    fn label_values_are_escaped() {
+        let metrics = metrics();
+        metrics.observe_upstream("finnhub", "odd\"end\\point\nx", 0.01, true);
+        let text = render(&metrics);
+        assert!(text.contains(
+            r#"finoracle_upstream_requests_total{provider="finnhub",endpoint="odd\"end\\point\nx"} 1"#
+        ));
+        assert_eq!(lines(&text, "finoracle_upstream_requests_total{").len(), 1);
+    }
+
+    #[test]
+    fn caches_and_the_rate_limit_are_reported() {
+        let metrics = metrics();
+        metrics.throttled();
+        metrics.in_flight.fetch_add(1, Ordering::Relaxed);
+        let text = render(&metrics);
+
+        for line in [
+            "finoracle_upstream_in_flight 1",
+            "finoracle_cache_hits_total{cache=\"quotes\"} 3",
+            "finoracle_cache_misses_total{cache=\"quotes\"} 1",
+            "finoracle_cache_hit_ratio{cache=\"quotes\"} 0.75",
+            "finoracle_cache_entries{cache=\"quotes\"} 2",
+            "finoracle_rate_limit_per_minute 60",
+            "finoracle_rate_limit_used 7",
+            "finoracle_rate_limited_total 1",
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line}");
+        }
+        // Every metric has its help and type.
+        assert_eq!(lines(&text, "# HELP").len(), lines(&text, "# TYPE").len());
+    }
+}
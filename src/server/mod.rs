@@ -0,0 +1,137 @@
+//! Standalone server mode. Serves the app and its server functions together
+//! with health checks and a versioned JSON API over the same data layer,
+//! configured from a TOML file (see [`ServerConfig`]).
+
+mod api;
+mod cache;
+mod config;
+mod metrics;
+
+pub use api::provider_get;
+use api::ApiState;
+use axum::extract::Request;
+use axum::http::HeaderValue;
+use axum::middleware::{self, Next};
+use axum::response::Response;
+use axum::routing::get;
+use config::ServerConfig;
+use dioxus::logger::tracing::{self, Instrument};
+use dioxus::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Echoed on every response; taken from the request when a proxy set it.
+const REQUEST_ID: &str = "x-request-id";
+
+pub fn launch(app: fn() -> Element) {
+    dioxus::logger::initialize_default();
+    let config = match ServerConfig::load() {
+        Ok(config) => config,
+        Err(e) => {
+            tracing::error!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .expect("failed to start the async runtime");
+    if let Err(e) = runtime.block_on(serve(config, app)) {
+        tracing::error!("{e}");
+        std::process::exit(1);
+    }
+}
+
+async fn serve(config: ServerConfig, app: fn() -> Element) -> std::io::Result<()> {
+    let address = config.address();
+    let state = ApiState::new(config);
+    state.restore().await;
+    let state = Arc::new(state);
+    state.share();
+    let router = axum::Router::new()
+        .route("/healthz", get(api::healthz))
+        .route("/readyz", get(api::readyz))
+        .route("/metrics", get(api::metrics))
+        .nest("/api/v1", api::routes())
+        .with_state(state)
+        .serve_dioxus_application(ServeConfigBuilder::default(), app)
+        .layer(middleware::from_fn(trace_request));
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    tracing::info!(%address, "listening");
+    axum::serve(listener, router.into_make_service()).await
+}
+
+/// Runs each request in a span carrying its id, and the function's name for
+/// server-function calls, then logs its status and latency. Everything
+/// logged while handling it, upstream calls and cache lookups included, is
+/// tagged the same way.
+async fn trace_request(request: Request, next: Next) -> Response {
+    static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+    let id = request
+        .headers()
+        .get(REQUEST_ID)
+        .and_then(|v| v.to_str().ok())
+        .map(String::from)
+        .unwrap_or_else(|| format!("{:08x}", NEXT_ID.fetch_add(1, Ordering::Relaxed)));
+    let span = tracing::info_span!(
+        "request",
+        id = %id,
+        method = %request.method(),
+        path = %request.uri().path(),
+        function = tracing::field::Empty,
+    );
+    if let Some(name) = server_function(request.uri().path()) {
+        span.record("function", name);
+    }
+
+    let started = Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+    span.in_scope(|| {
+        tracing::info!(status = response.status().as_u16(), latency_ms, "served");
+    });
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID, value);
+    }
+    response
+}
+
+/// The server function behind a request path, `/api/<name><hash>`.
+fn server_function(path: &str) -> Option<&str> {
+    let name = path
+        .strip_prefix("/api/")
+        .filter(|rest| *rest != "v1" && !rest.contains('/'))?
+        .trim_end_matches(|c: char| c.is_ascii_digit());
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_functions_are_named_without_their_hash() {
+        assert_eq!(
+            server_function("/api/get_stock_quote12345678901234567"),
+            Some("get_stock_quote")
+        );
+        assert_eq!(server_function("/api/get_fx_rates"), Some("get_fx_rates"));
+        // Digits inside the name are kept.
+        assert_eq!(
+            server_function("/api/get_sp500_members4821"),
+            Some("get_sp500_members")
+        );
+    }
+
+    #[test]
+    fn other_paths_are_not_server_functions() {
+        assert_eq!(server_function("/api/v1/quote/AAPL"), None);
+        assert_eq!(server_function("/api/v1"), None);
+        assert_eq!(server_function("/api/12345"), None);
+        assert_eq!(server_function("/api/"), None);
+        assert_eq!(server_function("/metrics"), None);
+        assert_eq!(server_function("/api/nested/path"), None);
+    }
+}
@@ -0,0 +1,278 @@
+use chrono::NaiveDate;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+
+/// Statement line items sent to `ChartView`, by series name.
+pub static STATEMENT_SERIES: GlobalSignal<Map<String, Value>> = Global::new(Map::new);
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statement {
+    Income,
+    Balance,
+    CashFlow,
+}
+
+impl Statement {
+    pub const ALL: [Statement; 3] = [Statement::Income, Statement::Balance, Statement::CashFlow];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Statement::Income => "Income",
+            Statement::Balance => "Balance sheet",
+            Statement::CashFlow => "Cash flow",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Annual,
+    Quarterly,
+}
+
+impl Frequency {
+    pub fn param(&self) -> &'static str {
+        match self {
+            Frequency::Annual => "annual",
+            Frequency::Quarterly => "quarterly",
+        }
+    }
+}
+
+/// One reported figure, tagged with its XBRL concept.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LineItem {
+    pub concept: String,
+    pub label: String,
+    #[serde(default)]
+    pub unit: String,
+    /// Some filings report a dash or text in place of a number.
+    #[serde(default, deserialize_with = "number_or_none")]
+    pub value: Option<f64>,
+}
+
+fn number_or_none<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Value::deserialize(deserializer)?.as_f64())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    #[serde(default)]
+    pub ic: Vec<LineItem>,
+    #[serde(default)]
+    pub bs: Vec<LineItem>,
+    #[serde(default)]
+    pub cf: Vec<LineItem>,
+}
+
+/// A 10-K or 10-Q as filed.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Filing {
+    pub year: i32,
+    #[serde(default)]
+    pub quarter: u32,
+    #[serde(default)]
+    pub form: String,
+    #[serde(rename = "endDate")]
+    pub end_date: String,
+    #[serde(rename = "filedDate", default)]
+    pub filed_date: String,
+    #[serde(default)]
+    pub report: Report,
+}
+
+impl Filing {
+    pub fn items(&self, statement: Statement) -> &[LineItem] {
+        match statement {
+            Statement::Income => &self.report.ic,
+            Statement::Balance => &self.report.bs,
+            Statement::CashFlow => &self.report.cf,
+        }
+    }
+
+    /// Period end; the provider sends `YYYY-MM-DD HH:MM:SS`.
+    pub fn period_end(&self) -> Option<NaiveDate> {
+        self.end_date
+            .get(..10)
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+
+    pub fn period_label(&self) -> String {
+        match self.quarter {
+            0 => format!("FY{}", self.year),
+            q => format!("Q{q} {}", self.year),
+        }
+    }
+}
+
+/// One statement laid out with a row per line item and a column per filing,
+/// newest first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StatementTable {
+    pub periods: Vec<(String, NaiveDate)>,
+    pub rows: Vec<Row>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Row {
+    pub concept: String,
+    pub label: String,
+    pub unit: String,
+    /// Parallel to `StatementTable::periods`.
+    pub values: Vec<Option<f64>>,
+}
+
+impl Row {
+    /// Change against the previous period, as a percentage, for each column.
+    pub fn growth(&self) -> Vec<Option<f64>> {
+        self.values
+            .iter()
+            .enumerate()
+            .map(
+                |(i, value)| match (value, self.values.get(i + 1).copied().flatten()) {
+                    (Some(v), Some(prev)) if prev != 0.0 => Some((v - prev) / prev.abs() * 100.0),
+                    _ => None,
+                },
+            )
+            .collect()
+    }
+
+    /// The row as a `[{period, v}]` series for `ChartView`.
+    pub fn to_chart_series(&self, periods: &[(String, NaiveDate)]) -> Value {
+        Value::Array(
+            periods
+                .iter()
+                .zip(&self.values)
+                .filter_map(|((_, end), value)| {
+                    Some(json!({"period": end.to_string(), "v": (*value)?}))
+                })
+                .collect(),
+        )
+    }
+}
+
+impl StatementTable {
+    /// Rows follow the newest filing's order; concepts that only appear in
+    /// older filings are appended after it.
+    pub fn build(filings: &[Filing], statement: Statement) -> StatementTable {
+        let mut filings: Vec<&Filing> = filings
+            .iter()
+            .filter(|f| f.period_end().is_some())
+            .collect();
+        filings.sort_by_key(|f| std::cmp::Reverse(f.period_end()));
+
+        let periods = filings
+            .iter()
+            .filter_map(|f| Some((f.period_label(), f.period_end()?)))
+            .collect();
+
+        let mut rows: Vec<Row> = Vec::new();
+        for (column, filing) in filings.iter().enumerate() {
+            for item in filing.items(statement) {
+                let row = match rows.iter().position(|r| r.concept == item.concept) {
+                    Some(i) => &mut rows[i],
+                    None => {
+                        rows.push(Row {
+                            concept: item.concept.to_owned(),
+                            label: item.label.to_owned(),
+                            unit: item.unit.to_owned(),
+                            values: vec![None; filings.len()],
+                        });
+                        rows.last_mut().expect("row just pushed")
+                    }
+                };
+                // Keep the first figure when a concept is reported twice.
+                if row.values[column].is_none() {
+                    row.values[column] = item.value;
+                }
+            }
+        }
+
+        // 10-Q cash flows are year to date; the quarter's own figure is the
+        // difference from the previous quarter of the same fiscal year.
+        if statement == Statement::CashFlow {
+            let keys: Vec<(i32, u32)> = filings.iter().map(|f| (f.year, f.quarter)).collect();
+            for row in rows.iter_mut() {
+                let cumulative = row.values.clone();
+                for (column, (year, quarter)) in keys.iter().enumerate() {
+                    if *quarter <= 1 {
+                        continue;
+                    }
+                    let previous = keys
+                        .iter()
+                        .position(|k| *k == (*year, quarter - 1))
+                        .and_then(|p| cumulative[p]);
+                    row.values[column] = match (cumulative[column], previous) {
+                        (Some(to_date), Some(before)) => Some(to_date - before),
+                        _ => None,
+                    };
+                }
+            }
+        }
+
+        StatementTable { periods, rows }
+    }
+}
+
+#[server]
+pub async fn get_financials_reported(
+    symbol: String,
+    frequency: Frequency,
+    api_key: String,
+) -> Result<Vec<Filing>, ServerFnError> {
+    #[derive(Deserialize)]
+    struct Response {
+        #[serde(default)]
+        data: Vec<Filing>,
+    }
+
+    let body = crate::server::provider_get(&format!(
+        "https://finnhub.io/api/v1/stock/financials-reported?symbol={}&freq={}&token={api_key}",
+        symbol,
+        frequency.param()
+    ))
+    .await?;
+    let response: Response = serde_json::from_str(&body)?;
+    Ok(response.data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filing(quarter: u32, end_date: &str, cash: f64) -> Filing {
+        Filing {
+            year: 2025,
+            quarter,
+            form: String::from("10-Q"),
+            end_date: format!("{end_date} 00:00:00"),
+            filed_date: String::new(),
+            report: Report {
+                cf: vec![LineItem {
+                    concept: String::from("NetCashProvidedByUsedInOperatingActivities"),
+                    label: String::from("Operating cash flow"),
+                    unit: String::from("usd"),
+                    value: Some(cash),
+                }],
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn quarterly_cash_flows_are_not_cumulative() {
+        let filings = [
+            filing(1, "2025-03-31", 100.0),
+            filing(2, "2025-06-30", 250.0),
+            filing(3, "2025-09-30", 330.0),
+        ];
+        let table = StatementTable::build(&filings, Statement::CashFlow);
+        let row = &table.rows[0];
+        assert_eq!(row.values, vec![Some(80.0), Some(150.0), Some(100.0)]);
+        assert_eq!(row.growth()[0], Some((80.0 - 150.0) / 150.0 * 100.0));
+    }
+}
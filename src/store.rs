@@ -0,0 +1,475 @@
+use crate::components::{ChartQuery, CHART_SELECTION};
+use crate::corporate::Adjustment;
+use crate::currency::Currency;
+use crate::exchange::Exchange;
+use crate::money::TickSize;
+use crate::platform;
+use crate::quality::Policy;
+use crate::search::{Listing, SecurityType};
+use crate::series::TimeDisplay;
+use dioxus::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The app's persisted session, and the one place its selections and
+/// settings live. Modules read them through memos such as `EXCHANGE` and
+/// change them with `update`.
+pub static APP_STATE: GlobalSignal<AppState> = Global::new(AppState::load);
+
+const STORAGE_KEY: &str = "finoracle.state";
+
+/// A tradable symbol with what is known about its listing.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Instrument {
+    /// Provider symbol, e.g. `BHP.AX`.
+    pub symbol: String,
+    pub description: String,
+    pub exchange: Exchange,
+    pub currency: Currency,
+    pub kind: SecurityType,
+}
+
+impl Default for Instrument {
+    fn default() -> Self {
+        Instrument::new("", "")
+    }
+}
+
+impl Instrument {
+    /// An instrument known only by symbol, with exchange and currency
+    /// inferred from its suffix.
+    pub fn new(symbol: &str, description: &str) -> Instrument {
+        let exchange = Exchange::from_symbol(symbol);
+        Instrument {
+            symbol: symbol.to_string(),
+            description: description.to_string(),
+            exchange,
+            currency: exchange.currency(),
+            kind: SecurityType::Other,
+        }
+    }
+
+    pub fn from_listing(listing: &Listing) -> Instrument {
+        let exchange = Exchange::from_symbol(&listing.symbol);
+        Instrument {
+            symbol: listing.symbol.to_owned(),
+            description: listing.description.to_owned(),
+            exchange,
+            currency: Currency::from_code(&listing.currency).unwrap_or(exchange.currency()),
+            kind: listing.kind,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbol.is_empty()
+    }
+
+    /// The bare ticker, e.g. `BHP` for `BHP.AX`.
+    pub fn ticker(&self) -> &str {
+        self.exchange.display_symbol(&self.symbol)
+    }
+
+    /// The exchange's price increments for ordinary shares.
+    pub fn tick_size(&self) -> TickSize {
+        TickSize::for_exchange(self.exchange)
+    }
+}
+
+/// Display settings shared by the chart and panels.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Settings {
+    pub time_display: TimeDisplay,
+    pub quality_policy: Policy,
+    pub adjustment: Adjustment,
+    pub sentiment_overlay: bool,
+    pub quote_refresh_secs: u64,
+    pub reporting_currency: Option<Currency>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            time_display: TimeDisplay::Exchange,
+            quality_policy: Policy::Flag,
+            adjustment: Adjustment::Split,
+            sentiment_overlay: false,
+            quote_refresh_secs: 15,
+            reporting_currency: None,
+        }
+    }
+}
+
+impl Settings {
+    /// Quote refresh intervals offered, in seconds, shortest first.
+    pub const REFRESH_SECS: [u64; 5] = [5, 15, 30, 60, 300];
+
+    /// Brings values from an older or hand-edited session back into range.
+    fn clamp(&mut self) {
+        let (min, max) = (Self::REFRESH_SECS[0], Self::REFRESH_SECS[4]);
+        self.quote_refresh_secs = self.quote_refresh_secs.clamp(min, max);
+    }
+}
+
+/// The panes the workspace is made of.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Panel {
+    List,
+    Detail,
+    Chart,
+    Watchlist,
+    News,
+    Earnings,
+    Screener,
+}
+
+impl Panel {
+    pub const ALL: [Panel; 7] = [
+        Panel::List,
+        Panel::Detail,
+        Panel::Chart,
+        Panel::Watchlist,
+        Panel::News,
+        Panel::Earnings,
+        Panel::Screener,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Panel::List => "Symbols",
+            Panel::Detail => "Detail",
+            Panel::Chart => "Chart",
+            Panel::Watchlist => "Watchlist",
+            Panel::News => "News",
+            Panel::Earnings => "Earnings",
+            Panel::Screener => "Screener",
+        }
+    }
+}
+
+/// A splitter between panes, dragged to resize them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Split {
+    /// Between the left column and the rest.
+    LeftWidth,
+    /// Between the two panes of the left column.
+    LeftHeight,
+    /// Between the main pane and the bottom row.
+    MainHeight,
+}
+
+/// Where each panel sits and how much room it gets. Sizes are percentages of
+/// the enclosing area.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Layout {
+    /// The left column, top to bottom.
+    pub left: Vec<Panel>,
+    /// The large pane at the top of the right column.
+    pub main: Panel,
+    /// The row under the main pane, left to right.
+    pub bottom: Vec<Panel>,
+    pub left_width: f64,
+    pub left_height: f64,
+    pub main_height: f64,
+    /// A panel filling the whole workspace.
+    pub maximized: Option<Panel>,
+    /// Panels collapsed to their title bar.
+    pub minimized: Vec<Panel>,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout {
+            left: vec![Panel::List, Panel::Detail],
+            main: Panel::Chart,
+            bottom: vec![
+                Panel::Watchlist,
+                Panel::News,
+                Panel::Earnings,
+                Panel::Screener,
+            ],
+            left_width: 25.0,
+            left_height: 50.0,
+            main_height: 50.0,
+            maximized: None,
+            minimized: Vec::new(),
+        }
+    }
+}
+
+impl Layout {
+    /// Smallest share a resized pane keeps.
+    const MIN_SIZE: f64 = 10.0;
+
+    fn slots_mut(&mut self) -> impl Iterator<Item = &mut Panel> {
+        self.left
+            .iter_mut()
+            .chain(std::iter::once(&mut self.main))
+            .chain(self.bottom.iter_mut())
+    }
+
+    /// Exchanges the places of two panels.
+    pub fn swap(&mut self, a: Panel, b: Panel) {
+        for slot in self.slots_mut() {
+            if *slot == a {
+                *slot = b;
+            } else if *slot == b {
+                *slot = a;
+            }
+        }
+    }
+
+    pub fn is_minimized(&self, panel: Panel) -> bool {
+        self.minimized.contains(&panel)
+    }
+
+    pub fn toggle_minimized(&mut self, panel: Panel) {
+        match self.minimized.iter().position(|p| *p == panel) {
+            Some(i) => {
+                self.minimized.remove(i);
+            }
+            None => {
+                self.minimized.push(panel);
+                if self.maximized == Some(panel) {
+                    self.maximized = None;
+                }
+            }
+        }
+    }
+
+    pub fn toggle_maximized(&mut self, panel: Panel) {
+        self.maximized = match self.maximized {
+            Some(p) if p == panel => None,
+            _ => Some(panel),
+        };
+        self.minimized.retain(|p| *p != panel);
+    }
+
+    /// Checks that every panel has exactly one place and that both columns
+    /// have something in them, as a layout read from a file may not.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.left.is_empty() {
+            return Err(String::from("the left column has no panels"));
+        }
+        if self.bottom.is_empty() {
+            return Err(String::from("the bottom row has no panels"));
+        }
+        let placed: Vec<Panel> = self
+            .left
+            .iter()
+            .chain(std::iter::once(&self.main))
+            .chain(self.bottom.iter())
+            .copied()
+            .collect();
+        for panel in Panel::ALL {
+            match placed.iter().filter(|p| **p == panel).count() {
+                0 => return Err(format!("the {} panel is missing", panel.label())),
+                1 => (),
+                _ => {
+                    return Err(format!(
+                        "the {} panel appears more than once",
+                        panel.label()
+                    ))
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn resize(&mut self, split: Split, percent: f64) {
+        let percent = percent.clamp(Self::MIN_SIZE, 100.0 - Self::MIN_SIZE);
+        match split {
+            Split::LeftWidth => self.left_width = percent,
+            Split::LeftHeight => self.left_height = percent,
+            Split::MainHeight => self.main_height = percent,
+        }
+    }
+}
+
+/// A layout saved under a name, also the contents of a `.finoracle` file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SavedWorkspace {
+    pub name: String,
+    pub layout: Layout,
+}
+
+/// Everything restored on reload. Missing fields fall back to their
+/// defaults so older saved sessions still load.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct AppState {
+    pub instrument: Instrument,
+    pub exchange: Option<Exchange>,
+    pub chart: ChartQuery,
+    pub comparison: Vec<String>,
+    pub watchlist: Vec<Instrument>,
+    pub settings: Settings,
+    pub layout: Layout,
+    pub workspaces: Vec<SavedWorkspace>,
+}
+
+impl AppState {
+    /// The saved session: localStorage on the web, a JSON file in the
+    /// config directory rather than with the caches on desktop.
+    pub fn load() -> AppState {
+        let mut state = Self::saved();
+        state.settings.clamp();
+        state
+    }
+
+    fn saved() -> AppState {
+        #[cfg(all(feature = "desktop", not(target_arch = "wasm32")))]
+        if let Some(state) = platform::config_dir()
+            .and_then(|dir| std::fs::read_to_string(dir.join("state.json")).ok())
+            .and_then(|json| serde_json::from_str(&json).ok())
+        {
+            return state;
+        }
+
+        platform::load(STORAGE_KEY).unwrap_or_default()
+    }
+
+    pub fn store(&self) {
+        #[cfg(all(feature = "desktop", not(target_arch = "wasm32")))]
+        if let (Some(dir), Ok(json)) = (platform::config_dir(), serde_json::to_string_pretty(self))
+        {
+            let _ = std::fs::create_dir_all(&dir);
+            let _ = std::fs::write(dir.join("state.json"), json);
+            return;
+        }
+
+        platform::save(STORAGE_KEY, self);
+    }
+}
+
+/// The selected instrument, restored from the saved session on first use
+/// and saved whenever it changes. A symbol given by the route wins over the
+/// saved one, along with the route's chart state.
+pub fn use_app_state(route_symbol: &str) -> Signal<Instrument> {
+    let restore_symbol = route_symbol.is_empty();
+    let symbol = use_signal(|| match restore_symbol {
+        true => APP_STATE.peek().instrument.clone(),
+        false => Instrument::default(),
+    });
+
+    // Runs once: it only peeks. The chart shows what it last did.
+    use_effect(move || {
+        let state = APP_STATE.peek();
+        if restore_symbol && !state.instrument.is_empty() {
+            *CHART_SELECTION.write() = Some(state.chart.clone());
+        }
+    });
+
+    use_effect(move || {
+        let instrument = symbol();
+        if APP_STATE.peek().instrument != instrument {
+            update(|state| state.instrument = instrument);
+        }
+    });
+
+    symbol
+}
+
+/// Changes the session and saves it.
+pub fn update(f: impl FnOnce(&mut AppState)) {
+    let mut state = APP_STATE.write();
+    f(&mut state);
+    state.store();
+}
+
+/// Changes the layout and saves it.
+pub fn update_layout(f: impl FnOnce(&mut Layout)) {
+    update(|state| f(&mut state.layout));
+}
+
+/// Saves the current layout under `name`, replacing one of the same name.
+pub fn save_workspace(name: &str) {
+    let mut state = APP_STATE.write();
+    let workspace = SavedWorkspace {
+        name: name.to_string(),
+        layout: state.layout.clone(),
+    };
+    match state.workspaces.iter_mut().find(|w| w.name == name) {
+        Some(existing) => *existing = workspace,
+        None => state.workspaces.push(workspace),
+    }
+    state.store();
+}
+
+pub fn load_workspace(name: &str) {
+    let mut state = APP_STATE.write();
+    if let Some(workspace) = state.workspaces.iter().find(|w| w.name == name) {
+        state.layout = workspace.layout.clone();
+        state.store();
+    }
+}
+
+pub fn delete_workspace(name: &str) {
+    let mut state = APP_STATE.write();
+    state.workspaces.retain(|w| w.name != name);
+    state.store();
+}
+
+/// The saved workspace `name` as the contents of a `.finoracle` file.
+pub fn export_workspace(name: &str) -> Option<String> {
+    APP_STATE
+        .read()
+        .workspaces
+        .iter()
+        .find(|w| w.name == name)
+        .and_then(|w| serde_json::to_string_pretty(w).ok())
+}
+
+/// Adds the workspace in a `.finoracle` file, replacing one of the same
+/// name, and switches to it. Returns its name, or why the file was refused.
+pub fn import_workspace(json: &str) -> Result<String, String> {
+    let workspace: SavedWorkspace = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    workspace.layout.validate()?;
+    let name = workspace.name.to_owned();
+    let mut state = APP_STATE.write();
+    state.layout = workspace.layout.clone();
+    match state.workspaces.iter_mut().find(|w| w.name == name) {
+        Some(existing) => *existing = workspace,
+        None => state.workspaces.push(workspace),
+    }
+    state.store();
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_layout_is_valid() {
+        assert_eq!(Layout::default().validate(), Ok(()));
+    }
+
+    #[test]
+    fn layouts_must_place_every_panel_once() {
+        let mut duplicated = Layout::default();
+        duplicated.bottom[0] = Panel::Chart;
+        assert!(duplicated.validate().is_err());
+
+        let mut missing = Layout::default();
+        missing.bottom.pop();
+        assert!(missing.validate().is_err());
+
+        let mut empty = Layout::default();
+        empty.bottom.append(&mut empty.left);
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn saved_refresh_intervals_are_clamped() {
+        let refresh = |json: &str| {
+            let mut settings: Settings = serde_json::from_str(json).unwrap();
+            settings.clamp();
+            settings.quote_refresh_secs
+        };
+        assert_eq!(refresh(r#"{"quote_refresh_secs": 0}"#), 5);
+        assert_eq!(refresh(r#"{"quote_refresh_secs": 86400}"#), 300);
+        assert_eq!(refresh(r#"{"quote_refresh_secs": 42}"#), 42);
+        assert_eq!(refresh("{}"), 15);
+    }
+}